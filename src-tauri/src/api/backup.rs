@@ -0,0 +1,723 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::api::client::{ApiError, OpenListClient};
+use crate::api::types::{Meta, ROLE_ADMIN, ROLE_GUEST};
+use crate::cmd::admin_pass::generate_random_password;
+
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Option names that hold credentials in storage additions and user records,
+/// compared ignoring case and `_`/`-` since drivers spell them differently.
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "token",
+    "access_token",
+    "refresh_token",
+    "cookie",
+    "cookies",
+    "secret",
+    "client_secret",
+    "app_secret",
+    "secret_key",
+    "secret_access_key",
+    "access_key_secret",
+    "api_key",
+    "private_key",
+    "otp_secret",
+    "authorization",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OpenListBackup {
+    pub version: u32,
+    pub exported_at: String,
+    pub secrets_stripped: bool,
+    pub storages: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metas: Option<Vec<Meta>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub users: Option<Vec<Value>>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Keep what is already configured in the core.
+    #[default]
+    Skip,
+    /// Overwrite the existing entry with the imported one.
+    Replace,
+    /// Import storages under a free mount path next to the existing one.
+    Rename,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportFailure {
+    pub kind: String,
+    pub name: String,
+    pub error: String,
+}
+
+/// A user the backup had no password for, created disabled with this one.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct GeneratedPassword {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ImportReport {
+    pub created: Vec<String>,
+    pub replaced: Vec<String>,
+    pub renamed: Vec<(String, String)>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<ImportFailure>,
+    /// Storages and metas a stripped backup had no credentials for, imported
+    /// without them.
+    pub without_secrets: Vec<String>,
+    pub generated_passwords: Vec<GeneratedPassword>,
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let normalize = |key: &str| key.replace(['_', '-'], "").to_ascii_lowercase();
+    let key = normalize(key);
+    SECRET_KEYS.iter().any(|secret| normalize(secret) == key)
+}
+
+fn strip_object_secrets(object: &mut serde_json::Map<String, Value>) {
+    for (key, value) in object.iter_mut() {
+        if is_secret_key(key) && !value.is_null() {
+            *value = Value::String(String::new());
+        }
+    }
+}
+
+/// Blanks credentials in a storage, including the ones inside the `addition`
+/// JSON string OpenList keeps driver options in.
+pub fn strip_storage_secrets(storage: &mut Value) {
+    let Some(object) = storage.as_object_mut() else {
+        return;
+    };
+    strip_object_secrets(object);
+    if let Some(Value::String(addition)) = object.get_mut("addition")
+        && let Ok(Value::Object(mut options)) = serde_json::from_str::<Value>(addition)
+    {
+        strip_object_secrets(&mut options);
+        *addition = Value::Object(options).to_string();
+    }
+}
+
+pub fn strip_user_secrets(user: &mut Value) {
+    if let Some(object) = user.as_object_mut() {
+        strip_object_secrets(object);
+        object.remove("sso_id");
+    }
+}
+
+/// Fills the credentials a stripped backup blanked with the ones `current`
+/// holds.
+fn fill_object_secrets(
+    object: &mut serde_json::Map<String, Value>,
+    current: &serde_json::Map<String, Value>,
+) {
+    for (key, value) in object.iter_mut() {
+        if is_secret_key(key)
+            && value.as_str() == Some("")
+            && let Some(kept) = current.get(key)
+        {
+            *value = kept.clone();
+        }
+    }
+}
+
+fn addition_options(storage: &Value) -> Option<serde_json::Map<String, Value>> {
+    match serde_json::from_str(storage.get("addition")?.as_str()?) {
+        Ok(Value::Object(options)) => Some(options),
+        _ => None,
+    }
+}
+
+/// Keeps the existing storage's credentials where a stripped backup has
+/// blanks, so replacing a storage doesn't log it out.
+pub fn restore_storage_secrets(storage: &mut Value, current: &Value) {
+    let (Some(object), Some(current_object)) = (storage.as_object_mut(), current.as_object())
+    else {
+        return;
+    };
+    fill_object_secrets(object, current_object);
+    if let (Some(mut options), Some(current_options)) =
+        (addition_options(storage), addition_options(current))
+    {
+        fill_object_secrets(&mut options, &current_options);
+        storage["addition"] = Value::String(Value::Object(options).to_string());
+    }
+}
+
+fn has_blank_secrets(storage: &Value) -> bool {
+    let blank = |object: &serde_json::Map<String, Value>| {
+        object
+            .iter()
+            .any(|(key, value)| is_secret_key(key) && value.as_str() == Some(""))
+    };
+    storage.as_object().is_some_and(blank) || addition_options(storage).is_some_and(|o| blank(&o))
+}
+
+/// Removes the fields the target core assigns itself.
+fn clean_for_create(storage: &mut Value) {
+    if let Some(object) = storage.as_object_mut() {
+        for key in ["id", "status", "modified"] {
+            object.remove(key);
+        }
+    }
+}
+
+fn mount_path(storage: &Value) -> String {
+    storage
+        .get("mount_path")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn id_of(value: &Value) -> Option<u64> {
+    value.get("id").and_then(Value::as_u64)
+}
+
+pub fn free_mount_path(mount_path: &str, taken: &HashSet<String>) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => format!("{mount_path} (imported)"),
+            n => format!("{mount_path} (imported {n})"),
+        })
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_default()
+}
+
+pub async fn export(
+    client: &OpenListClient,
+    include_metas: bool,
+    include_users: bool,
+    strip_secrets: bool,
+) -> Result<OpenListBackup, ApiError> {
+    let mut storages = client.list_storages_raw().await?.content;
+    let mut metas = if include_metas {
+        Some(client.list_metas().await?.content)
+    } else {
+        None
+    };
+    let mut users = if include_users {
+        Some(client.list_users_raw().await?.content)
+    } else {
+        None
+    };
+
+    if strip_secrets {
+        storages.iter_mut().for_each(strip_storage_secrets);
+        for meta in metas.iter_mut().flatten() {
+            meta.password.clear();
+        }
+        users.iter_mut().flatten().for_each(strip_user_secrets);
+    }
+
+    Ok(OpenListBackup {
+        version: BACKUP_FORMAT_VERSION,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        secrets_stripped: strip_secrets,
+        storages,
+        metas,
+        users,
+    })
+}
+
+pub async fn import(
+    client: &OpenListClient,
+    backup: OpenListBackup,
+    policy: ConflictPolicy,
+) -> Result<ImportReport, ApiError> {
+    if backup.version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format version {} is newer than this app supports",
+            backup.version
+        )
+        .into());
+    }
+    let mut report = ImportReport::default();
+
+    let stripped = backup.secrets_stripped;
+    let existing = client.list_storages_raw().await?.content;
+    let mut taken: HashSet<String> = existing.iter().map(mount_path).collect();
+    for mut storage in backup.storages {
+        let path = mount_path(&storage);
+        clean_for_create(&mut storage);
+        let current = existing.iter().find(|s| mount_path(s) == path);
+        let missing_secrets = stripped
+            && has_blank_secrets(&storage)
+            && !matches!(
+                (current, policy),
+                (Some(_), ConflictPolicy::Replace | ConflictPolicy::Skip)
+            );
+        let result = match (current, policy) {
+            (None, _) => client.create_storage(&storage).await.map(|_| {
+                taken.insert(path.clone());
+                report.created.push(path.clone());
+            }),
+            (Some(_), ConflictPolicy::Skip) => {
+                report.skipped.push(path.clone());
+                Ok(())
+            }
+            (Some(current), ConflictPolicy::Replace) => {
+                if stripped {
+                    restore_storage_secrets(&mut storage, current);
+                }
+                storage["id"] = Value::from(id_of(current).unwrap_or_default());
+                client
+                    .update_storage(&storage)
+                    .await
+                    .map(|_| report.replaced.push(path.clone()))
+            }
+            (Some(_), ConflictPolicy::Rename) => {
+                let renamed = free_mount_path(&path, &taken);
+                storage["mount_path"] = Value::String(renamed.clone());
+                client.create_storage(&storage).await.map(|_| {
+                    taken.insert(renamed.clone());
+                    report.renamed.push((path.clone(), renamed));
+                })
+            }
+        };
+        match result {
+            Ok(()) if missing_secrets => report.without_secrets.push(path),
+            Ok(()) => {}
+            Err(e) => report.failed.push(ImportFailure {
+                kind: "storage".into(),
+                name: path,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    if let Some(metas) = backup.metas {
+        let existing = client.list_metas().await?.content;
+        for mut meta in metas {
+            let current = existing.iter().find(|m| m.path == meta.path);
+            // A stripped backup can't tell which metas had a password.
+            let blank_password = stripped && meta.password.is_empty();
+            let result = match current {
+                None => {
+                    meta.id = None;
+                    client.create_meta(&meta).await.map(|_| {
+                        report.created.push(format!("meta {}", meta.path));
+                        if blank_password {
+                            report.without_secrets.push(format!("meta {}", meta.path));
+                        }
+                    })
+                }
+                Some(current) if policy == ConflictPolicy::Replace => {
+                    meta.id = current.id;
+                    if blank_password {
+                        meta.password = current.password.clone();
+                    }
+                    client
+                        .update_meta(&meta)
+                        .await
+                        .map(|_| report.replaced.push(format!("meta {}", meta.path)))
+                }
+                Some(_) => {
+                    report.skipped.push(format!("meta {}", meta.path));
+                    Ok(())
+                }
+            };
+            if let Err(e) = result {
+                report.failed.push(ImportFailure {
+                    kind: "meta".into(),
+                    name: meta.path,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    if let Some(users) = backup.users {
+        let existing = client.list_users_raw().await?.content;
+        for mut user in users {
+            let name = user
+                .get("username")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            // Every core already has its own admin and guest accounts.
+            let role = user.get("role").and_then(Value::as_i64);
+            if matches!(role, Some(ROLE_ADMIN | ROLE_GUEST)) {
+                report.skipped.push(format!("user {name}"));
+                continue;
+            }
+            let current = existing
+                .iter()
+                .find(|u| u.get("username").and_then(Value::as_str) == Some(name.as_str()));
+            // Cores don't return user passwords, so exports usually carry
+            // none, stripped or not.
+            let blank_password = user
+                .get("password")
+                .and_then(Value::as_str)
+                .is_none_or(str::is_empty);
+            let result = match current {
+                None => {
+                    if let Some(object) = user.as_object_mut() {
+                        object.remove("id");
+                    }
+                    // A blank password would leave an account anyone can log
+                    // in to, so it stays disabled until an admin hands the
+                    // generated one out.
+                    let generated = blank_password.then(|| generate_random_password(16));
+                    if let Some(password) = &generated {
+                        user["password"] = Value::String(password.clone());
+                        user["disabled"] = Value::Bool(true);
+                    }
+                    client.create_user(&user).await.map(|_| {
+                        report.created.push(format!("user {name}"));
+                        if let Some(password) = generated {
+                            report.generated_passwords.push(GeneratedPassword {
+                                username: name.clone(),
+                                password,
+                            });
+                        }
+                    })
+                }
+                Some(current) if policy == ConflictPolicy::Replace => {
+                    user["id"] = Value::from(id_of(current).unwrap_or_default());
+                    if blank_password {
+                        // An empty password tells the core to keep the current one.
+                        user["password"] = Value::String(String::new());
+                    }
+                    client
+                        .update_user(&user)
+                        .await
+                        .map(|_| report.replaced.push(format!("user {name}")))
+                }
+                Some(_) => {
+                    report.skipped.push(format!("user {name}"));
+                    Ok(())
+                }
+            };
+            if let Err(e) = result {
+                report.failed.push(ImportFailure {
+                    kind: "user".into(),
+                    name,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use mockito::{Matcher, Mock, Server, ServerGuard};
+    use serde_json::{Value, json};
+
+    use super::{
+        BACKUP_FORMAT_VERSION, ConflictPolicy, OpenListBackup, free_mount_path, import,
+        restore_storage_secrets, strip_storage_secrets, strip_user_secrets,
+    };
+    use crate::api::client::{Credentials, OpenListClient};
+    use crate::api::types::Meta;
+
+    fn ok(data: Value) -> String {
+        json!({ "code": 200, "message": "success", "data": data }).to_string()
+    }
+
+    /// A core with one storage at `/team`, a password protected meta on it
+    /// and a user `bob`.
+    async fn core() -> (ServerGuard, OpenListClient) {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/auth/login")
+            .with_body(ok(json!({ "token": "jwt" })))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/admin/storage/list")
+            .match_query(Matcher::Any)
+            .with_body(ok(json!({
+                "content": [{
+                    "id": 3,
+                    "mount_path": "/team",
+                    "driver": "WebDav",
+                    "addition": json!({ "address": "https://dav", "password": "stored" }).to_string(),
+                }],
+                "total": 1
+            })))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/admin/meta/list")
+            .match_query(Matcher::Any)
+            .with_body(ok(json!({
+                "content": [{ "id": 4, "path": "/team", "password": "door", "p_sub": true }],
+                "total": 1
+            })))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/admin/user/list")
+            .match_query(Matcher::Any)
+            .with_body(ok(json!({
+                "content": [{ "id": 5, "username": "bob", "role": 0, "password": "" }],
+                "total": 1
+            })))
+            .create_async()
+            .await;
+        let client = OpenListClient::new(
+            &server.url(),
+            Some(Credentials {
+                username: "admin".into(),
+                password: "secret".into(),
+            }),
+            false,
+        )
+        .unwrap();
+        (server, client)
+    }
+
+    fn backup(stripped: bool, password: &str) -> OpenListBackup {
+        OpenListBackup {
+            version: BACKUP_FORMAT_VERSION,
+            exported_at: String::new(),
+            secrets_stripped: stripped,
+            storages: vec![json!({
+                "id": 1,
+                "mount_path": "/team",
+                "driver": "WebDav",
+                "addition": json!({ "address": "https://new-dav", "password": password }).to_string(),
+            })],
+            metas: Some(vec![
+                Meta {
+                    path: "/team".into(),
+                    password: password.into(),
+                    p_sub: true,
+                    ..Default::default()
+                },
+                Meta {
+                    path: "/private".into(),
+                    password: password.into(),
+                    ..Default::default()
+                },
+            ]),
+            users: Some(vec![
+                json!({ "username": "bob", "role": 0, "password": "", "base_path": "/team" }),
+                json!({ "username": "carol", "role": 0, "password": "" }),
+            ]),
+        }
+    }
+
+    async fn expect(server: &mut ServerGuard, path: &str, body: Value, hits: usize) -> Mock {
+        server
+            .mock("POST", path)
+            .match_body(Matcher::PartialJson(body))
+            .with_body(ok(Value::Null))
+            .expect(hits)
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn skip_leaves_existing_entries_alone() {
+        let (mut server, client) = core().await;
+        let update = expect(&mut server, "/api/admin/storage/update", json!({}), 0).await;
+        let create_meta = expect(
+            &mut server,
+            "/api/admin/meta/create",
+            json!({ "path": "/private", "password": "door2" }),
+            1,
+        )
+        .await;
+        let create_user = expect(
+            &mut server,
+            "/api/admin/user/create",
+            json!({ "username": "carol", "disabled": true }),
+            1,
+        )
+        .await;
+
+        let report = import(&client, backup(false, "door2"), ConflictPolicy::Skip)
+            .await
+            .unwrap();
+        update.assert_async().await;
+        create_meta.assert_async().await;
+        create_user.assert_async().await;
+        assert_eq!(report.skipped, vec!["/team", "meta /team", "user bob"]);
+        assert_eq!(report.created, vec!["meta /private", "user carol"]);
+        assert!(report.without_secrets.is_empty());
+        // Carol had no password, so she gets a generated one to hand out.
+        assert_eq!(report.generated_passwords.len(), 1);
+        assert_eq!(report.generated_passwords[0].username, "carol");
+        assert_eq!(report.generated_passwords[0].password.len(), 16);
+    }
+
+    #[tokio::test]
+    async fn replace_overwrites_with_the_backup() {
+        let (mut server, client) = core().await;
+        let update = expect(
+            &mut server,
+            "/api/admin/storage/update",
+            json!({
+                "id": 3,
+                "addition": json!({ "address": "https://new-dav", "password": "new" }).to_string(),
+            }),
+            1,
+        )
+        .await;
+        let update_meta = expect(
+            &mut server,
+            "/api/admin/meta/update",
+            json!({ "id": 4, "password": "new" }),
+            1,
+        )
+        .await;
+        server
+            .mock("POST", "/api/admin/meta/create")
+            .with_body(ok(Value::Null))
+            .create_async()
+            .await;
+        let update_user = expect(
+            &mut server,
+            "/api/admin/user/update",
+            json!({ "id": 5, "base_path": "/team" }),
+            1,
+        )
+        .await;
+        let create_user = expect(
+            &mut server,
+            "/api/admin/user/create",
+            json!({ "username": "carol", "disabled": true }),
+            1,
+        )
+        .await;
+
+        let report = import(&client, backup(false, "new"), ConflictPolicy::Replace)
+            .await
+            .unwrap();
+        update.assert_async().await;
+        update_meta.assert_async().await;
+        update_user.assert_async().await;
+        create_user.assert_async().await;
+        assert_eq!(report.replaced, vec!["/team", "meta /team", "user bob"]);
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+    }
+
+    #[tokio::test]
+    async fn replacing_from_a_stripped_backup_keeps_stored_secrets() {
+        let (mut server, client) = core().await;
+        let update = expect(
+            &mut server,
+            "/api/admin/storage/update",
+            json!({
+                "id": 3,
+                "addition": json!({ "address": "https://new-dav", "password": "stored" }).to_string(),
+            }),
+            1,
+        )
+        .await;
+        let update_meta = expect(
+            &mut server,
+            "/api/admin/meta/update",
+            json!({ "id": 4, "password": "door" }),
+            1,
+        )
+        .await;
+        let create_meta = expect(
+            &mut server,
+            "/api/admin/meta/create",
+            json!({ "path": "/private", "password": "" }),
+            1,
+        )
+        .await;
+        let update_user = expect(
+            &mut server,
+            "/api/admin/user/update",
+            json!({ "id": 5, "password": "" }),
+            1,
+        )
+        .await;
+        let create_user = expect(
+            &mut server,
+            "/api/admin/user/create",
+            json!({ "username": "carol", "disabled": true }),
+            1,
+        )
+        .await;
+
+        let report = import(&client, backup(true, ""), ConflictPolicy::Replace)
+            .await
+            .unwrap();
+        update.assert_async().await;
+        update_meta.assert_async().await;
+        create_meta.assert_async().await;
+        update_user.assert_async().await;
+        create_user.assert_async().await;
+        assert_eq!(report.without_secrets, vec!["meta /private"]);
+        assert_eq!(report.created, vec!["meta /private", "user carol"]);
+    }
+
+    #[test]
+    fn restores_blanked_storage_secrets() {
+        let current = json!({
+            "token": "t-1",
+            "addition": json!({ "refresh_token": "r-1", "root": "a" }).to_string(),
+        });
+        let mut storage = json!({
+            "token": "",
+            "addition": json!({ "refresh_token": "", "root": "b" }).to_string(),
+        });
+        restore_storage_secrets(&mut storage, &current);
+        let addition: Value = serde_json::from_str(storage["addition"].as_str().unwrap()).unwrap();
+        assert_eq!(storage["token"], "t-1");
+        assert_eq!(addition, json!({ "refresh_token": "r-1", "root": "b" }));
+    }
+
+    #[test]
+    fn strips_secrets_inside_storage_additions() {
+        let mut storage = json!({
+            "mount_path": "/aliyun",
+            "driver": "AliyundriveOpen",
+            "addition": json!({
+                "refresh_token": "r-123",
+                "root_folder_id": "root",
+                "AccessToken": "a-456"
+            })
+            .to_string(),
+        });
+        strip_storage_secrets(&mut storage);
+
+        let addition: serde_json::Value =
+            serde_json::from_str(storage["addition"].as_str().unwrap()).unwrap();
+        assert_eq!(addition["refresh_token"], "");
+        assert_eq!(addition["AccessToken"], "");
+        assert_eq!(addition["root_folder_id"], "root");
+        assert_eq!(storage["mount_path"], "/aliyun");
+    }
+
+    #[test]
+    fn strips_user_passwords() {
+        let mut user = json!({ "username": "alice", "password": "hunter2", "sso_id": "x" });
+        strip_user_secrets(&mut user);
+        assert_eq!(user, json!({ "username": "alice", "password": "" }));
+    }
+
+    #[test]
+    fn picks_a_free_mount_path() {
+        let taken: HashSet<String> = ["/team", "/team (imported)"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(free_mount_path("/team", &taken), "/team (imported 2)");
+        assert_eq!(free_mount_path("/docs", &taken), "/docs (imported)");
+    }
+}
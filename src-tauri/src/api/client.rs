@@ -0,0 +1,731 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::api::types::{
+    ApiResponse, CreatedId, CreatedShare, FsGet, FsGetRequest, FsList, FsListRequest, LoginData,
+    Meta, NewShare, Page, SettingItem, Storage, TaskInfo, TaskKind, UserInfo,
+};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Uploads and downloads can take hours, so they only time out when stalled.
+const TRANSFER_READ_TIMEOUT: Duration = Duration::from_secs(60);
+/// OpenList issues tokens valid for 48 hours by default; refresh well before that.
+const TOKEN_MAX_AGE: Duration = Duration::from_secs(12 * 60 * 60);
+const CODE_UNAUTHORIZED: i64 = 401;
+
+#[derive(Debug, Serialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ApiError {
+    #[error("{message}")]
+    Config { message: String },
+    #[error("{message}")]
+    MissingCredentials { message: String },
+    #[error("{message}")]
+    Request { message: String },
+    #[error("{message}")]
+    Unauthorized { message: String },
+    #[error("{message}")]
+    Api { message: String, code: i64 },
+    #[error("{message}")]
+    Decode { message: String },
+}
+
+impl From<ApiError> for String {
+    fn from(error: ApiError) -> Self {
+        error.to_string()
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::Config { message }
+    }
+}
+
+impl From<&str> for ApiError {
+    fn from(message: &str) -> Self {
+        Self::Config {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Request {
+            message: format!("OpenList API request failed: {error}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug)]
+struct CachedToken {
+    token: String,
+    obtained_at: Instant,
+}
+
+#[derive(Debug)]
+struct ClientInner {
+    http: reqwest::Client,
+    transfer_http: reqwest::Client,
+    base_url: String,
+    credentials: Option<Credentials>,
+    token: Mutex<Option<CachedToken>>,
+}
+
+/// Authenticated client for the OpenList REST API. Clones share the cached token.
+#[derive(Debug, Clone)]
+pub struct OpenListClient {
+    inner: Arc<ClientInner>,
+}
+
+impl OpenListClient {
+    pub fn new(
+        base_url: &str,
+        credentials: Option<Credentials>,
+        accept_invalid_certs: bool,
+    ) -> Result<Self, ApiError> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .tls_danger_accept_invalid_certs(accept_invalid_certs)
+            .build()?;
+        let transfer_http = reqwest::Client::builder()
+            .connect_timeout(REQUEST_TIMEOUT)
+            .read_timeout(TRANSFER_READ_TIMEOUT)
+            .tls_danger_accept_invalid_certs(accept_invalid_certs)
+            .build()?;
+        Ok(Self {
+            inner: Arc::new(ClientInner {
+                http,
+                transfer_http,
+                base_url: base_url.trim_end_matches('/').to_string(),
+                credentials,
+                token: Mutex::new(None),
+            }),
+        })
+    }
+
+    /// Whether this client was built for the same server and account.
+    pub fn is_for(&self, base_url: &str, credentials: Option<&Credentials>) -> bool {
+        self.inner.base_url == base_url.trim_end_matches('/')
+            && self.inner.credentials.as_ref() == credentials
+    }
+
+    /// Whether both clients talk to the same server as the same account.
+    pub fn same_account(&self, other: &Self) -> bool {
+        other.is_for(&self.inner.base_url, self.inner.credentials.as_ref())
+    }
+
+    pub async fn me(&self) -> Result<UserInfo, ApiError> {
+        self.request(Method::GET, "/api/me", &[], None::<&()>).await
+    }
+
+    pub async fn list_storages(&self) -> Result<Page<Storage>, ApiError> {
+        self.list_all("/api/admin/storage/list").await
+    }
+
+    /// Storages exactly as the core returns them, for round-tripping.
+    pub async fn list_storages_raw(&self) -> Result<Page<serde_json::Value>, ApiError> {
+        self.list_all("/api/admin/storage/list").await
+    }
+
+    pub async fn list_metas(&self) -> Result<Page<Meta>, ApiError> {
+        self.list_all("/api/admin/meta/list").await
+    }
+
+    pub async fn list_users_raw(&self) -> Result<Page<serde_json::Value>, ApiError> {
+        self.list_all("/api/admin/user/list").await
+    }
+
+    async fn list_all<T: DeserializeOwned>(&self, path: &str) -> Result<Page<T>, ApiError> {
+        self.request(
+            Method::GET,
+            path,
+            &[("page", "1".into()), ("per_page", "0".into())],
+            None::<&()>,
+        )
+        .await
+    }
+
+    pub async fn enable_storage(&self, id: u64) -> Result<(), ApiError> {
+        self.storage_action("enable", id).await
+    }
+
+    pub async fn disable_storage(&self, id: u64) -> Result<(), ApiError> {
+        self.storage_action("disable", id).await
+    }
+
+    pub async fn delete_storage(&self, id: u64) -> Result<(), ApiError> {
+        self.storage_action("delete", id).await
+    }
+
+    /// Re-initialises a storage's driver by saving it back unchanged. The raw
+    /// JSON is round-tripped so fields this client does not model survive.
+    pub async fn reload_storage(&self, id: u64) -> Result<(), ApiError> {
+        let storage: serde_json::Value = self
+            .request(
+                Method::GET,
+                "/api/admin/storage/get",
+                &[("id", id.to_string())],
+                None::<&()>,
+            )
+            .await?;
+        self.update_storage(&storage).await
+    }
+
+    async fn storage_action(&self, action: &str, id: u64) -> Result<(), ApiError> {
+        self.request(
+            Method::POST,
+            &format!("/api/admin/storage/{action}"),
+            &[("id", id.to_string())],
+            None::<&()>,
+        )
+        .await
+    }
+
+    pub async fn create_storage<S: Serialize + ?Sized>(
+        &self,
+        storage: &S,
+    ) -> Result<u64, ApiError> {
+        let created: CreatedId = self
+            .request(
+                Method::POST,
+                "/api/admin/storage/create",
+                &[],
+                Some(storage),
+            )
+            .await?;
+        Ok(created.id)
+    }
+
+    pub async fn update_storage<S: Serialize + ?Sized>(&self, storage: &S) -> Result<(), ApiError> {
+        self.request(
+            Method::POST,
+            "/api/admin/storage/update",
+            &[],
+            Some(storage),
+        )
+        .await
+    }
+
+    pub async fn create_meta(&self, meta: &Meta) -> Result<(), ApiError> {
+        self.request(Method::POST, "/api/admin/meta/create", &[], Some(meta))
+            .await
+    }
+
+    pub async fn update_meta(&self, meta: &Meta) -> Result<(), ApiError> {
+        self.request(Method::POST, "/api/admin/meta/update", &[], Some(meta))
+            .await
+    }
+
+    pub async fn list_users(&self) -> Result<Page<UserInfo>, ApiError> {
+        self.list_all("/api/admin/user/list").await
+    }
+
+    pub async fn create_user<S: Serialize + ?Sized>(&self, user: &S) -> Result<(), ApiError> {
+        self.request(Method::POST, "/api/admin/user/create", &[], Some(user))
+            .await
+    }
+
+    pub async fn update_user(&self, user: &serde_json::Value) -> Result<(), ApiError> {
+        self.request(Method::POST, "/api/admin/user/update", &[], Some(user))
+            .await
+    }
+
+    pub async fn cancel_user_2fa(&self, id: u64) -> Result<(), ApiError> {
+        self.request(
+            Method::POST,
+            "/api/admin/user/cancel_2fa",
+            &[("id", id.to_string())],
+            None::<&()>,
+        )
+        .await
+    }
+
+    pub async fn list_dir(&self, request: &FsListRequest) -> Result<FsList, ApiError> {
+        self.request(Method::POST, "/api/fs/list", &[], Some(request))
+            .await
+    }
+
+    pub async fn get_file(&self, path: &str) -> Result<FsGet, ApiError> {
+        let request = FsGetRequest {
+            path: path.to_string(),
+            password: String::new(),
+        };
+        self.request(Method::POST, "/api/fs/get", &[], Some(&request))
+            .await
+    }
+
+    /// Creates a share for the given paths and returns its id.
+    pub async fn create_share(&self, share: &NewShare) -> Result<String, ApiError> {
+        self.request::<CreatedShare, _>(Method::POST, "/api/share/create", &[], Some(share))
+            .await
+            .map(|created| created.id)
+    }
+
+    /// Streams `body` to `remote_path` through `fs/put`. The body can't be
+    /// replayed, so a rejected token only clears the cache for the next attempt.
+    pub async fn put_file(
+        &self,
+        remote_path: &str,
+        size: u64,
+        modified_ms: Option<u128>,
+        body: reqwest::Body,
+    ) -> Result<(), ApiError> {
+        let token = self.token(false).await?;
+        let url = format!("{}/api/fs/put", self.inner.base_url);
+        let encoded_path: String =
+            url::form_urlencoded::byte_serialize(remote_path.as_bytes()).collect();
+        let mut request = self
+            .inner
+            .transfer_http
+            .put(url)
+            .header(reqwest::header::AUTHORIZATION, token)
+            .header("File-Path", encoded_path.replace('+', "%20"))
+            .header("As-Task", "false")
+            .header(reqwest::header::CONTENT_LENGTH, size)
+            .body(body);
+        if let Some(modified_ms) = modified_ms {
+            request = request.header("Last-Modified", modified_ms.to_string());
+        }
+        let result = Self::decode::<serde_json::Value>(request.send().await?).await;
+        if let Err(ApiError::Unauthorized { .. }) = &result {
+            *self.inner.token.lock().await = None;
+        }
+        result.map(|_| ())
+    }
+
+    /// Starts downloading a file's raw URL from byte `offset`.
+    pub async fn open_download(
+        &self,
+        raw_url: &str,
+        offset: u64,
+    ) -> Result<reqwest::Response, ApiError> {
+        let mut request = self.inner.transfer_http.get(raw_url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(ApiError::Request {
+                message: format!("Download failed with HTTP {}", response.status()),
+            });
+        }
+        Ok(response)
+    }
+
+    pub async fn list_settings(&self, group: Option<i64>) -> Result<Vec<SettingItem>, ApiError> {
+        let query: Vec<(&str, String)> = group
+            .map(|group| vec![("group", group.to_string())])
+            .unwrap_or_default();
+        self.request(Method::GET, "/api/admin/setting/list", &query, None::<&()>)
+            .await
+    }
+
+    /// Tasks of one queue, either still pending or running, or already finished.
+    pub async fn list_tasks(&self, kind: TaskKind, done: bool) -> Result<Vec<TaskInfo>, ApiError> {
+        let state = if done { "done" } else { "undone" };
+        let path = format!("/api/task/{}/{state}", kind.as_str());
+        self.request::<Option<Vec<TaskInfo>>, ()>(Method::GET, &path, &[], None)
+            .await
+            .map(Option::unwrap_or_default)
+    }
+
+    pub async fn cancel_task(&self, kind: TaskKind, id: &str) -> Result<(), ApiError> {
+        self.task_action(kind, "cancel", id).await
+    }
+
+    pub async fn retry_task(&self, kind: TaskKind, id: &str) -> Result<(), ApiError> {
+        self.task_action(kind, "retry", id).await
+    }
+
+    async fn task_action(&self, kind: TaskKind, action: &str, id: &str) -> Result<(), ApiError> {
+        let path = format!("/api/task/{}/{action}", kind.as_str());
+        self.request(Method::POST, &path, &[("tid", id.to_string())], None::<&()>)
+            .await
+    }
+
+    /// Sends an authenticated request, logging in again once if the cached
+    /// token has been rejected.
+    pub async fn request<T, B>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&B>,
+    ) -> Result<T, ApiError>
+    where
+        T: DeserializeOwned,
+        B: Serialize + ?Sized,
+    {
+        let token = self.token(false).await?;
+        match self.send(method.clone(), path, query, body, &token).await {
+            Err(ApiError::Unauthorized { .. }) => {
+                let token = self.token(true).await?;
+                self.send(method, path, query, body, &token).await
+            }
+            result => result,
+        }
+    }
+
+    async fn send<T, B>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&B>,
+        token: &str,
+    ) -> Result<T, ApiError>
+    where
+        T: DeserializeOwned,
+        B: Serialize + ?Sized,
+    {
+        let mut url = Url::parse(&format!("{}{path}", self.inner.base_url)).map_err(|e| {
+            ApiError::Config {
+                message: format!("Invalid OpenList API URL: {e}"),
+            }
+        })?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        let mut request = self
+            .inner
+            .http
+            .request(method, url)
+            .header(reqwest::header::AUTHORIZATION, token);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        Self::decode(request.send().await?).await
+    }
+
+    async fn decode<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, ApiError> {
+        let status = response.status();
+        let bytes = response.bytes().await?;
+        let envelope: ApiResponse<serde_json::Value> = match serde_json::from_slice(&bytes) {
+            Ok(envelope) => envelope,
+            Err(_) if status == StatusCode::UNAUTHORIZED => {
+                return Err(ApiError::Unauthorized {
+                    message: "OpenList rejected the credentials".into(),
+                });
+            }
+            Err(e) => {
+                return Err(ApiError::Decode {
+                    message: format!("Unexpected OpenList response (HTTP {status}): {e}"),
+                });
+            }
+        };
+
+        if envelope.code == CODE_UNAUTHORIZED || status == StatusCode::UNAUTHORIZED {
+            return Err(ApiError::Unauthorized {
+                message: format!("OpenList rejected the credentials: {}", envelope.message),
+            });
+        }
+        if envelope.code != 200 {
+            return Err(ApiError::Api {
+                message: format!("OpenList API error: {}", envelope.message),
+                code: envelope.code,
+            });
+        }
+        serde_json::from_value(envelope.data.unwrap_or_default()).map_err(|e| ApiError::Decode {
+            message: format!("Failed to decode OpenList response: {e}"),
+        })
+    }
+
+    async fn token(&self, force_refresh: bool) -> Result<String, ApiError> {
+        let mut cached = self.inner.token.lock().await;
+        if !force_refresh
+            && let Some(token) = cached.as_ref()
+            && token.obtained_at.elapsed() < TOKEN_MAX_AGE
+        {
+            return Ok(token.token.clone());
+        }
+
+        let token = self.login().await?;
+        *cached = Some(CachedToken {
+            token: token.clone(),
+            obtained_at: Instant::now(),
+        });
+        Ok(token)
+    }
+
+    async fn login(&self) -> Result<String, ApiError> {
+        let credentials =
+            self.inner
+                .credentials
+                .as_ref()
+                .ok_or_else(|| ApiError::MissingCredentials {
+                    message: "No admin password stored for OpenList, set one in the settings"
+                        .into(),
+                })?;
+        let response = self
+            .inner
+            .http
+            .post(format!("{}/api/auth/login", self.inner.base_url))
+            .json(&serde_json::json!({
+                "username": credentials.username,
+                "password": credentials.password,
+            }))
+            .send()
+            .await?;
+        match Self::decode::<LoginData>(response).await {
+            Ok(data) => Ok(data.token),
+            // A failed login comes back as a plain API error, report it as such.
+            Err(ApiError::Api { message, .. }) => Err(ApiError::Unauthorized { message }),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(test)]
+    async fn set_token(&self, token: &str) {
+        *self.inner.token.lock().await = Some(CachedToken {
+            token: token.into(),
+            obtained_at: Instant::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+    use serde_json::json;
+
+    use super::{ApiError, Credentials, OpenListClient};
+    use crate::api::types::FsListRequest;
+
+    fn client(server: &Server, credentials: bool) -> OpenListClient {
+        let credentials = credentials.then(|| Credentials {
+            username: "admin".into(),
+            password: "secret".into(),
+        });
+        OpenListClient::new(&format!("{}/", server.url()), credentials, false).unwrap()
+    }
+
+    fn ok(data: serde_json::Value) -> String {
+        json!({ "code": 200, "message": "success", "data": data }).to_string()
+    }
+
+    #[tokio::test]
+    async fn logs_in_once_and_reuses_the_token() {
+        let mut server = Server::new_async().await;
+        let login = server
+            .mock("POST", "/api/auth/login")
+            .match_body(Matcher::Json(
+                json!({ "username": "admin", "password": "secret" }),
+            ))
+            .with_body(ok(json!({ "token": "jwt-1" })))
+            .expect(1)
+            .create_async()
+            .await;
+        let me = server
+            .mock("GET", "/api/me")
+            .match_header("authorization", "jwt-1")
+            .with_body(ok(json!({ "id": 1, "username": "admin", "role": 2 })))
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = client(&server, true);
+        assert_eq!(client.me().await.unwrap().username, "admin");
+        assert_eq!(client.clone().me().await.unwrap().id, 1);
+
+        login.assert_async().await;
+        me.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn refreshes_a_rejected_token() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/api/admin/storage/list")
+            .match_header("authorization", "stale")
+            .match_query(Matcher::Any)
+            .with_body(
+                json!({ "code": 401, "message": "token is expired", "data": null }).to_string(),
+            )
+            .create_async()
+            .await;
+        let login = server
+            .mock("POST", "/api/auth/login")
+            .with_body(ok(json!({ "token": "fresh" })))
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/admin/storage/list")
+            .match_header("authorization", "fresh")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("page".into(), "1".into()),
+                Matcher::UrlEncoded("per_page".into(), "0".into()),
+            ]))
+            .with_body(ok(json!({
+                "content": [{
+                    "id": 3,
+                    "mount_path": "/local",
+                    "driver": "Local",
+                    "status": "work",
+                    "disabled": false
+                }],
+                "total": 1
+            })))
+            .create_async()
+            .await;
+
+        let client = client(&server, true);
+        client.set_token("stale").await;
+        let storages = client.list_storages().await.unwrap();
+
+        assert_eq!(storages.total, 1);
+        assert_eq!(storages.content[0].mount_path, "/local");
+        assert_eq!(storages.content[0].status, "work");
+        login.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn decodes_directory_listings_and_settings() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/auth/login")
+            .with_body(ok(json!({ "token": "jwt" })))
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/api/fs/list")
+            .match_body(Matcher::PartialJson(
+                json!({ "path": "/empty", "refresh": true }),
+            ))
+            .with_body(ok(
+                json!({ "content": null, "total": 0, "write": true, "provider": "Local" }),
+            ))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/admin/setting/list")
+            .match_query(Matcher::UrlEncoded("group".into(), "1".into()))
+            .with_body(ok(
+                json!([{ "key": "version", "value": "v4.1.0", "type": "string", "group": 1 }]),
+            ))
+            .create_async()
+            .await;
+
+        let client = client(&server, true);
+        let listing = client
+            .list_dir(&FsListRequest {
+                path: "/empty".into(),
+                refresh: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(listing.content.is_empty());
+        assert!(listing.write);
+
+        let settings = client.list_settings(Some(1)).await.unwrap();
+        assert_eq!(settings[0].key, "version");
+        assert_eq!(settings[0].kind, "string");
+    }
+
+    #[tokio::test]
+    async fn surfaces_login_and_api_errors() {
+        let mut server = Server::new_async().await;
+        assert!(matches!(
+            client(&server, false).me().await,
+            Err(ApiError::MissingCredentials { .. })
+        ));
+
+        server
+            .mock("POST", "/api/auth/login")
+            .with_body(
+                json!({ "code": 400, "message": "password is incorrect", "data": null })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+        match client(&server, true).me().await {
+            Err(ApiError::Unauthorized { message }) => assert!(message.contains("incorrect")),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn manages_storages_by_id() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/auth/login")
+            .with_body(ok(json!({ "token": "jwt" })))
+            .create_async()
+            .await;
+        let disable = server
+            .mock("POST", "/api/admin/storage/disable")
+            .match_query(Matcher::UrlEncoded("id".into(), "7".into()))
+            .with_body(ok(serde_json::Value::Null))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/admin/storage/get")
+            .match_query(Matcher::UrlEncoded("id".into(), "7".into()))
+            .with_body(ok(json!({
+                "id": 7,
+                "mount_path": "/team",
+                "driver": "Local",
+                "proxy_range": true
+            })))
+            .create_async()
+            .await;
+        let update = server
+            .mock("POST", "/api/admin/storage/update")
+            .match_body(Matcher::Json(json!({
+                "id": 7,
+                "mount_path": "/team",
+                "driver": "Local",
+                "proxy_range": true
+            })))
+            .with_body(ok(serde_json::Value::Null))
+            .create_async()
+            .await;
+
+        let client = client(&server, true);
+        client.disable_storage(7).await.unwrap();
+        client.reload_storage(7).await.unwrap();
+
+        disable.assert_async().await;
+        update.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn reports_api_error_codes() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/auth/login")
+            .with_body(ok(json!({ "token": "jwt" })))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/me")
+            .with_body(
+                json!({ "code": 500, "message": "database is locked", "data": null }).to_string(),
+            )
+            .create_async()
+            .await;
+
+        match client(&server, true).me().await {
+            Err(ApiError::Api { code, message }) => {
+                assert_eq!(code, 500);
+                assert!(message.contains("database is locked"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
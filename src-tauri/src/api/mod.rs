@@ -0,0 +1,3 @@
+pub mod backup;
+pub mod client;
+pub mod types;
@@ -0,0 +1,408 @@
+use serde::{Deserialize, Serialize};
+
+/// Envelope every OpenList API response is wrapped in.
+#[derive(Debug, Deserialize)]
+pub struct ApiResponse<T> {
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+    pub data: Option<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Page<T> {
+    #[serde(
+        default = "Vec::new",
+        deserialize_with = "null_as_empty",
+        bound(deserialize = "T: Deserialize<'de>")
+    )]
+    pub content: Vec<T>,
+    #[serde(default)]
+    pub total: u64,
+}
+
+pub const ROLE_GENERAL: i64 = 0;
+pub const ROLE_GUEST: i64 = 1;
+pub const ROLE_ADMIN: i64 = 2;
+
+/// Number of permission bits OpenList defines for users, from "see hidden
+/// files" (bit 0) to "decompress archives" (bit 13).
+pub const USER_PERMISSION_BITS: u32 = 14;
+
+#[derive(Debug, Deserialize)]
+pub struct LoginData {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserInfo {
+    pub id: u64,
+    pub username: String,
+    #[serde(default)]
+    pub base_path: String,
+    #[serde(default)]
+    pub role: i64,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub permission: i64,
+    #[serde(default)]
+    pub otp: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewUser {
+    pub username: String,
+    pub password: String,
+    #[serde(default = "default_base_path")]
+    pub base_path: String,
+    #[serde(default)]
+    pub role: i64,
+    #[serde(default)]
+    pub permission: i64,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+fn default_base_path() -> String {
+    "/".into()
+}
+
+/// Changes to apply to an existing user; unset fields are left alone.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct UserPatch {
+    pub base_path: Option<String>,
+    pub permission: Option<i64>,
+    pub disabled: Option<bool>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Storage {
+    pub id: u64,
+    pub mount_path: String,
+    #[serde(default)]
+    pub order: i64,
+    pub driver: String,
+    #[serde(default)]
+    pub cache_expiration: i64,
+    #[serde(default)]
+    pub status: String,
+    /// Driver specific options, serialized as a JSON string by OpenList.
+    #[serde(default)]
+    pub addition: String,
+    #[serde(default)]
+    pub remark: String,
+    #[serde(default)]
+    pub modified: String,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default)]
+    pub enable_sign: bool,
+    #[serde(default)]
+    pub order_by: String,
+    #[serde(default)]
+    pub order_direction: String,
+    #[serde(default)]
+    pub extract_folder: String,
+    #[serde(default)]
+    pub web_proxy: bool,
+    #[serde(default)]
+    pub webdav_policy: String,
+    #[serde(default)]
+    pub down_proxy_url: String,
+}
+
+/// Body of `/api/admin/storage/create`.
+#[derive(Debug, Serialize, Clone)]
+pub struct NewStorage {
+    pub mount_path: String,
+    pub order: i64,
+    pub driver: String,
+    pub cache_expiration: i64,
+    pub addition: String,
+    pub remark: String,
+    pub disabled: bool,
+    pub enable_sign: bool,
+    pub order_by: String,
+    pub order_direction: String,
+    pub extract_folder: String,
+    pub web_proxy: bool,
+    pub webdav_policy: String,
+    pub down_proxy_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatedId {
+    pub id: u64,
+}
+
+/// Per-path overrides (password, write access, hidden entries) OpenList calls meta.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Meta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    pub path: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub p_sub: bool,
+    #[serde(default)]
+    pub write: bool,
+    #[serde(default)]
+    pub w_sub: bool,
+    #[serde(default)]
+    pub hide: String,
+    #[serde(default)]
+    pub h_sub: bool,
+    #[serde(default)]
+    pub readme: String,
+    #[serde(default)]
+    pub r_sub: bool,
+    #[serde(default)]
+    pub header: String,
+    #[serde(default)]
+    pub header_sub: bool,
+}
+
+/// Status OpenList reports for a storage whose driver initialised correctly.
+pub const STORAGE_STATUS_WORK: &str = "work";
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct StorageSummary {
+    pub id: u64,
+    pub mount_path: String,
+    pub driver: String,
+    pub order: i64,
+    pub remark: String,
+    pub disabled: bool,
+    pub status: String,
+    pub healthy: bool,
+    pub error: Option<String>,
+}
+
+impl From<&Storage> for StorageSummary {
+    fn from(storage: &Storage) -> Self {
+        let healthy = !storage.disabled && storage.status == STORAGE_STATUS_WORK;
+        // Anything other than "work" or "disabled" is the driver's init error.
+        let error = (!storage.disabled
+            && !storage.status.is_empty()
+            && storage.status != STORAGE_STATUS_WORK)
+            .then(|| storage.status.clone());
+        Self {
+            id: storage.id,
+            mount_path: storage.mount_path.clone(),
+            driver: storage.driver.clone(),
+            order: storage.order,
+            remark: storage.remark.clone(),
+            disabled: storage.disabled,
+            status: storage.status.clone(),
+            healthy,
+            error,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FsObject {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    pub is_dir: bool,
+    #[serde(default)]
+    pub modified: String,
+    #[serde(default)]
+    pub sign: String,
+    #[serde(default)]
+    pub thumb: String,
+    #[serde(default, rename = "type")]
+    pub kind: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FsList {
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub content: Vec<FsObject>,
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub readme: String,
+    #[serde(default)]
+    pub write: bool,
+    #[serde(default)]
+    pub provider: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct FsListRequest {
+    pub path: String,
+    pub password: String,
+    pub page: u32,
+    pub per_page: u32,
+    pub refresh: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct FsGetRequest {
+    pub path: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FsGet {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    pub is_dir: bool,
+    #[serde(default)]
+    pub raw_url: String,
+    /// Signature the core expects on `/d` links when signing is enabled.
+    #[serde(default)]
+    pub sign: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct NewShare {
+    pub files: Vec<String>,
+    pub expires: Option<String>,
+    pub pwd: String,
+    pub max_accessed: u32,
+    pub remark: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatedShare {
+    pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SettingItem {
+    pub key: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default)]
+    pub help: String,
+    #[serde(default, rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub options: String,
+    #[serde(default)]
+    pub group: i64,
+    #[serde(default)]
+    pub flag: i64,
+}
+
+/// Task queues of the core, named as in `/api/task/{kind}/...`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Upload,
+    Copy,
+    Move,
+    OfflineDownload,
+    OfflineDownloadTransfer,
+    Decompress,
+    DecompressUpload,
+}
+
+impl TaskKind {
+    pub const ALL: [TaskKind; 7] = [
+        TaskKind::Upload,
+        TaskKind::Copy,
+        TaskKind::Move,
+        TaskKind::OfflineDownload,
+        TaskKind::OfflineDownloadTransfer,
+        TaskKind::Decompress,
+        TaskKind::DecompressUpload,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskKind::Upload => "upload",
+            TaskKind::Copy => "copy",
+            TaskKind::Move => "move",
+            TaskKind::OfflineDownload => "offline_download",
+            TaskKind::OfflineDownloadTransfer => "offline_download_transfer",
+            TaskKind::Decompress => "decompress",
+            TaskKind::DecompressUpload => "decompress_upload",
+        }
+    }
+}
+
+/// Task states as numbered by the core's task manager.
+pub const TASK_STATE_SUCCEEDED: i64 = 2;
+pub const TASK_STATE_FAILED: i64 = 7;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaskInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub creator: String,
+    #[serde(default)]
+    pub state: i64,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub progress: f64,
+    #[serde(default)]
+    pub start_time: Option<String>,
+    #[serde(default)]
+    pub end_time: Option<String>,
+    #[serde(default)]
+    pub total_bytes: i64,
+    #[serde(default)]
+    pub error: String,
+}
+
+impl TaskInfo {
+    pub fn failed(&self) -> bool {
+        self.state == TASK_STATE_FAILED
+            || (self.state != TASK_STATE_SUCCEEDED && !self.error.is_empty())
+    }
+}
+
+/// OpenList sends `null` instead of `[]` for empty directories.
+fn null_as_empty<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Ok(Option::<Vec<T>>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Storage, StorageSummary};
+
+    fn storage(status: &str, disabled: bool) -> Storage {
+        serde_json::from_value(json!({
+            "id": 1,
+            "mount_path": "/drive",
+            "driver": "Aliyundrive",
+            "status": status,
+            "disabled": disabled
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn summarises_storage_health() {
+        let working = StorageSummary::from(&storage("work", false));
+        assert!(working.healthy);
+        assert_eq!(working.error, None);
+
+        let failing = StorageSummary::from(&storage("failed get objs: token expired", false));
+        assert!(!failing.healthy);
+        assert_eq!(
+            failing.error.as_deref(),
+            Some("failed get objs: token expired")
+        );
+
+        let disabled = StorageSummary::from(&storage("disabled", true));
+        assert!(!disabled.healthy);
+        assert_eq!(disabled.error, None);
+    }
+}
@@ -2,12 +2,17 @@ use std::process::Command;
 
 use rand::Rng;
 use rand::distr::Alphanumeric;
+use regex::Regex;
 use tauri::State;
 
+use crate::api::client::OpenListClient;
+use crate::cmd::users::{core_online, set_user_password};
+use crate::conf::config::DEFAULT_CORE_PROFILE;
+use crate::conf::core::OpenListCoreConfig;
 use crate::object::structs::AppState;
-use crate::utils::path::{get_default_openlist_data_dir, get_openlist_binary_path_with_custom};
+use crate::utils::path::{get_default_openlist_data_dir, resolve_openlist_binary_path};
 
-fn generate_random_password(length: usize) -> String {
+pub(crate) fn generate_random_password(length: usize) -> String {
     rand::rng()
         .sample_iter(&Alphanumeric)
         .take(length)
@@ -15,22 +20,38 @@ fn generate_random_password(length: usize) -> String {
         .collect()
 }
 
-async fn execute_openlist_admin_set(
-    password: &str,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    let binary_path = get_openlist_binary_path_with_custom(state.clone())?;
+/// Runs an `openlist` subcommand against a profile's binary and data
+/// directory, returning everything it printed.
+pub(crate) fn run_openlist_cli(
+    config: &OpenListCoreConfig,
+    args: &[&str],
+) -> Result<String, String> {
+    run_openlist_cli_redacted(config, args, None)
+}
+
+/// Masks `secret` in command lines and output written to the log.
+fn redact(text: &str, secret: Option<&str>) -> String {
+    match secret.filter(|secret| !secret.is_empty()) {
+        Some(secret) => text.replace(secret, "********"),
+        None => text.to_string(),
+    }
+}
+
+fn run_openlist_cli_redacted(
+    config: &OpenListCoreConfig,
+    args: &[&str],
+    secret: Option<&str>,
+) -> Result<String, String> {
+    let binary_path = resolve_openlist_binary_path(config.binary_path.as_deref())?;
     let app_dir = binary_path
         .parent()
         .ok_or("Failed to get OpenList binary parent directory")?;
     let mut cmd = Command::new(&binary_path);
-    cmd.args(["admin", "set", password]);
+    cmd.args(args);
     cmd.current_dir(app_dir);
 
-    let effective_data_dir = if let Some(settings) = state.get_settings()
-        && !settings.openlist.data_dir.is_empty()
-    {
-        settings.openlist.data_dir
+    let effective_data_dir = if !config.data_dir.is_empty() {
+        config.data_dir.clone()
     } else {
         get_default_openlist_data_dir()
             .map_err(|e| format!("Failed to get default data directory: {e}"))?
@@ -41,7 +62,11 @@ async fn execute_openlist_admin_set(
     cmd.arg("--data");
     cmd.arg(&effective_data_dir);
     log::info!("Using data directory: {effective_data_dir}");
-    log::info!("Executing command: {cmd:?}");
+    log::info!(
+        "Executing command: {} {}",
+        binary_path.display(),
+        redact(&args.join(" "), secret)
+    );
     #[cfg(windows)]
     {
         use std::os::windows::process::CommandExt;
@@ -51,42 +76,106 @@ async fn execute_openlist_admin_set(
         .output()
         .map_err(|e| format!("Failed to execute openlist command: {e}"))?;
 
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        log::error!("OpenList admin set command failed. stdout: {stdout}, stderr: {stderr}");
-        return Err(format!("OpenList admin set command failed: {stderr}"));
+        let (stdout, stderr) = (redact(&stdout, secret), redact(&stderr, secret));
+        log::error!("OpenList command failed. stdout: {stdout}, stderr: {stderr}");
+        return Err(format!("OpenList command failed: {stderr}"));
     }
+    // Depending on the version, the CLI logs to either stream.
+    Ok(format!("{stdout}{stderr}"))
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    log::info!("Successfully set admin password. Output: {stdout}");
+/// Extracts the password printed by `openlist admin random`, either as a
+/// plain `password: xxx` line or inside a logrus `msg="..."` field.
+pub fn parse_cli_password(output: &str) -> Option<String> {
+    let ansi = Regex::new(r"\x1b\[[0-9;]*m").ok()?;
+    let output = ansi.replace_all(output, "");
+    let (_, rest) = output.rsplit_once("password:")?;
+    let password = rest.lines().next()?.trim().trim_end_matches('"').trim();
+    (!password.is_empty()).then(|| password.to_string())
+}
 
-    Ok(())
+/// Lets the CLI generate a new admin password, so it never shows up in a
+/// process argument list, and returns it.
+pub(crate) fn random_admin_password(config: &OpenListCoreConfig) -> Result<String, String> {
+    let output = run_openlist_cli(config, &["admin", "random"])?;
+    parse_cli_password(&output)
+        .ok_or_else(|| "OpenList did not print the generated admin password".to_string())
 }
 
-async fn internal_update_admin_password(
-    new_pass: String,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let new_password = if new_pass.is_empty() {
-        generate_random_password(16)
-    } else {
-        new_pass
-    };
+/// Sets the admin password with `openlist admin set`, which works on the
+/// data directory while the core is stopped. The CLI only takes it as an
+/// argument, so it is masked in the log.
+fn set_admin_password_cli(config: &OpenListCoreConfig, password: &str) -> Result<(), String> {
+    if config.is_remote() {
+        return Err(
+            "The admin password of a remote OpenList instance can only be changed through its API"
+                .to_string(),
+        );
+    }
+    run_openlist_cli_redacted(config, &["admin", "set", password], Some(password)).map(|_| ())
+}
 
-    execute_openlist_admin_set(&new_password, state.clone())
-        .await
-        .map_err(|e| format!("Failed to set admin password: {e}"))?;
+/// Sets `password` through the admin API when the core answers, and through
+/// the CLI when it's stopped or the API call fails, e.g. because the stored
+/// password the desktop signs in with is already wrong.
+async fn apply_admin_password(
+    client: Option<&OpenListClient>,
+    password: &str,
+    cli: impl FnOnce(&str) -> Result<(), String>,
+) -> Result<(), String> {
+    if let Some(client) = client {
+        match set_user_password(client, None, password).await {
+            Ok(_) => return Ok(()),
+            Err(e) => log::warn!("Failed to set the admin password through the API: {e}"),
+        }
+    }
+    cli(password)
+}
 
+/// Remembers a local profile's admin password, which the desktop uses to
+/// sign in to its core.
+pub(crate) fn store_admin_password(profile: &str, password: &str, state: &AppState) {
     if let Some(mut settings) = state.get_settings() {
-        settings.app.admin_password = Some(new_password.clone());
+        if let Err(e) = settings.set_admin_password(profile, password) {
+            log::warn!("Failed to store the admin password: {e}");
+            return;
+        }
         state.update_settings(settings.clone());
 
         if let Err(e) = settings.save() {
             log::warn!("Failed to save settings to disk: {e}");
         }
     }
+}
 
+/// Generates a new admin password through the CLI, or sets `new_pass`.
+async fn internal_update_admin_password(
+    new_pass: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let new_password = if new_pass.is_empty() {
+        let settings = state.get_settings().ok_or("Failed to read app settings")?;
+        random_admin_password(&settings.openlist)
+            .map_err(|e| format!("Failed to set admin password: {e}"))?
+    } else {
+        let settings = state.get_settings().ok_or("Failed to read app settings")?;
+        let client = if core_online(DEFAULT_CORE_PROFILE, &state).await? {
+            state.api_client(DEFAULT_CORE_PROFILE).ok()
+        } else {
+            None
+        };
+        apply_admin_password(client.as_ref(), &new_pass, |password| {
+            set_admin_password_cli(&settings.openlist, password)
+        })
+        .await
+        .map_err(|e| format!("Failed to set admin password: {e}"))?;
+        new_pass
+    };
+
+    store_admin_password(DEFAULT_CORE_PROFILE, &new_password, &state);
     Ok(new_password)
 }
 
@@ -118,3 +207,74 @@ pub async fn set_admin_password(
     log::info!("Setting custom admin password");
     internal_update_admin_password(password, state.clone()).await
 }
+
+#[cfg(test)]
+mod tests {
+    use mockito::Server;
+    use serde_json::json;
+
+    use super::{apply_admin_password, parse_cli_password, redact};
+    use crate::api::client::{Credentials, OpenListClient};
+
+    #[test]
+    fn reads_the_generated_password() {
+        let plain = "\x1b[36mINFO\x1b[0m admin user has been updated:\nusername: admin\npassword: Ab3dE9xz\n";
+        assert_eq!(parse_cli_password(plain).as_deref(), Some("Ab3dE9xz"));
+
+        let logrus = "time=\"2026-01-01T00:00:00Z\" level=info msg=\"username: admin\"\n\
+                      time=\"2026-01-01T00:00:00Z\" level=info msg=\"password: q1w2e3r4\"\n";
+        assert_eq!(parse_cli_password(logrus).as_deref(), Some("q1w2e3r4"));
+
+        assert_eq!(parse_cli_password("failed to update admin user"), None);
+    }
+
+    #[tokio::test]
+    async fn sets_the_password_through_the_cli_while_offline() {
+        let mut set = None;
+        apply_admin_password(None, "n3w-pass", |password| {
+            set = Some(password.to_string());
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(set.as_deref(), Some("n3w-pass"));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_cli_when_the_login_fails() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/auth/login")
+            .with_body(
+                json!({ "code": 400, "message": "password is incorrect", "data": null })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+        let client = OpenListClient::new(
+            &server.url(),
+            Some(Credentials {
+                username: "admin".into(),
+                password: "stale".into(),
+            }),
+            false,
+        )
+        .unwrap();
+
+        let mut cli_used = false;
+        apply_admin_password(Some(&client), "n3w-pass", |_| {
+            cli_used = true;
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert!(cli_used);
+    }
+
+    #[test]
+    fn masks_the_password_in_logged_commands() {
+        let logged = redact("admin set n3w-pass", Some("n3w-pass"));
+        assert_eq!(logged, "admin set ********");
+        assert_eq!(redact("output", None), "output");
+    }
+}
@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::process::Command;
 
 use tauri::State;
@@ -19,9 +20,11 @@ pub async fn get_binary_version(
         "rclone" => get_rclone_binary_path_with_custom(state),
         other => Err(format!("Unsupported binary name: {}", other)),
     };
-    let binary_path = binary_path?;
+    read_binary_version(&binary_path?)
+}
 
-    let mut cmd = Command::new(&binary_path);
+pub(crate) fn read_binary_version(binary_path: &Path) -> Result<String, String> {
+    let mut cmd = Command::new(binary_path);
     cmd.arg("version");
 
     #[cfg(windows)]
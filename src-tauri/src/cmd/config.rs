@@ -6,19 +6,18 @@ use tauri::State;
 use crate::cmd::openlist_core::{get_openlist_core_process_status, start_openlist_core};
 use crate::conf::config::MergedSettings;
 use crate::object::structs::AppState;
-use crate::utils::path::{app_config_file_path, get_default_openlist_data_dir};
+use crate::utils::path::get_default_openlist_data_dir;
 
 fn write_json_to_file<T: serde::Serialize>(path: PathBuf, value: &T) -> Result<(), String> {
     let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
     fs::write(path, json).map_err(|e| e.to_string())
 }
 
-fn persist_app_settings(settings: &MergedSettings) -> Result<(), String> {
-    let path = app_config_file_path().map_err(|e| e.to_string())?;
-    write_json_to_file(path, settings)
-}
-
-fn update_data_config(port: u16, data_dir: Option<&str>) -> Result<(), String> {
+pub(crate) fn update_data_config(
+    scheme_key: &str,
+    value: serde_json::Value,
+    data_dir: Option<&str>,
+) -> Result<(), String> {
     let data_config_path = if let Some(dir) = data_dir.filter(|d| !d.is_empty()) {
         PathBuf::from(dir).join("config.json")
     } else {
@@ -33,14 +32,14 @@ fn update_data_config(port: u16, data_dir: Option<&str>) -> Result<(), String> {
         let s = fs::read_to_string(&data_config_path).map_err(|e| e.to_string())?;
         serde_json::from_str::<serde_json::Value>(&s).map_err(|e| e.to_string())?
     } else {
-        serde_json::json!({ "scheme": { "http_port": port } })
+        serde_json::json!({ "scheme": { scheme_key: value.clone() } })
     };
 
     let scheme = cfg_value.get_mut("scheme").and_then(|v| v.as_object_mut());
     if let Some(obj) = scheme {
-        obj.insert("http_port".into(), serde_json::json!(port));
+        obj.insert(scheme_key.into(), value);
     } else {
-        cfg_value["scheme"] = serde_json::json!({ "http_port": port });
+        cfg_value["scheme"] = serde_json::json!({ scheme_key: value });
     }
 
     write_json_to_file(data_config_path, &cfg_value)
@@ -58,8 +57,9 @@ pub async fn save_settings(
     state: State<'_, AppState>,
 ) -> Result<bool, String> {
     settings.rclone.normalize_network_mode();
+    settings.validate_core_profiles()?;
     state.update_settings(settings.clone());
-    persist_app_settings(&settings)?;
+    settings.save()?;
     log::info!("Settings saved successfully");
     Ok(true)
 }
@@ -70,18 +70,27 @@ pub async fn save_settings_and_restart(
     state: State<'_, AppState>,
 ) -> Result<bool, String> {
     settings.rclone.normalize_network_mode();
+    settings.validate_core_profiles()?;
     state.update_settings(settings.clone());
-    persist_app_settings(&settings)?;
-    let data_dir = if settings.openlist.data_dir.is_empty() {
-        None
-    } else {
-        Some(settings.openlist.data_dir.as_str())
-    };
-    update_data_config(settings.openlist.port, data_dir)?;
-    if let Ok(info) = get_openlist_core_process_status().await
-        && info.is_running
-    {
-        start_openlist_core(state.clone()).await?;
+    settings.save()?;
+    for profile in settings.core_profile_names() {
+        let config = settings.core_profile(&profile)?;
+        if config.is_remote() {
+            state.core_health.write().remove(&profile);
+            continue;
+        }
+        let data_dir = Some(config.data_dir.as_str()).filter(|d| !d.is_empty());
+        update_data_config("http_port", serde_json::json!(config.port), data_dir)?;
+        update_data_config(
+            "address",
+            serde_json::json!(config.bind_address()?.to_string()),
+            data_dir,
+        )?;
+        if let Ok(info) = get_openlist_core_process_status(&profile).await
+            && info.is_running
+        {
+            start_openlist_core(Some(profile), state.clone()).await?;
+        }
     }
 
     Ok(true)
@@ -91,7 +100,7 @@ pub async fn save_settings_and_restart(
 pub async fn reset_settings(state: State<'_, AppState>) -> Result<Option<MergedSettings>, String> {
     let base_settings = MergedSettings::default();
     state.update_settings(base_settings.clone());
-    persist_app_settings(&base_settings)?;
+    base_settings.save()?;
     log::info!("Settings reset to default");
     Ok(Some(base_settings))
 }
@@ -4,8 +4,14 @@ pub mod config;
 pub mod firewall;
 pub mod logs;
 pub mod macos_dock;
+pub mod network;
+pub mod openlist_api;
 pub mod openlist_core;
 pub mod os_operate;
+pub mod rclone_cache;
 pub mod rclone_core;
 pub mod rclone_mount;
+pub mod share_link;
+pub mod transfers;
 pub mod updater;
+pub mod users;
@@ -0,0 +1,77 @@
+use tauri::State;
+
+use crate::cmd::openlist_core::core_listen_port;
+use crate::conf::core::{BindMode, OpenListCoreConfig};
+use crate::object::structs::{AccessUrl, AppState, NetworkAccessInfo, NetworkWarning};
+use crate::utils::network::{InterfaceAddress, format_url, list_interface_addresses};
+
+#[tauri::command]
+pub async fn list_network_interfaces() -> Result<Vec<InterfaceAddress>, String> {
+    list_interface_addresses()
+}
+
+#[tauri::command]
+pub async fn get_network_access_info(
+    state: State<'_, AppState>,
+) -> Result<NetworkAccessInfo, String> {
+    let settings = state
+        .app_settings
+        .read()
+        .clone()
+        .ok_or("Failed to read app settings")?;
+    network_access_info(&settings.openlist)
+}
+
+pub(crate) fn network_access_info(
+    openlist: &OpenListCoreConfig,
+) -> Result<NetworkAccessInfo, String> {
+    let scheme = if openlist.ssl_enabled {
+        "https"
+    } else {
+        "http"
+    };
+    let bind_address = openlist.bind_address()?;
+    let port = core_listen_port(openlist);
+    let mut warnings = Vec::new();
+
+    let Some(port) = port else {
+        warnings.push(NetworkWarning::PortUnknown);
+        return Ok(NetworkAccessInfo {
+            bind_address: bind_address.to_string(),
+            local_url: None,
+            lan_urls: Vec::new(),
+            warnings,
+        });
+    };
+
+    let lan_urls: Vec<AccessUrl> = list_interface_addresses()?
+        .into_iter()
+        .filter(|addr| !addr.is_loopback)
+        .filter(|addr| match openlist.bind_mode {
+            BindMode::Loopback => false,
+            BindMode::All => true,
+            BindMode::Interface => addr.ip == bind_address,
+        })
+        .map(|addr| AccessUrl {
+            url: format_url(scheme, addr.ip, port),
+            address: addr.ip.to_string(),
+            interface: addr.name,
+        })
+        .collect();
+
+    if openlist.is_exposed() {
+        if !openlist.ssl_enabled {
+            warnings.push(NetworkWarning::PlainHttp);
+        }
+        if lan_urls.is_empty() {
+            warnings.push(NetworkWarning::NoLanAddress);
+        }
+    }
+
+    Ok(NetworkAccessInfo {
+        bind_address: bind_address.to_string(),
+        local_url: Some(format!("{scheme}://{}:{port}", openlist.local_host())),
+        lan_urls,
+        warnings,
+    })
+}
@@ -0,0 +1,482 @@
+use std::path::{Path, PathBuf};
+
+use tauri::State;
+
+use crate::api::backup::{self, ConflictPolicy, ImportReport, OpenListBackup};
+use crate::api::client::{ApiError, OpenListClient};
+use crate::api::types::{
+    FsList, FsListRequest, NewStorage, NewUser, ROLE_GENERAL, SettingItem, StorageSummary,
+    TaskKind, UserInfo,
+};
+use crate::cmd::admin_pass::generate_random_password;
+use crate::cmd::network::network_access_info;
+use crate::conf::config::DEFAULT_CORE_PROFILE;
+use crate::core::task_monitor::{self, MonitoredTask};
+use crate::object::structs::{AppState, SharedFolder, SharedFolderAccount, SharedFolderUrl};
+use crate::utils::network::join_url_path;
+
+pub(crate) fn profile_name(profile: Option<String>) -> String {
+    profile.unwrap_or_else(|| DEFAULT_CORE_PROFILE.to_string())
+}
+
+fn normalize_mount_path(mount_path: &str) -> Result<String, String> {
+    let segments: Vec<&str> = mount_path
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    if segments.is_empty() {
+        return Err("Mount path cannot be the OpenList root".into());
+    }
+    if segments
+        .iter()
+        .any(|segment| matches!(*segment, "." | ".."))
+    {
+        return Err(format!("Invalid mount path '{mount_path}'"));
+    }
+    Ok(format!("/{}", segments.join("/")))
+}
+
+fn local_storage(local_path: &Path, mount_path: &str) -> NewStorage {
+    let addition = serde_json::json!({
+        "root_folder_path": local_path.to_string_lossy(),
+        "thumbnail": false,
+        "thumb_cache_folder": "",
+        "show_hidden": true,
+        "mkdir_perm": "777",
+        "recycle_bin_path": "delete permanently",
+    });
+    NewStorage {
+        mount_path: mount_path.to_string(),
+        order: 0,
+        driver: "Local".into(),
+        cache_expiration: 0,
+        addition: addition.to_string(),
+        remark: "Shared from OpenList Desktop".into(),
+        disabled: false,
+        enable_sign: false,
+        order_by: "name".into(),
+        order_direction: "asc".into(),
+        extract_folder: String::new(),
+        web_proxy: false,
+        webdav_policy: "native_proxy".into(),
+        down_proxy_url: String::new(),
+    }
+}
+
+fn shared_folder_url(
+    interface: Option<String>,
+    base_url: &str,
+    mount_path: &str,
+) -> Result<SharedFolderUrl, String> {
+    Ok(SharedFolderUrl {
+        interface,
+        web_url: join_url_path(base_url, mount_path)?,
+        webdav_url: join_url_path(&join_url_path(base_url, "dav")?, mount_path)?,
+    })
+}
+
+#[tauri::command]
+pub async fn get_openlist_api_user(
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<UserInfo, ApiError> {
+    state.api_client(&profile_name(profile))?.me().await
+}
+
+#[tauri::command]
+pub async fn list_openlist_files(
+    path: String,
+    refresh: Option<bool>,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<FsList, ApiError> {
+    let request = FsListRequest {
+        path,
+        refresh: refresh.unwrap_or(false),
+        ..Default::default()
+    };
+    state
+        .api_client(&profile_name(profile))?
+        .list_dir(&request)
+        .await
+}
+
+#[tauri::command]
+pub async fn list_openlist_settings(
+    group: Option<i64>,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SettingItem>, ApiError> {
+    state
+        .api_client(&profile_name(profile))?
+        .list_settings(group)
+        .await
+}
+
+#[tauri::command]
+pub async fn list_openlist_storages(
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<StorageSummary>, ApiError> {
+    let mut storages: Vec<StorageSummary> = state
+        .api_client(&profile_name(profile))?
+        .list_storages()
+        .await?
+        .content
+        .iter()
+        .map(StorageSummary::from)
+        .collect();
+    storages.sort_by_key(|storage| (storage.order, storage.id));
+    Ok(storages)
+}
+
+#[tauri::command]
+pub async fn set_openlist_storage_enabled(
+    id: u64,
+    enabled: bool,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let client = state.api_client(&profile_name(profile))?;
+    if enabled {
+        client.enable_storage(id).await
+    } else {
+        client.disable_storage(id).await
+    }
+}
+
+#[tauri::command]
+pub async fn reload_openlist_storage(
+    id: u64,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), ApiError> {
+    state
+        .api_client(&profile_name(profile))?
+        .reload_storage(id)
+        .await
+}
+
+#[tauri::command]
+pub async fn delete_openlist_storage(
+    id: u64,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), ApiError> {
+    state
+        .api_client(&profile_name(profile))?
+        .delete_storage(id)
+        .await
+}
+
+/// Permissions of a share account: WebDAV read, plus upload, rename, move,
+/// copy, delete and WebDAV manage when it may write.
+fn share_permission(read_only: bool) -> i64 {
+    const WEBDAV_READ: i64 = 1 << 8;
+    const WRITE: i64 = (1 << 3) | (1 << 4) | (1 << 5) | (1 << 6) | (1 << 7) | (1 << 9);
+    if read_only {
+        WEBDAV_READ
+    } else {
+        WEBDAV_READ | WRITE
+    }
+}
+
+/// Whether to create a share account, and if so whether it's read-only.
+/// Without an account only admins can reach the storage, so `read_only`
+/// can't be honoured and is rejected instead of ignored.
+fn share_account_access(
+    read_only: Option<bool>,
+    share_account: Option<bool>,
+) -> Result<Option<bool>, String> {
+    if share_account.unwrap_or(false) {
+        return Ok(Some(read_only.unwrap_or(true)));
+    }
+    if read_only.is_some() {
+        return Err(
+            "Read-only access applies to a share account, enable share_account to use it"
+                .to_string(),
+        );
+    }
+    Ok(None)
+}
+
+/// Creates the storage and, if asked, an account confined to it. The storage
+/// is deleted again when the account can't be created.
+async fn publish_folder(
+    client: &OpenListClient,
+    local: &Path,
+    mount_path: &str,
+    account: Option<bool>,
+) -> Result<(u64, Option<SharedFolderAccount>), ApiError> {
+    let storage_id = client
+        .create_storage(&local_storage(local, mount_path))
+        .await?;
+    let Some(read_only) = account else {
+        return Ok((storage_id, None));
+    };
+
+    let account = SharedFolderAccount {
+        username: format!("share-{}", generate_random_password(8).to_lowercase()),
+        password: generate_random_password(20),
+    };
+    let user = NewUser {
+        username: account.username.clone(),
+        password: account.password.clone(),
+        base_path: mount_path.to_string(),
+        role: ROLE_GENERAL,
+        permission: share_permission(read_only),
+        disabled: false,
+    };
+    if let Err(e) = client.create_user(&user).await {
+        if let Err(delete_error) = client.delete_storage(storage_id).await {
+            log::error!(
+                "Failed to remove storage {storage_id} after a failed share: {delete_error}"
+            );
+        }
+        return Err(e);
+    }
+    Ok((storage_id, Some(account)))
+}
+
+/// Publishes a local directory as a `Local` storage of the profile's core.
+/// Only admins can reach it unless `share_account` is set, which creates an
+/// account whose root is the new storage; `read_only` limits that account to
+/// reading and is an error without one.
+#[tauri::command]
+pub async fn share_local_folder(
+    local_path: String,
+    mount_path: String,
+    read_only: Option<bool>,
+    share_account: Option<bool>,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SharedFolder, ApiError> {
+    let profile = profile_name(profile);
+    let settings = state.get_settings().ok_or("Failed to read app settings")?;
+    let config = settings.core_profile(&profile)?;
+    if config.is_remote() {
+        return Err("Local folders can only be shared through a local OpenList Core".into());
+    }
+    let local = PathBuf::from(&local_path);
+    if !local.is_dir() {
+        return Err(format!("'{local_path}' is not a directory").into());
+    }
+    let mount_path = normalize_mount_path(&mount_path)?;
+    let account = share_account_access(read_only, share_account)?;
+    let access = network_access_info(config)?;
+
+    let client = state.api_client(&profile)?;
+    let (storage_id, account) = publish_folder(&client, &local, &mount_path, account).await?;
+    log::info!("Shared '{local_path}' as OpenList storage {storage_id} at {mount_path}");
+
+    let mut urls = Vec::new();
+    if let Some(local_url) = &access.local_url {
+        urls.push(shared_folder_url(None, local_url, &mount_path)?);
+    }
+    for lan in &access.lan_urls {
+        urls.push(shared_folder_url(
+            Some(lan.interface.clone()),
+            &lan.url,
+            &mount_path,
+        )?);
+    }
+
+    Ok(SharedFolder {
+        storage_id,
+        mount_path,
+        local_path,
+        urls,
+        account,
+    })
+}
+
+/// Writes the profile's storages, and optionally its metas and users, to a
+/// JSON file that `import_openlist_config` can restore on another core.
+#[tauri::command]
+pub async fn export_openlist_config(
+    path: String,
+    include_metas: Option<bool>,
+    include_users: Option<bool>,
+    strip_secrets: Option<bool>,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<usize, ApiError> {
+    let client = state.api_client(&profile_name(profile))?;
+    let export = backup::export(
+        &client,
+        include_metas.unwrap_or(false),
+        include_users.unwrap_or(false),
+        strip_secrets.unwrap_or(true),
+    )
+    .await?;
+    let json = serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize export: {e}"))?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write '{path}': {e}"))?;
+    log::info!(
+        "Exported {} OpenList storages to {path}",
+        export.storages.len()
+    );
+    Ok(export.storages.len())
+}
+
+#[tauri::command]
+pub async fn import_openlist_config(
+    path: String,
+    conflict: Option<ConflictPolicy>,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ImportReport, ApiError> {
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read '{path}': {e}"))?;
+    let export: OpenListBackup = serde_json::from_str(&content)
+        .map_err(|e| format!("'{path}' is not an OpenList Desktop export: {e}"))?;
+    let client = state.api_client(&profile_name(profile))?;
+    let report = backup::import(&client, export, conflict.unwrap_or_default()).await?;
+    log::info!(
+        "Imported OpenList config from {path}: {} created, {} replaced, {} renamed, {} skipped, {} failed",
+        report.created.len(),
+        report.replaced.len(),
+        report.renamed.len(),
+        report.skipped.len(),
+        report.failed.len()
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn list_openlist_tasks(
+    done: Option<bool>,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<MonitoredTask>, ApiError> {
+    let client = state.api_client(&profile_name(profile))?;
+    task_monitor::list_tasks(&client, done.unwrap_or(false)).await
+}
+
+#[tauri::command]
+pub async fn cancel_openlist_task(
+    kind: TaskKind,
+    id: String,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), ApiError> {
+    state
+        .api_client(&profile_name(profile))?
+        .cancel_task(kind, &id)
+        .await
+}
+
+#[tauri::command]
+pub async fn retry_openlist_task(
+    kind: TaskKind,
+    id: String,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), ApiError> {
+    state
+        .api_client(&profile_name(profile))?
+        .retry_task(kind, &id)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use mockito::{Matcher, Server};
+    use serde_json::json;
+
+    use super::{
+        normalize_mount_path, publish_folder, share_account_access, share_permission,
+        shared_folder_url,
+    };
+    use crate::api::client::{Credentials, OpenListClient};
+
+    #[test]
+    fn normalizes_mount_paths() {
+        assert_eq!(normalize_mount_path("share").unwrap(), "/share");
+        assert_eq!(
+            normalize_mount_path(" /team/ docs/ ").unwrap(),
+            "/team/docs"
+        );
+        assert_eq!(normalize_mount_path("\\team\\docs").unwrap(), "/team/docs");
+        assert!(normalize_mount_path("/").is_err());
+        assert!(normalize_mount_path("/team/../etc").is_err());
+    }
+
+    #[test]
+    fn builds_web_and_webdav_urls() {
+        let url = shared_folder_url(None, "http://localhost:5244", "/Team Files").unwrap();
+        assert_eq!(url.web_url, "http://localhost:5244/Team%20Files");
+        assert_eq!(url.webdav_url, "http://localhost:5244/dav/Team%20Files");
+    }
+
+    #[test]
+    fn read_only_needs_a_share_account() {
+        assert_eq!(share_account_access(None, None), Ok(None));
+        assert_eq!(share_account_access(None, Some(true)), Ok(Some(true)));
+        assert_eq!(
+            share_account_access(Some(false), Some(true)),
+            Ok(Some(false))
+        );
+        assert!(share_account_access(Some(true), None).is_err());
+        assert!(share_account_access(Some(true), Some(false)).is_err());
+    }
+
+    #[tokio::test]
+    async fn confines_the_share_account_and_cleans_up_on_failure() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/auth/login")
+            .with_body(json!({ "code": 200, "data": { "token": "jwt" } }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/api/admin/storage/create")
+            .with_body(json!({ "code": 200, "data": { "id": 9 } }).to_string())
+            .create_async()
+            .await;
+        let create_user = server
+            .mock("POST", "/api/admin/user/create")
+            .match_body(Matcher::PartialJson(json!({
+                "base_path": "/photos",
+                "role": 0,
+                "permission": share_permission(true),
+            })))
+            .with_body(json!({ "code": 500, "message": "user exists" }).to_string())
+            .create_async()
+            .await;
+        let delete = server
+            .mock("POST", "/api/admin/storage/delete")
+            .match_query(Matcher::UrlEncoded("id".into(), "9".into()))
+            .with_body(json!({ "code": 200 }).to_string())
+            .create_async()
+            .await;
+
+        let client = OpenListClient::new(
+            &server.url(),
+            Some(Credentials {
+                username: "admin".into(),
+                password: "secret".into(),
+            }),
+            false,
+        )
+        .unwrap();
+        let error = publish_folder(&client, Path::new("/srv/photos"), "/photos", Some(true))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("user exists"), "{error}");
+        create_user.assert_async().await;
+        delete.assert_async().await;
+
+        // Without an account nothing but the storage is created.
+        let (id, account) = publish_folder(&client, Path::new("/srv/photos"), "/photos", None)
+            .await
+            .unwrap();
+        assert_eq!(id, 9);
+        assert!(account.is_none());
+        assert_eq!(share_permission(true) & (1 << 3), 0);
+        assert_ne!(share_permission(false) & (1 << 3), 0);
+    }
+}
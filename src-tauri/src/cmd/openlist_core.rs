@@ -1,30 +1,229 @@
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
 use tauri::State;
 use tokio::time::{Duration, sleep};
+use url::Url;
 
-use crate::conf::config::MergedSettings;
+use crate::cmd::binary::read_binary_version;
+use crate::cmd::config::update_data_config;
+use crate::conf::config::{DEFAULT_CORE_PROFILE, MergedSettings};
+use crate::conf::core::OpenListCoreConfig;
+use crate::core::health::{CoreOwnership, HealthSample};
 use crate::core::process_manager::{PROCESS_MANAGER, ProcessConfig, ProcessInfo};
 use crate::object::structs::{AppState, ServiceStatus};
+use crate::utils::args::format_command_line;
 use crate::utils::path::{
-    get_app_logs_dir, get_default_openlist_data_dir, get_openlist_binary_path_with_custom,
+    get_app_logs_dir, get_default_openlist_data_dir, resolve_openlist_binary_path,
 };
+use crate::utils::port::{PortOwner, find_available_port, find_port_owner, is_port_available};
 
 pub const OPENLIST_CORE_PROCESS_ID: &str = "openlist_core";
 
-fn build_openlist_config(state: State<'_, AppState>) -> Result<ProcessConfig, String> {
+#[derive(Debug, Serialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OpenListCoreError {
+    #[error("{message}")]
+    PortInUse {
+        message: String,
+        scheme: String,
+        port: u16,
+        owner: Option<PortOwner>,
+    },
+    #[error("{message}")]
+    Other { message: String },
+}
+
+impl From<String> for OpenListCoreError {
+    fn from(message: String) -> Self {
+        Self::Other { message }
+    }
+}
+
+impl From<&str> for OpenListCoreError {
+    fn from(message: &str) -> Self {
+        Self::Other {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<OpenListCoreError> for String {
+    fn from(error: OpenListCoreError) -> Self {
+        error.to_string()
+    }
+}
+
+fn port_in_use_error(scheme: &str, port: u16) -> OpenListCoreError {
+    let owner = find_port_owner(port);
+    let holder = match &owner {
+        Some(PortOwner {
+            pid,
+            name: Some(name),
+        }) => format!(" by {name} (pid {pid})"),
+        Some(PortOwner { pid, name: None }) => format!(" by pid {pid}"),
+        None => String::new(),
+    };
+    OpenListCoreError::PortInUse {
+        message: format!(
+            "{} port {port} is already in use{holder}",
+            scheme.to_uppercase()
+        ),
+        scheme: scheme.to_string(),
+        port,
+        owner,
+    }
+}
+
+pub(crate) fn core_listen_port(config: &OpenListCoreConfig) -> Option<u16> {
+    if config.ssl_enabled {
+        let data_dir = Some(config.data_dir.as_str()).filter(|d| !d.is_empty());
+        MergedSettings::get_port_from_data_config_for_dir(data_dir, true)
+            .ok()
+            .flatten()
+    } else {
+        Some(config.port)
+    }
+}
+
+pub fn core_process_id(profile: &str) -> String {
+    if profile == DEFAULT_CORE_PROFILE {
+        OPENLIST_CORE_PROCESS_ID.to_string()
+    } else {
+        format!("{OPENLIST_CORE_PROCESS_ID}_{profile}")
+    }
+}
+
+/// How long a stopped core may take to release its ports.
+const PORT_RELEASE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Waits until the ports of a core that was just stopped are free again, so a
+/// slow shutdown isn't mistaken for another program holding them. Gives up
+/// quietly at the deadline and leaves the error to the port check.
+async fn wait_for_core_ports(config: &OpenListCoreConfig) {
+    let Ok(bind_address) = config.bind_address() else {
+        return;
+    };
+    let ports: Vec<u16> = [Some(config.port), core_listen_port(config)]
+        .into_iter()
+        .flatten()
+        .collect();
+    let deadline = Instant::now() + PORT_RELEASE_TIMEOUT;
+    while !ports
+        .iter()
+        .all(|port| is_port_available(bind_address, *port))
+    {
+        if Instant::now() >= deadline {
+            log::warn!("OpenList Core ports {ports:?} are still in use after stopping it");
+            return;
+        }
+        sleep(Duration::from_millis(200)).await;
+    }
+}
+
+fn ensure_core_ports_available(
+    state: State<'_, AppState>,
+    profile: &str,
+) -> Result<(), OpenListCoreError> {
+    let mut settings = state.get_settings().ok_or("Failed to read app settings")?;
+    // Ports configured for the other local profiles are never handed out.
+    let mut reserved: Vec<u16> = settings
+        .core_profile_names()
+        .iter()
+        .filter(|name| name.as_str() != profile)
+        .filter_map(|name| settings.core_profile(name).ok())
+        .filter(|config| !config.is_remote())
+        .map(|config| config.port)
+        .collect();
+
+    let config = settings.core_profile_mut(profile)?;
+    let data_dir = Some(config.data_dir.clone()).filter(|d| !d.is_empty());
+    let bind_address = config.bind_address()?;
+    let http_port = config.port;
+    let https_port = if config.ssl_enabled {
+        core_listen_port(config)
+    } else {
+        None
+    };
+
+    let current_address = MergedSettings::get_address_from_data_config_for_dir(data_dir.as_deref())
+        .ok()
+        .flatten();
+    if current_address.as_deref() != Some(bind_address.to_string().as_str()) {
+        update_data_config(
+            "address",
+            serde_json::json!(bind_address.to_string()),
+            data_dir.as_deref(),
+        )?;
+    }
+    if config.is_exposed() && !config.ssl_enabled {
+        log::warn!(
+            "OpenList Core '{profile}' listens on {bind_address} without HTTPS, other devices on \
+             the network can read its traffic"
+        );
+    }
+
+    reserved.extend([Some(http_port), https_port].into_iter().flatten());
+    let mut settings_changed = false;
+    for (scheme, port_key, port) in [
+        ("http", "http_port", Some(http_port)),
+        ("https", "https_port", https_port),
+    ] {
+        let Some(port) = port else {
+            continue;
+        };
+        if is_port_available(bind_address, port) {
+            continue;
+        }
+        if !config.auto_select_port {
+            return Err(port_in_use_error(scheme, port));
+        }
+        let Some(free_port) = find_available_port(bind_address, port.saturating_add(1), &reserved)
+        else {
+            return Err(port_in_use_error(scheme, port));
+        };
+
+        log::warn!(
+            "{} port {port} is already in use, switching OpenList Core '{profile}' to port \
+             {free_port}",
+            scheme.to_uppercase()
+        );
+        update_data_config(port_key, serde_json::json!(free_port), data_dir.as_deref())?;
+        reserved.push(free_port);
+        if scheme == "http" {
+            config.port = free_port;
+            settings_changed = true;
+        }
+    }
+
+    if settings_changed {
+        state.update_settings(settings.clone());
+        settings.save()?;
+    }
+    Ok(())
+}
+
+fn build_openlist_config(
+    state: State<'_, AppState>,
+    profile: &str,
+) -> Result<ProcessConfig, String> {
     let settings = state
         .app_settings
         .read()
         .clone()
         .ok_or("Failed to read app settings")?;
-    let data_dir = settings.openlist.data_dir;
-    let binary_path = get_openlist_binary_path_with_custom(state)
+    let config = settings.core_profile(profile)?;
+    let extra_args = config.launch_args()?;
+    let binary_path = resolve_openlist_binary_path(config.binary_path.as_deref())
         .map_err(|e| format!("Failed to get OpenList binary path: {e}"))?;
+    let process_id = core_process_id(profile);
     let log_file_path =
         get_app_logs_dir().map_err(|e| format!("Failed to get app logs directory: {e}"))?;
-    let log_file_path = log_file_path.join("process_openlist_core.log");
+    let log_file_path = log_file_path.join(format!("process_{process_id}.log"));
 
-    let effective_data_dir = if !data_dir.is_empty() {
-        data_dir
+    let effective_data_dir = if !config.data_dir.is_empty() {
+        config.data_dir.clone()
     } else {
         get_default_openlist_data_dir()
             .map_err(|e| format!("Failed to get default data directory: {e}"))?
@@ -32,114 +231,243 @@ fn build_openlist_config(state: State<'_, AppState>) -> Result<ProcessConfig, St
             .to_string()
     };
 
+    let mut args = vec!["server".into(), "--data".into(), effective_data_dir];
+    args.extend(extra_args);
+
     Ok(ProcessConfig {
-        id: OPENLIST_CORE_PROCESS_ID.into(),
-        name: "openlist_core_process".into(),
+        name: format!("{process_id}_process"),
+        id: process_id,
         bin_path: binary_path.to_string_lossy().into_owned(),
-        args: vec!["server".into(), "--data".into(), effective_data_dir],
+        args,
         log_file: log_file_path.to_string_lossy().into_owned(),
         working_dir: binary_path
             .parent()
             .map(|p| p.to_string_lossy().into_owned()),
-        env_vars: None,
+        env_vars: Some(config.env.clone()).filter(|env| !env.is_empty()),
+        secret_env_vars: Default::default(),
     })
 }
 
+fn resolve_profile(
+    state: &State<'_, AppState>,
+    profile: Option<String>,
+) -> Result<(String, OpenListCoreConfig), String> {
+    let settings = state.get_settings().ok_or("Failed to read app settings")?;
+    let name = profile.unwrap_or_else(|| DEFAULT_CORE_PROFILE.to_string());
+    let config = settings.core_profile(&name)?.clone();
+    Ok((name, config))
+}
+
+fn ensure_local_mode(name: &str, config: &OpenListCoreConfig) -> Result<(), String> {
+    if config.is_remote() {
+        return Err(format!(
+            "OpenList profile '{name}' is configured as a remote instance, the local core cannot \
+             be started or stopped"
+        ));
+    }
+    Ok(())
+}
+
 #[tauri::command]
-pub async fn start_openlist_core(state: State<'_, AppState>) -> Result<ProcessInfo, String> {
-    let config = build_openlist_config(state)?;
+pub async fn start_openlist_core(
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ProcessInfo, OpenListCoreError> {
+    let (profile, config) = resolve_profile(&state, profile)?;
+    ensure_local_mode(&profile, &config)?;
+    let process_config = build_openlist_config(state.clone(), &profile)?;
+    let process_id = process_config.id.clone();
 
-    if PROCESS_MANAGER.is_registered(OPENLIST_CORE_PROCESS_ID) {
-        let _ = PROCESS_MANAGER.stop(OPENLIST_CORE_PROCESS_ID);
-        sleep(Duration::from_millis(500)).await;
-        let _ = PROCESS_MANAGER.remove(OPENLIST_CORE_PROCESS_ID);
-        sleep(Duration::from_millis(500)).await;
+    if PROCESS_MANAGER.is_registered(&process_id) {
+        let _ = PROCESS_MANAGER.stop(&process_id);
+        let _ = PROCESS_MANAGER.remove(&process_id);
+        wait_for_core_ports(&config).await;
     }
 
-    PROCESS_MANAGER.register_and_start(config)
+    ensure_core_ports_available(state.clone(), &profile)?;
+    let info = PROCESS_MANAGER.register_and_start(process_config)?;
+    state.core_health.write().remove(&profile);
+    Ok(info)
 }
 
 #[tauri::command]
-pub async fn stop_openlist_core(_state: State<'_, AppState>) -> Result<ProcessInfo, String> {
-    if !PROCESS_MANAGER.is_registered(OPENLIST_CORE_PROCESS_ID) {
-        return Err("OpenList Core process not registered.".into());
+pub async fn stop_openlist_core(
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ProcessInfo, String> {
+    let (profile, config) = resolve_profile(&state, profile)?;
+    ensure_local_mode(&profile, &config)?;
+    let process_id = core_process_id(&profile);
+    if !PROCESS_MANAGER.is_registered(&process_id) {
+        return Err(format!("OpenList Core process '{profile}' not registered."));
     }
-    let raw_info = PROCESS_MANAGER.stop(OPENLIST_CORE_PROCESS_ID);
-    PROCESS_MANAGER.remove(OPENLIST_CORE_PROCESS_ID)?;
+    let raw_info = PROCESS_MANAGER.stop(&process_id);
+    PROCESS_MANAGER.remove(&process_id)?;
     raw_info
 }
 
-pub async fn get_openlist_core_process_status() -> Result<ProcessInfo, String> {
-    if !PROCESS_MANAGER.is_registered(OPENLIST_CORE_PROCESS_ID) {
-        return Err("OpenList Core process not registered.".into());
+pub async fn get_openlist_core_process_status(profile: &str) -> Result<ProcessInfo, String> {
+    let process_id = core_process_id(profile);
+    if !PROCESS_MANAGER.is_registered(&process_id) {
+        return Err(format!("OpenList Core process '{profile}' not registered."));
     }
-    PROCESS_MANAGER.get_status(OPENLIST_CORE_PROCESS_ID)
+    PROCESS_MANAGER.get_status(&process_id)
 }
 
-#[tauri::command]
-pub async fn get_openlist_core_status(state: State<'_, AppState>) -> Result<ServiceStatus, String> {
-    let app_settings = state
-        .app_settings
+pub(crate) fn core_base_url(config: &OpenListCoreConfig) -> Result<String, String> {
+    if config.is_remote() {
+        return config.remote_url();
+    }
+    let protocol = if config.ssl_enabled { "https" } else { "http" };
+    let port = core_listen_port(config).ok_or("OpenList Core HTTPS port is not configured")?;
+    Ok(format!("{protocol}://{}:{port}", config.local_host()))
+}
+
+async fn core_version(
+    state: State<'_, AppState>,
+    profile: &str,
+    client: &reqwest::Client,
+    base_url: &str,
+    process: Option<&ProcessInfo>,
+) -> Option<String> {
+    if let Some(version) = state
+        .core_health
         .read()
-        .clone()
-        .ok_or("Failed to read app settings")?;
-    let openlist_config = app_settings.openlist;
-    let protocol = if openlist_config.ssl_enabled {
-        "https"
-    } else {
-        "http"
-    };
-    let data_dir = if openlist_config.data_dir.is_empty() {
-        None
-    } else {
-        Some(openlist_config.data_dir.as_str())
+        .get(profile)
+        .and_then(|history| history.version())
+    {
+        return Some(version);
+    }
+    let version = match process {
+        Some(info) => read_binary_version(&PathBuf::from(&info.config.bin_path)),
+        None => fetch_public_version(client, base_url).await,
     };
-    let port = if openlist_config.ssl_enabled {
-        MergedSettings::get_port_from_data_config_for_dir(data_dir, true)
+    let version = version
+        .map_err(|e| log::debug!("Failed to read OpenList Core version: {e}"))
+        .ok()?;
+    state
+        .core_health
+        .write()
+        .entry(profile.to_string())
+        .or_default()
+        .set_version(version.clone());
+    Some(version)
+}
+
+async fn fetch_public_version(client: &reqwest::Client, base_url: &str) -> Result<String, String> {
+    let body: serde_json::Value = client
+        .get(format!("{base_url}/api/public/settings"))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    body.pointer("/data/version")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| "Version not found in public settings".to_string())
+}
+
+#[tauri::command]
+pub async fn get_openlist_core_status(
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ServiceStatus, String> {
+    let (profile, openlist_config) = resolve_profile(&state, profile)?;
+    let process_id = core_process_id(&profile);
+    let remote = openlist_config.is_remote();
+    let base_url = core_base_url(&openlist_config);
+    let port = if remote {
+        base_url
+            .as_deref()
             .ok()
-            .flatten()
+            .and_then(|url| Url::parse(url).ok())
+            .and_then(|url| url.port_or_known_default())
     } else {
-        Some(openlist_config.port)
+        core_listen_port(&openlist_config)
     };
 
-    let Some(port) = port else {
-        return Ok(ServiceStatus {
-            running: false,
-            pid: PROCESS_MANAGER
-                .get_status(OPENLIST_CORE_PROCESS_ID)
-                .ok()
-                .and_then(|info| info.pid),
-            port: None,
-        });
+    let process = if remote {
+        None
+    } else {
+        PROCESS_MANAGER
+            .get_status(&process_id)
+            .ok()
+            .filter(|info| info.is_running)
     };
-    let health_check_url = format!("{protocol}://localhost:{port}");
-    let health_url = format!("{health_check_url}/ping");
+    let local_pid = process.as_ref().and_then(|info| info.pid);
+    let started_at = process.as_ref().and_then(|info| info.started_at);
+    let uptime_secs = started_at.map(|started| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs().saturating_sub(started))
+            .unwrap_or(0)
+    });
 
     // OpenList commonly uses self-signed certificates for local HTTPS endpoints.
     let client = reqwest::Client::builder()
-        .tls_danger_accept_invalid_certs(openlist_config.ssl_enabled)
+        .tls_danger_accept_invalid_certs(!remote && openlist_config.ssl_enabled)
+        .timeout(Duration::from_secs(5))
         .build()
         .map_err(|e| format!("Failed to create health check client: {e}"))?;
 
-    // Get PID from process manager if available
-    let local_pid = PROCESS_MANAGER
-        .get_status(OPENLIST_CORE_PROCESS_ID)
-        .ok()
-        .and_then(|info| info.pid);
-
-    match client.get(&health_url).send().await {
-        Ok(response) => {
-            let is_running = response.status().is_success();
-            Ok(ServiceStatus {
-                running: is_running,
-                pid: local_pid,
-                port: Some(port),
-            })
+    let sample = match &base_url {
+        Ok(base_url) => {
+            let started = Instant::now();
+            match client.get(format!("{base_url}/ping")).send().await {
+                Ok(response) => HealthSample {
+                    healthy: response.status().is_success(),
+                    latency_ms: Some(started.elapsed().as_millis() as u64),
+                    http_status: Some(response.status().as_u16()),
+                },
+                Err(_) => HealthSample {
+                    healthy: false,
+                    latency_ms: None,
+                    http_status: None,
+                },
+            }
         }
-        Err(_) => Ok(ServiceStatus {
-            running: false,
-            pid: local_pid,
-            port: Some(port),
-        }),
-    }
+        Err(_) => HealthSample {
+            healthy: false,
+            latency_ms: None,
+            http_status: None,
+        },
+    };
+    let availability = {
+        let mut health = state.core_health.write();
+        let history = health.entry(profile.clone()).or_default();
+        history.record(sample);
+        history.availability()
+    };
+
+    let ownership = match &process {
+        _ if remote => Some(CoreOwnership::Remote),
+        Some(_) if PROCESS_MANAGER.is_adopted(&process_id) => Some(CoreOwnership::Adopted),
+        Some(_) => Some(CoreOwnership::Managed),
+        None if sample.healthy => Some(CoreOwnership::External),
+        None => None,
+    };
+    let version = match &base_url {
+        Ok(base_url) if sample.healthy => {
+            core_version(state.clone(), &profile, &client, base_url, process.as_ref()).await
+        }
+        _ => None,
+    };
+
+    Ok(ServiceStatus {
+        running: sample.healthy,
+        pid: local_pid,
+        port,
+        version,
+        started_at,
+        uptime_secs,
+        latency_ms: sample.latency_ms,
+        http_status: sample.http_status,
+        ownership,
+        availability,
+        command_line: process
+            .as_ref()
+            .map(|info| format_command_line(&info.config.bin_path, &info.config.args)),
+    })
 }
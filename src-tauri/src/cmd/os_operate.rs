@@ -6,7 +6,7 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, State};
 
-use crate::cmd::openlist_core::OPENLIST_CORE_PROCESS_ID;
+use crate::cmd::openlist_core::core_process_id;
 use crate::cmd::rclone_mount::stop_all_rclone_mounts;
 use crate::core::process_manager::PROCESS_MANAGER;
 use crate::object::structs::AppState;
@@ -248,14 +248,19 @@ pub async fn update_tool_version(
 ) -> Result<String, String> {
     log::info!("Updating {tool} to version {version}");
     if tool.as_str() == "openlist" {
-        let process_id = OPENLIST_CORE_PROCESS_ID;
-        let was_running = PROCESS_MANAGER.is_running(process_id);
-        if was_running {
-            log::info!("Stopping {tool} process");
-            PROCESS_MANAGER
-                .stop(process_id)
-                .map_err(|e| format!("Failed to stop process: {e}"))?;
-            log::info!("Successfully stopped {tool} process");
+        let profiles = state
+            .get_settings()
+            .map(|settings| settings.core_profile_names())
+            .unwrap_or_default();
+        for profile in profiles {
+            let process_id = core_process_id(&profile);
+            if PROCESS_MANAGER.is_running(&process_id) {
+                log::info!("Stopping {tool} process for profile '{profile}'");
+                PROCESS_MANAGER
+                    .stop(&process_id)
+                    .map_err(|e| format!("Failed to stop process: {e}"))?;
+                log::info!("Successfully stopped {tool} process for profile '{profile}'");
+            }
         }
     } else {
         stop_all_rclone_mounts().await?;
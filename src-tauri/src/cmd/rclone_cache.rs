@@ -0,0 +1,330 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::{Value, json};
+use tauri::State;
+
+use crate::cmd::rclone_mount::{is_mounted, mount_endpoint};
+use crate::object::structs::AppState;
+use crate::utils::args::split_args_vec;
+
+#[derive(Debug, Serialize)]
+pub struct RcloneCacheInfo {
+    pub name: String,
+    pub cache_dir: String,
+    pub data_dir: String,
+    pub meta_dir: String,
+    /// Bytes the cached files take on disk.
+    pub size: u64,
+    pub files: u64,
+    /// Cached files that haven't been uploaded yet.
+    pub dirty_files: u64,
+    pub max_size: Option<String>,
+    pub max_age: Option<String>,
+    pub mounted: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct DirUsage {
+    size: u64,
+    files: u64,
+}
+
+/// Where rclone keeps its cache without `--cache-dir`. Mounts inherit the
+/// app's environment, so `RCLONE_CACHE_DIR` applies to them too.
+fn default_cache_dir() -> Result<PathBuf, String> {
+    if let Some(dir) = std::env::var_os("RCLONE_CACHE_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    dirs::cache_dir()
+        .map(|dir| dir.join("rclone"))
+        .ok_or_else(|| "Failed to find the user cache directory".to_string())
+}
+
+/// Value of `flag` in extra flags, in either `--flag=value` or `--flag value`
+/// form.
+fn flag_value(flags: &[String], flag: &str) -> Option<String> {
+    let args = split_args_vec(flags.to_vec());
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == flag {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix(flag)?
+                .strip_prefix('=')
+                .map(str::to_string)
+        }
+    })
+}
+
+/// VFS cache data and metadata directories of every path of a remote.
+fn remote_cache_dirs(cache_dir: &Path, name: &str) -> Result<(PathBuf, PathBuf), String> {
+    if matches!(name, "" | "." | "..") || name.contains(['/', '\\']) {
+        return Err(format!("Invalid remote name '{name}'"));
+    }
+    Ok((
+        cache_dir.join("vfs").join(name),
+        cache_dir.join("vfsMeta").join(name),
+    ))
+}
+
+fn walk_files(dir: &Path, visit: &mut impl FnMut(&Path, &fs::Metadata)) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            walk_files(&path, visit);
+        } else {
+            visit(&path, &metadata);
+        }
+    }
+}
+
+/// Cached files are sparse with `--vfs-cache-mode full`, so count the blocks
+/// they use rather than their length where possible.
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.blocks() * 512
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.len()
+    }
+}
+
+fn disk_usage(dir: &Path) -> DirUsage {
+    let mut usage = DirUsage::default();
+    walk_files(dir, &mut |_, metadata| {
+        usage.size += allocated_size(metadata);
+        usage.files += 1;
+    });
+    usage
+}
+
+/// Entries whose cache metadata marks them as written but not uploaded.
+fn dirty_files(meta_dir: &Path) -> u64 {
+    let mut dirty = 0;
+    walk_files(meta_dir, &mut |path, _| {
+        let is_dirty = fs::read(path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<Value>(&data).ok())
+            .and_then(|info| info.get("Dirty").and_then(Value::as_bool))
+            .unwrap_or(false);
+        if is_dirty {
+            dirty += 1;
+        }
+    });
+    dirty
+}
+
+/// Cache directory and limits of a mount, from its settings or extra flags.
+/// A mount with its own `--cache-dir` always runs in its own process, since
+/// the daemon can't apply one per mount, so this is the directory it uses.
+fn cache_settings(
+    name: &str,
+    state: &State<'_, AppState>,
+) -> Result<(PathBuf, Option<String>, Option<String>), String> {
+    let config = state
+        .get_settings()
+        .ok_or("Failed to read app settings")?
+        .rclone
+        .mount_config
+        .and_then(|mut configs| configs.remove(name));
+    let flags = config
+        .as_ref()
+        .and_then(|config| config.extra_flags.clone())
+        .unwrap_or_default();
+    let setting = |value: Option<&String>, flag: &str| {
+        value
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| value.trim().to_string())
+            .or_else(|| flag_value(&flags, flag))
+    };
+
+    let cache_dir = match setting(
+        config.as_ref().and_then(|c| c.cache_dir.as_ref()),
+        "--cache-dir",
+    ) {
+        Some(dir) => PathBuf::from(dir),
+        None => default_cache_dir()?,
+    };
+    let max_size = setting(
+        config.as_ref().and_then(|c| c.cache_max_size.as_ref()),
+        "--vfs-cache-max-size",
+    );
+    let max_age = setting(
+        config.as_ref().and_then(|c| c.cache_max_age.as_ref()),
+        "--vfs-cache-max-age",
+    );
+    Ok((cache_dir, max_size, max_age))
+}
+
+/// Where the VFS cache of a mount lives and how much disk it uses.
+#[tauri::command]
+pub async fn get_mount_cache_info(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<RcloneCacheInfo, String> {
+    let (cache_dir, max_size, max_age) = cache_settings(&name, &state)?;
+    let (mut data_dir, mut meta_dir) = remote_cache_dirs(&cache_dir, &name)?;
+
+    let mounted = is_mounted(&name).await;
+    // A running mount knows exactly which directories it uses.
+    if let Some((rc, fs)) = mount_endpoint(&name).await {
+        let params = fs.map_or_else(|| json!({}), |fs| json!({ "fs": fs }));
+        if let Ok(stats) = rc.call("vfs/stats", params).await {
+            let disk_cache = &stats["diskCache"];
+            if let (Some(path), Some(path_meta)) =
+                (disk_cache["path"].as_str(), disk_cache["pathMeta"].as_str())
+            {
+                data_dir = PathBuf::from(path);
+                meta_dir = PathBuf::from(path_meta);
+            }
+        }
+    }
+
+    let (usage, dirty) = tokio::task::spawn_blocking({
+        let data_dir = data_dir.clone();
+        let meta_dir = meta_dir.clone();
+        move || (disk_usage(&data_dir), dirty_files(&meta_dir))
+    })
+    .await
+    .map_err(|e| format!("Failed to measure the cache of '{name}': {e}"))?;
+
+    Ok(RcloneCacheInfo {
+        name,
+        cache_dir: cache_dir.to_string_lossy().into_owned(),
+        data_dir: data_dir.to_string_lossy().into_owned(),
+        meta_dir: meta_dir.to_string_lossy().into_owned(),
+        size: usage.size,
+        files: usage.files,
+        dirty_files: dirty,
+        max_size,
+        max_age,
+        mounted,
+    })
+}
+
+/// Deletes the VFS cache of an unmounted remote and returns the bytes freed.
+/// Refuses while cached writes are waiting to be uploaded, since deleting
+/// them would lose the data.
+#[tauri::command]
+pub async fn purge_mount_cache(name: String, state: State<'_, AppState>) -> Result<u64, String> {
+    if is_mounted(&name).await {
+        return Err(format!("Unmount '{name}' before purging its cache"));
+    }
+    let (cache_dir, _, _) = cache_settings(&name, &state)?;
+    let (data_dir, meta_dir) = remote_cache_dirs(&cache_dir, &name)?;
+
+    tokio::task::spawn_blocking(move || {
+        let dirty = dirty_files(&meta_dir);
+        if dirty > 0 {
+            return Err(format!(
+                "{dirty} cached files of '{name}' are not uploaded yet; mount it and let the uploads finish first"
+            ));
+        }
+        let usage = disk_usage(&data_dir);
+        for dir in [&data_dir, &meta_dir] {
+            if dir.exists() {
+                fs::remove_dir_all(dir)
+                    .map_err(|e| format!("Failed to delete {}: {e}", dir.display()))?;
+            }
+        }
+        log::info!("Purged {} bytes of VFS cache of '{name}'", usage.size);
+        Ok(usage.size)
+    })
+    .await
+    .map_err(|e| format!("Failed to purge the cache: {e}"))?
+}
+
+/// Reads a directory tree of a mounted remote into the VFS directory cache,
+/// so browsing it doesn't wait for the remote. Recursive refreshes run in
+/// the background.
+#[tauri::command]
+pub async fn refresh_mount_dir(
+    name: String,
+    dir: Option<String>,
+    recursive: bool,
+) -> Result<(), String> {
+    let (rc, fs) = mount_endpoint(&name)
+        .await
+        .ok_or_else(|| format!("Remote '{name}' is not mounted"))?;
+
+    let mut params = json!({ "recursive": recursive.to_string() });
+    if let Some(dir) = dir.map(|dir| dir.trim_matches('/').to_string()) {
+        params["dir"] = json!(dir);
+    }
+    if let Some(fs) = fs {
+        params["fs"] = json!(fs);
+    }
+    if recursive {
+        params["_async"] = json!(true);
+    }
+
+    let response = rc.call("vfs/refresh", params).await?;
+    let failed: Vec<String> = response
+        .get("result")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter(|(_, status)| status.as_str() != Some("OK"))
+        .map(|(dir, status)| format!("{dir}: {}", status.as_str().unwrap_or_default()))
+        .collect();
+    if !failed.is_empty() {
+        return Err(format!("Failed to refresh {}", failed.join(", ")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{DirUsage, dirty_files, disk_usage, flag_value, remote_cache_dirs};
+
+    #[test]
+    fn reads_cache_flags_in_either_form() {
+        let flags = vec![
+            "--vfs-cache-mode full --cache-dir '/tmp/my cache'".to_string(),
+            "--vfs-cache-max-size=5G".to_string(),
+        ];
+        assert_eq!(
+            flag_value(&flags, "--cache-dir").as_deref(),
+            Some("/tmp/my cache")
+        );
+        assert_eq!(
+            flag_value(&flags, "--vfs-cache-max-size").as_deref(),
+            Some("5G")
+        );
+        assert_eq!(flag_value(&flags, "--vfs-cache-max-age"), None);
+    }
+
+    #[test]
+    fn measures_cache_and_finds_pending_uploads() {
+        let cache = std::env::temp_dir().join(format!("rclone-cache-{}", std::process::id()));
+        let (data, meta) = remote_cache_dirs(&cache, "nas").unwrap();
+        fs::create_dir_all(data.join("media")).unwrap();
+        fs::create_dir_all(meta.join("media")).unwrap();
+        fs::write(data.join("media/a.bin"), vec![1u8; 10_000]).unwrap();
+        fs::write(data.join("b.txt"), "b").unwrap();
+        fs::write(meta.join("media/a.bin"), r#"{"Size":10000,"Dirty":false}"#).unwrap();
+        fs::write(meta.join("b.txt"), r#"{"Size":1,"Dirty":true}"#).unwrap();
+
+        let usage = disk_usage(&data);
+        assert_eq!(usage.files, 2);
+        assert!(usage.size > 0);
+        assert_eq!(dirty_files(&meta), 1);
+        assert_eq!(disk_usage(&cache.join("missing")), DirUsage::default());
+        assert!(remote_cache_dirs(&cache, "..").is_err());
+        assert!(remote_cache_dirs(&cache, "a/b").is_err());
+
+        fs::remove_dir_all(cache).unwrap();
+    }
+}
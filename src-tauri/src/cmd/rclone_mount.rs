@@ -1,28 +1,37 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use tauri::State;
 use tokio::task::JoinSet;
 use tokio::time::{Duration, sleep, timeout};
 
-use crate::conf::rclone_config::{RcloneConfigFile, WebDavRemoteConfig, reveal_password};
+use crate::conf::rclone::RcloneMountConfig;
+use crate::conf::rclone_config::{
+    RcloneConfigFile, SECRET_MASK, build_remote_config, config_password, reveal_password,
+    set_config_password, validate_remote_name,
+};
+use crate::conf::rclone_crypt;
+use crate::conf::rclone_providers::{
+    RcloneBackend, fallback_backend, find_backend, is_password_option, is_secret_option,
+    rclone_backends,
+};
+use crate::core::mount_watchdog;
 use crate::core::process_manager::{PROCESS_MANAGER, ProcessConfig, ProcessInfo};
+use crate::core::rclone_daemon::{
+    RCD_PROCESS_ID, daemon_process, ensure_daemon, mount_params, running_daemon, stop_daemon,
+};
+use crate::core::rclone_rc::{RcloneRc, endpoint, forget_endpoint, register_endpoint};
 use crate::object::structs::{AppState, RcloneMountInfo};
 use crate::utils::args::{remove_network_mode_flags, split_args_vec};
+#[cfg(target_os = "linux")]
+use crate::utils::fuse::{self, FuseMount};
 use crate::utils::path::{
     get_app_logs_dir, get_rclone_binary_path_with_custom, get_rclone_config_path_with_custom,
 };
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RcloneWebdavConfigInput {
-    pub url: String,
-    pub vendor: Option<String>,
-    pub user: String,
-    pub pass: String,
-}
-
 #[derive(Debug, Clone, Deserialize)]
 pub struct MountProcessInput {
     pub id: String,
@@ -36,6 +45,28 @@ pub fn get_mount_process_id(remote_name: &str) -> String {
     format!("rclone_mount_{remote_name}_process")
 }
 
+/// Mount request for a saved mount, as the mount view would send it.
+pub fn mount_input(config: &RcloneMountConfig) -> MountProcessInput {
+    let mut args = vec![
+        format!(
+            "{}:{}",
+            config.name,
+            config.volume_name.as_deref().unwrap_or("")
+        ),
+        config.mount_point.as_deref().unwrap_or("").to_string(),
+    ];
+    if let Some(extra_flags) = &config.extra_flags {
+        args.extend(extra_flags.clone());
+    }
+    let id = get_mount_process_id(&config.name);
+    MountProcessInput {
+        id: id.clone(),
+        name: id,
+        args,
+        network_mode: config.network_mode.unwrap_or(false),
+    }
+}
+
 fn split_mount_args(args: Vec<String>) -> Vec<String> {
     let mut args = args.into_iter();
     let mut result: Vec<String> = args.by_ref().take(2).collect();
@@ -48,16 +79,31 @@ fn insert_mount_flag(args: &mut Vec<String>, flag: String) {
     args.insert(args.len().min(2), flag);
 }
 
+fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().take_while(|arg| *arg != "--").any(|arg| {
+        arg == flag
+            || arg
+                .strip_prefix(flag)
+                .is_some_and(|rest| rest.starts_with('='))
+    })
+}
+
 fn ensure_vfs_write_cache(args: &mut Vec<String>) {
-    let has_cache_mode = args
-        .iter()
-        .take_while(|arg| *arg != "--")
-        .any(|arg| arg == "--vfs-cache-mode" || arg.starts_with("--vfs-cache-mode="));
-    if !has_cache_mode {
+    if !has_flag(args, "--vfs-cache-mode") {
         insert_mount_flag(args, "--vfs-cache-mode=writes".into());
     }
 }
 
+/// Adds the cache settings of the mount unless its extra flags set them.
+fn insert_cache_flags(args: &mut Vec<String>, config: &RcloneMountConfig) {
+    for flag in config.cache_flags() {
+        let name = flag.split('=').next().unwrap_or_default();
+        if !has_flag(args, name) {
+            insert_mount_flag(args, flag);
+        }
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn insert_network_mode(args: &mut Vec<String>, network_mode: bool) {
     insert_mount_flag(args, format!("--network-mode={network_mode}"));
@@ -66,109 +112,6 @@ fn insert_network_mode(args: &mut Vec<String>, network_mode: bool) {
 #[cfg(not(target_os = "windows"))]
 fn insert_network_mode(_args: &mut Vec<String>, _network_mode: bool) {}
 
-#[cfg(test)]
-mod tests {
-    #[cfg(target_os = "windows")]
-    use super::insert_network_mode;
-    use super::{ensure_vfs_write_cache, split_mount_args};
-
-    #[test]
-    fn preserves_mount_positionals_while_splitting_extra_flags() {
-        let args = vec![
-            "--network-mode".into(),
-            r"C:\Mount Dir".into(),
-            "--log-file 'C:\\Log Dir\\rclone.log' --network-mode=false".into(),
-        ];
-
-        assert_eq!(
-            split_mount_args(args),
-            vec![
-                "--network-mode",
-                r"C:\Mount Dir",
-                "--log-file",
-                r"C:\Log Dir\rclone.log"
-            ]
-        );
-    }
-
-    #[test]
-    fn adds_default_vfs_write_cache() {
-        let mut args = vec![
-            "remote:".into(),
-            "mount-point".into(),
-            "--log-file".into(),
-            "--".into(),
-        ];
-
-        ensure_vfs_write_cache(&mut args);
-
-        assert_eq!(
-            args,
-            vec![
-                "remote:",
-                "mount-point",
-                "--vfs-cache-mode=writes",
-                "--log-file",
-                "--"
-            ]
-        );
-    }
-
-    #[test]
-    fn ignores_vfs_cache_mode_after_option_terminator() {
-        let mut args = vec![
-            "remote:".into(),
-            "mount-point".into(),
-            "--".into(),
-            "--vfs-cache-mode=full".into(),
-        ];
-
-        ensure_vfs_write_cache(&mut args);
-
-        assert_eq!(args[2], "--vfs-cache-mode=writes");
-    }
-
-    #[test]
-    fn preserves_explicit_vfs_cache_mode() {
-        for cache_mode in [
-            vec!["--vfs-cache-mode=full".into()],
-            vec!["--vfs-cache-mode".into(), "off".into()],
-        ] {
-            let mut args = vec!["remote:".into(), "mount-point".into()];
-            args.extend(cache_mode);
-            let expected = args.clone();
-
-            ensure_vfs_write_cache(&mut args);
-
-            assert_eq!(args, expected);
-        }
-    }
-
-    #[cfg(target_os = "windows")]
-    #[test]
-    fn inserts_network_mode_after_mount_positionals() {
-        let mut args = vec![
-            "remote:".into(),
-            "mount-point".into(),
-            "--log-file".into(),
-            "--".into(),
-        ];
-
-        insert_network_mode(&mut args, true);
-
-        assert_eq!(
-            args,
-            vec![
-                "remote:",
-                "mount-point",
-                "--network-mode=true",
-                "--log-file",
-                "--"
-            ]
-        );
-    }
-}
-
 #[cfg(target_os = "macos")]
 fn get_libfuse_path() -> Option<String> {
     [
@@ -186,67 +129,144 @@ fn get_libfuse_path() -> Option<String> {
     None
 }
 
+/// Remotes and their options, with secrets replaced by [`SECRET_MASK`] and a
+/// `secrets` map telling which of them are set.
 #[tauri::command]
 pub async fn rclone_list_config(
     remote_type: String,
     state: State<'_, AppState>,
 ) -> Result<Value, String> {
     let config = RcloneConfigFile::load_with_custom(state.clone())?;
+    let backends = rclone_backends(state.clone())
+        .inspect_err(|e| log::warn!("Failed to load rclone backend schema: {e}"))
+        .ok();
 
     let filtered: HashMap<String, Value> = config
-        .remotes
-        .iter()
-        .filter(|(_, remote)| remote_type.is_empty() || remote.remote_type == remote_type)
-        .map(|(name, remote)| {
+        .remotes()
+        .into_iter()
+        .filter(|remote| remote_type.is_empty() || remote.remote_type == remote_type)
+        .map(|remote| {
             let mut obj = serde_json::Map::new();
             obj.insert("type".to_string(), json!(remote.remote_type));
+            let backend = backends
+                .as_deref()
+                .and_then(|backends| find_backend(backends, &remote.remote_type));
+            let mut secrets = serde_json::Map::new();
             for (key, value) in &remote.options {
-                if key == "pass" {
-                    let revealed_pass = reveal_password(value, state.clone())
-                        .unwrap_or_else(|_| "*****".to_string());
-                    obj.insert(key.clone(), json!(revealed_pass));
+                if is_secret_option(backend, key) {
+                    let masked = if value.is_empty() { "" } else { SECRET_MASK };
+                    obj.insert(key.clone(), json!(masked));
+                    secrets.insert(key.clone(), json!(!value.is_empty()));
                     continue;
                 }
                 obj.insert(key.clone(), json!(value));
             }
-            (name.clone(), Value::Object(obj))
+            obj.insert("secrets".to_string(), Value::Object(secrets));
+            (remote.name, Value::Object(obj))
         })
         .collect();
 
     Ok(json!(filtered))
 }
 
+/// Reveals one secret option of a remote, for when the user asks to see it.
+#[tauri::command]
+pub async fn rclone_reveal_remote_secret(
+    name: String,
+    field: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let config = RcloneConfigFile::load_with_custom(state.clone())?;
+    let remote = config
+        .remote(&name)
+        .ok_or_else(|| format!("Remote '{name}' does not exist"))?;
+    let Some(value) = remote.options.get(&field) else {
+        return Ok(String::new());
+    };
+    let backends = rclone_backends(state)
+        .inspect_err(|e| log::warn!("Failed to load rclone backend schema: {e}"))
+        .ok();
+    let backend = backends
+        .as_deref()
+        .and_then(|backends| find_backend(backends, &remote.remote_type));
+    if !is_secret_option(backend, &field) {
+        return Err(format!(
+            "Option '{field}' of remote '{name}' is not a secret"
+        ));
+    }
+    if is_password_option(backend, &field) {
+        reveal_password(value)
+    } else {
+        Ok(value.clone())
+    }
+}
+
 #[tauri::command]
 pub async fn rclone_list_remotes(state: State<'_, AppState>) -> Result<Vec<String>, String> {
     let config = RcloneConfigFile::load_with_custom(state)?;
     Ok(config.list_remotes())
 }
 
+/// Options of all backends rclone supports, for rendering remote forms.
 #[tauri::command]
-pub async fn rclone_create_remote(
-    name: String,
-    r#type: String,
-    config: RcloneWebdavConfigInput,
+pub async fn rclone_backend_schema(
     state: State<'_, AppState>,
-) -> Result<bool, String> {
-    let mut rclone_config = RcloneConfigFile::load_with_custom(state.clone())?;
+) -> Result<Vec<RcloneBackend>, String> {
+    Ok(rclone_backends(state)?
+        .iter()
+        .filter(|backend| !backend.hide)
+        .cloned()
+        .collect())
+}
 
-    if r#type != "webdav" {
-        return Err(format!("Unsupported remote type: {}", r#type));
-    }
+/// Turns the option values sent by the UI, which may also be numbers or
+/// booleans, into rclone.conf strings. `null` clears an option, like an empty
+/// string does.
+fn option_strings(config: HashMap<String, Value>) -> BTreeMap<String, String> {
+    config
+        .into_iter()
+        .map(|(key, value)| match value {
+            Value::Null => (key, String::new()),
+            Value::String(value) => (key, value),
+            other => (key, other.to_string()),
+        })
+        .collect()
+}
 
-    let webdav = WebDavRemoteConfig {
-        name: name.clone(),
-        url: config.url,
-        vendor: config.vendor,
-        user: config.user,
-        pass: config.pass,
+fn write_remote(
+    name: &str,
+    remote_type: &str,
+    config: HashMap<String, Value>,
+    mut rclone_config: RcloneConfigFile,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    validate_remote_name(name)?;
+    let options = option_strings(config);
+    let backend = match rclone_backends(state.clone()) {
+        Ok(backends) => find_backend(&backends, remote_type)
+            .cloned()
+            .ok_or_else(|| format!("Unsupported remote type: {remote_type}"))?,
+        Err(e) => {
+            log::warn!("Saving remote '{name}' without the rclone backend schema: {e}");
+            fallback_backend(remote_type, options.keys().cloned())
+        }
     };
-
-    let remote_config = webdav.to_rclone_config_with_obscured_pass(state.clone())?;
+    let existing = rclone_config.remote(name);
+    let remote_config = build_remote_config(name, &backend, options, existing.as_ref())?;
     rclone_config.set_remote(remote_config);
-    rclone_config.save(state)?;
+    rclone_config.save(state)
+}
 
+/// Creates a remote of any rclone backend from its type and options.
+#[tauri::command]
+pub async fn rclone_create_remote(
+    name: String,
+    r#type: String,
+    config: HashMap<String, Value>,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let rclone_config = RcloneConfigFile::load_with_custom(state.clone())?;
+    write_remote(&name, &r#type, config, rclone_config, state)?;
     Ok(true)
 }
 
@@ -254,31 +274,67 @@ pub async fn rclone_create_remote(
 pub async fn rclone_update_remote(
     name: String,
     r#type: String,
-    config: RcloneWebdavConfigInput,
+    config: HashMap<String, Value>,
     state: State<'_, AppState>,
 ) -> Result<bool, String> {
-    let mut rclone_config = RcloneConfigFile::load_with_custom(state.clone())?;
+    let rclone_config = RcloneConfigFile::load_with_custom(state.clone())?;
 
     if !rclone_config.has_remote(&name) {
         return Err(format!("Remote '{name}' does not exist"));
     }
 
-    if r#type != "webdav" {
-        return Err(format!("Unsupported remote type: {}", r#type));
-    }
+    write_remote(&name, &r#type, config, rclone_config, state)?;
+    Ok(true)
+}
 
-    let webdav = WebDavRemoteConfig {
-        name: name.clone(),
-        url: config.url,
-        vendor: config.vendor,
-        user: config.user,
-        pass: config.pass,
-    };
+#[derive(Debug, Clone, Serialize)]
+pub struct RcloneConfigStatus {
+    pub encrypted: bool,
+    pub unlocked: bool,
+}
 
-    let remote_config = webdav.to_rclone_config_with_obscured_pass(state.clone())?;
-    rclone_config.set_remote(remote_config);
-    rclone_config.save(state)?;
+#[tauri::command]
+pub async fn rclone_config_status(
+    state: State<'_, AppState>,
+) -> Result<RcloneConfigStatus, String> {
+    let path = get_rclone_config_path_with_custom(state)?;
+    let encrypted = RcloneConfigFile::is_encrypted_at(&path)?;
+    Ok(RcloneConfigStatus {
+        encrypted,
+        unlocked: !encrypted || RcloneConfigFile::load_from_path(&path).is_ok(),
+    })
+}
+
+/// Checks the password of an encrypted rclone.conf and keeps it for this
+/// session, for reading the config and for rclone processes.
+#[tauri::command]
+pub async fn unlock_rclone_config(
+    password: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let path = get_rclone_config_path_with_custom(state)?;
+    let text =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read rclone config: {e}"))?;
+    if rclone_crypt::is_encrypted(&text) {
+        rclone_crypt::decrypt(&text, &password)?;
+        set_config_password(Some(password));
+        log::info!("Unlocked encrypted rclone config");
+    }
+    Ok(true)
+}
 
+/// Encrypts the rclone.conf with `password`, or changes the password of an
+/// already encrypted one.
+#[tauri::command]
+pub async fn encrypt_rclone_config(
+    password: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let mut rclone_config = RcloneConfigFile::load_with_custom(state.clone())?;
+    rclone_config.set_password(password.clone());
+    rclone_config.save(state)?;
+    set_config_password(Some(password));
+    log::info!("Encrypted rclone config");
     Ok(true)
 }
 
@@ -289,11 +345,7 @@ pub async fn rclone_delete_remote(
 ) -> Result<bool, String> {
     let mut rclone_config = RcloneConfigFile::load_with_custom(state.clone())?;
 
-    let process_id = get_mount_process_id(&name);
-    if PROCESS_MANAGER.is_registered(&process_id) {
-        let _ = PROCESS_MANAGER.stop(&process_id);
-        let _ = PROCESS_MANAGER.remove(&process_id);
-    }
+    let _ = unmount_remote(name.clone()).await;
 
     if rclone_config.remove_remote(&name).is_none() {
         return Err(format!("Remote '{name}' does not exist"));
@@ -303,11 +355,13 @@ pub async fn rclone_delete_remote(
     Ok(true)
 }
 
-#[tauri::command]
-pub async fn mount_remote(
-    config: MountProcessInput,
+/// An rclone process running `command` against the app's rclone.conf.
+fn rclone_process(
+    id: &str,
+    command: &str,
+    extra_args: Vec<String>,
     state: State<'_, AppState>,
-) -> Result<ProcessInfo, String> {
+) -> Result<ProcessConfig, String> {
     let binary_path = get_rclone_binary_path_with_custom(state.clone())
         .map_err(|e| format!("Failed to get rclone binary path: {e}"))?;
     let log_dir =
@@ -315,30 +369,12 @@ pub async fn mount_remote(
     let rclone_conf_path = get_rclone_config_path_with_custom(state)
         .map_err(|e| format!("Failed to get rclone config path: {e}"))?;
 
-    let mut args_vec = split_mount_args(config.args.clone());
-    insert_network_mode(&mut args_vec, config.network_mode);
-    ensure_vfs_write_cache(&mut args_vec);
-
-    let mount_point_opt = args_vec.iter().filter(|arg| !arg.starts_with('-')).nth(1);
-
-    if let Some(mount_point) = mount_point_opt {
-        let mount_path = Path::new(mount_point);
-        if !mount_path.exists()
-            && let Err(e) = fs::create_dir_all(mount_path)
-        {
-            return Err(format!(
-                "Failed to create mount point directory '{}': {}",
-                mount_point, e
-            ));
-        }
-    }
-
     let mut args: Vec<String> = vec![
-        "mount".into(),
+        command.into(),
         "--config".into(),
         rclone_conf_path.to_string_lossy().into_owned(),
     ];
-    args.extend(args_vec);
+    args.extend(extra_args);
 
     let log_file = log_dir.join("process_rclone.log");
 
@@ -348,9 +384,9 @@ pub async fn mount_remote(
         None
     };
 
-    let process_config = ProcessConfig {
-        id: config.id.clone(),
-        name: config.name.clone(),
+    Ok(ProcessConfig {
+        id: id.to_string(),
+        name: id.to_string(),
         bin_path: binary_path.to_string_lossy().into_owned(),
         args,
         log_file: log_file.to_string_lossy().into_owned(),
@@ -358,8 +394,98 @@ pub async fn mount_remote(
             .parent()
             .map(|p| p.to_string_lossy().into_owned()),
         env_vars,
+        secret_env_vars: config_password()
+            .map(|password| HashMap::from([("RCLONE_CONFIG_PASS".to_string(), password)]))
+            .unwrap_or_default(),
+    })
+}
+
+fn fs_remote_name(fs: &str) -> &str {
+    fs.split(':').next().unwrap_or("")
+}
+
+async fn daemon_mount_fs(remote_name: &str) -> Option<String> {
+    let daemon = running_daemon()?;
+    let mounts = daemon.list_mounts().await.ok()?;
+    mounts
+        .into_iter()
+        .find(|mount| fs_remote_name(&mount.fs) == remote_name)
+        .map(|mount| mount.fs)
+}
+
+/// The remote-control endpoint serving the mount of `remote_name`, with the
+/// fs to address it by when that's the shared daemon.
+pub(crate) async fn mount_endpoint(remote_name: &str) -> Option<(RcloneRc, Option<String>)> {
+    let process_id = get_mount_process_id(remote_name);
+    if PROCESS_MANAGER.is_running(&process_id) {
+        return endpoint(&process_id).map(|rc| (rc, None));
+    }
+    let fs = daemon_mount_fs(remote_name).await?;
+    Some((running_daemon()?, Some(fs)))
+}
+
+pub(crate) async fn is_mounted(remote_name: &str) -> bool {
+    PROCESS_MANAGER.is_running(&get_mount_process_id(remote_name))
+        || daemon_mount_fs(remote_name).await.is_some()
+}
+
+/// Unmounts every daemon mount of `remote_name`, if the daemon is running.
+async fn unmount_from_daemon(remote_name: &str) -> Result<(), String> {
+    let Some(daemon) = running_daemon() else {
+        return Ok(());
     };
+    for mount in daemon.list_mounts().await? {
+        if fs_remote_name(&mount.fs) == remote_name {
+            daemon.unmount(&mount.mount_point).await?;
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn mount_remote(
+    config: MountProcessInput,
+    state: State<'_, AppState>,
+) -> Result<ProcessInfo, String> {
+    let mut args_vec = split_mount_args(config.args.clone());
+    insert_network_mode(&mut args_vec, config.network_mode);
+    ensure_vfs_write_cache(&mut args_vec);
+    let mount_config = args_vec.first().and_then(|fs| {
+        state
+            .get_settings()?
+            .rclone
+            .mount_config?
+            .remove(fs_remote_name(fs))
+    });
+    if let Some(mount_config) = &mount_config {
+        insert_cache_flags(&mut args_vec, mount_config);
+    }
+
+    let mount_point_opt = args_vec.iter().filter(|arg| !arg.starts_with('-')).nth(1);
 
+    if let Some(mount_point) = mount_point_opt {
+        let mount_path = Path::new(mount_point);
+        // A dead mount makes the mount point look missing and can't be
+        // mounted over.
+        #[cfg(target_os = "linux")]
+        {
+            let stale_path = fuse::mountinfo_path(mount_path);
+            clean_stale_fuse_mounts(move |mount| mount.mount_point == stale_path).await?;
+        }
+        if !mount_path.exists()
+            && let Err(e) = fs::create_dir_all(mount_path)
+        {
+            return Err(format!(
+                "Failed to create mount point directory '{}': {}",
+                mount_point, e
+            ));
+        }
+    }
+
+    if let Some(fs) = args_vec.first() {
+        unmount_from_daemon(fs_remote_name(fs)).await?;
+    }
+    forget_endpoint(&config.id);
     if PROCESS_MANAGER.is_registered(&config.id) {
         let _ = PROCESS_MANAGER.stop(&config.id);
         sleep(Duration::from_millis(500)).await;
@@ -367,34 +493,120 @@ pub async fn mount_remote(
         sleep(Duration::from_millis(500)).await;
     }
 
-    PROCESS_MANAGER.register_and_start(process_config)
+    let use_daemon = state
+        .get_settings()
+        .is_some_and(|settings| settings.rclone.use_daemon());
+    if use_daemon {
+        let (params, ignored) = mount_params(&args_vec)?;
+        if ignored.is_empty() {
+            let daemon =
+                ensure_daemon(rclone_process(RCD_PROCESS_ID, "rcd", Vec::new(), state)?).await?;
+            daemon.mount(params).await?;
+            return daemon_process();
+        }
+        // Dropping the flags would change how the mount behaves.
+        log::info!(
+            "Mounting '{}' in its own process, rclone rcd can't apply {} per mount",
+            config.name,
+            ignored.join(" ")
+        );
+    }
+
+    // Each mount answers stats queries on its own remote-control endpoint.
+    let rc = RcloneRc::random_local()?;
+    insert_mount_flag(&mut args_vec, format!("--rc-addr={}", rc.addr()));
+    insert_mount_flag(&mut args_vec, "--rc".into());
+    let mut process_config = rclone_process(&config.id, "mount", args_vec, state)?;
+    process_config.name = config.name;
+    process_config.secret_env_vars.extend(rc.env_vars());
+    let info = PROCESS_MANAGER.register_and_start(process_config)?;
+    register_endpoint(&config.id, rc);
+    Ok(info)
 }
 
 #[tauri::command]
 pub async fn unmount_remote(name: String) -> Result<bool, String> {
-    let process_id = get_mount_process_id(&name);
+    // Unmounted on purpose, so the watchdog must not bring it back.
+    mount_watchdog::unwatch(&name);
+    stop_mount(&name).await?;
+    Ok(true)
+}
+
+/// Unmounts `remote_name` from the daemon and stops its mount process.
+pub(crate) async fn stop_mount(remote_name: &str) -> Result<(), String> {
+    unmount_from_daemon(remote_name).await?;
 
-    if !PROCESS_MANAGER.is_registered(&process_id) {
-        return Ok(true); // Already not mounted
+    let process_id = get_mount_process_id(remote_name);
+    forget_endpoint(&process_id);
+
+    if PROCESS_MANAGER.is_registered(&process_id) {
+        let info = PROCESS_MANAGER.get_status(&process_id)?;
+        if info.is_running {
+            PROCESS_MANAGER.stop(&process_id)?;
+        }
+        let _ = PROCESS_MANAGER.remove(&process_id);
     }
 
-    let info = PROCESS_MANAGER.get_status(&process_id)?;
-    if info.is_running {
-        PROCESS_MANAGER.stop(&process_id)?;
+    // Also when the process is long gone, e.g. after a crash.
+    #[cfg(target_os = "linux")]
+    {
+        let remote_name = remote_name.to_string();
+        clean_stale_fuse_mounts(move |mount| fs_remote_name(&mount.source) == remote_name).await?;
     }
 
-    let _ = PROCESS_MANAGER.remove(&process_id);
+    Ok(())
+}
 
-    Ok(true)
+/// Unmounts the stale rclone FUSE mounts `matches` picks, which rclone
+/// processes that died without unmounting leave behind.
+#[cfg(target_os = "linux")]
+pub async fn clean_stale_fuse_mounts(
+    matches: impl Fn(&FuseMount) -> bool + Send + 'static,
+) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || fuse::clean_stale_mounts(matches))
+        .await
+        .map_err(|e| format!("Failed to clean stale mounts: {e}"))?
+        .map(|_| ())
+}
+
+/// Mount points with a check still running. A hung mount can block
+/// `read_dir` for good, so each gets no new check until the last one returns.
+static PENDING_CHECKS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Marks a mount point as being checked until dropped.
+struct PendingCheck(String);
+
+impl PendingCheck {
+    fn start(mount_point: &str) -> Option<Self> {
+        PENDING_CHECKS
+            .lock()
+            .get_or_insert_with(HashSet::new)
+            .insert(mount_point.to_string())
+            .then(|| Self(mount_point.to_string()))
+    }
+}
+
+impl Drop for PendingCheck {
+    fn drop(&mut self) {
+        if let Some(pending) = PENDING_CHECKS.lock().as_mut() {
+            pending.remove(&self.0);
+        }
+    }
 }
 
 #[tauri::command]
 pub async fn check_mount_status(mount_point: String) -> Result<(), String> {
     let timeout_duration = Duration::from_secs(2);
     let mount_point_clone = mount_point.clone();
+    let Some(pending) = PendingCheck::start(&mount_point) else {
+        return Err(format!(
+            "Timeout: Network drive '{mount_point}' is still non-responsive"
+        ));
+    };
 
     let result = timeout(timeout_duration, async move {
         tokio::task::spawn_blocking(move || {
+            let _pending = pending;
             let path = Path::new(&mount_point_clone);
 
             if !path.exists() {
@@ -449,10 +661,65 @@ pub async fn check_mount_status(mount_point: String) -> Result<(), String> {
     }
 }
 
+/// Status of one mount, with stats from `rc` when it's mounted. `shared`
+/// tells that `rc` is the daemon serving other mounts as well.
+async fn mount_info(
+    remote_path: String,
+    mount_point: String,
+    process_id: String,
+    is_running: bool,
+    rc: Option<(RcloneRc, bool)>,
+) -> RcloneMountInfo {
+    let check_result = check_mount_status(mount_point.clone()).await;
+
+    let (status, error_msg) = match check_result {
+        Ok(()) => {
+            if is_running {
+                ("mounted".to_string(), None)
+            } else {
+                ("unmounted".to_string(), None)
+            }
+        }
+        Err(e) => {
+            if is_running {
+                ("error".to_string(), Some(e))
+            } else {
+                ("unmounted".to_string(), None)
+            }
+        }
+    };
+
+    let remote_name = fs_remote_name(&remote_path).to_string();
+
+    let stats = match rc {
+        Some((rc, shared)) if status == "mounted" => rc
+            .mount_stats(shared.then_some(remote_path.as_str()))
+            .await
+            .inspect_err(|e| log::debug!("Failed to read stats of mount '{remote_path}': {e}"))
+            .ok(),
+        _ => None,
+    };
+
+    RcloneMountInfo {
+        name: remote_name,
+        process_id,
+        remote_path,
+        mount_point,
+        status,
+        error_msg,
+        stats,
+    }
+}
+
 #[tauri::command]
 pub async fn get_mount_info_list(
     _state: State<'_, AppState>,
 ) -> Result<Vec<RcloneMountInfo>, String> {
+    Ok(collect_mount_infos().await)
+}
+
+/// Mounts of both the per-mount processes and the shared daemon.
+pub async fn collect_mount_infos() -> Vec<RcloneMountInfo> {
     let process_list = PROCESS_MANAGER.list();
     let mut set = JoinSet::new();
 
@@ -463,42 +730,31 @@ pub async fn get_mount_info_list(
 
         let args = &process.config.args;
         if args.len() >= 5 && args[0] == "mount" {
-            let remote_path = args[3].clone();
-            let mount_point = args[4].clone();
-            let process_id = process.id.clone();
-            let is_running = process.is_running;
-
-            set.spawn(async move {
-                let check_result = check_mount_status(mount_point.clone()).await;
-
-                let (status, error_msg) = match check_result {
-                    Ok(()) => {
-                        if is_running {
-                            ("mounted".to_string(), None)
-                        } else {
-                            ("unmounted".to_string(), None)
-                        }
-                    }
-                    Err(e) => {
-                        if is_running {
-                            ("error".to_string(), Some(e))
-                        } else {
-                            ("unmounted".to_string(), None)
-                        }
-                    }
-                };
-
-                let remote_name = remote_path.split(':').next().unwrap_or("").to_string();
+            set.spawn(mount_info(
+                args[3].clone(),
+                args[4].clone(),
+                process.id.clone(),
+                process.is_running,
+                endpoint(&process.id).map(|rc| (rc, false)),
+            ));
+        }
+    }
 
-                RcloneMountInfo {
-                    name: remote_name,
-                    process_id,
-                    remote_path,
-                    mount_point,
-                    status,
-                    error_msg,
+    if let Some(daemon) = running_daemon() {
+        match daemon.list_mounts().await {
+            Ok(mounts) => {
+                for mount in mounts {
+                    let process_id = get_mount_process_id(fs_remote_name(&mount.fs));
+                    set.spawn(mount_info(
+                        mount.fs,
+                        mount.mount_point,
+                        process_id,
+                        true,
+                        Some((daemon.clone(), true)),
+                    ));
                 }
-            });
+            }
+            Err(e) => log::warn!("Failed to list rclone rcd mounts: {e}"),
         }
     }
 
@@ -511,15 +767,164 @@ pub async fn get_mount_info_list(
 
     mount_infos.sort_by(|a, b| a.name.cmp(&b.name));
 
-    Ok(mount_infos)
+    mount_infos
 }
 
 pub async fn stop_all_rclone_mounts() -> Result<(), String> {
+    mount_watchdog::unwatch_all();
     let process_list = PROCESS_MANAGER.list();
     for process in process_list {
         if process.id.starts_with("rclone_mount_") && process.is_running {
+            forget_endpoint(&process.id);
             PROCESS_MANAGER.stop(&process.id)?;
         }
     }
-    Ok(())
+    stop_daemon().await
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_os = "windows")]
+    use super::insert_network_mode;
+    use super::{PendingCheck, ensure_vfs_write_cache, insert_cache_flags, split_mount_args};
+    use crate::conf::rclone::RcloneMountConfig;
+
+    #[test]
+    fn preserves_mount_positionals_while_splitting_extra_flags() {
+        let args = vec![
+            "--network-mode".into(),
+            r"C:\Mount Dir".into(),
+            "--log-file 'C:\\Log Dir\\rclone.log' --network-mode=false".into(),
+        ];
+
+        assert_eq!(
+            split_mount_args(args),
+            vec![
+                "--network-mode",
+                r"C:\Mount Dir",
+                "--log-file",
+                r"C:\Log Dir\rclone.log"
+            ]
+        );
+    }
+
+    #[test]
+    fn adds_default_vfs_write_cache() {
+        let mut args = vec![
+            "remote:".into(),
+            "mount-point".into(),
+            "--log-file".into(),
+            "--".into(),
+        ];
+
+        ensure_vfs_write_cache(&mut args);
+
+        assert_eq!(
+            args,
+            vec![
+                "remote:",
+                "mount-point",
+                "--vfs-cache-mode=writes",
+                "--log-file",
+                "--"
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_vfs_cache_mode_after_option_terminator() {
+        let mut args = vec![
+            "remote:".into(),
+            "mount-point".into(),
+            "--".into(),
+            "--vfs-cache-mode=full".into(),
+        ];
+
+        ensure_vfs_write_cache(&mut args);
+
+        assert_eq!(args[2], "--vfs-cache-mode=writes");
+    }
+
+    #[test]
+    fn extra_flags_override_cache_settings() {
+        let config: RcloneMountConfig = serde_json::from_value(serde_json::json!({
+            "name": "nas",
+            "type": "webdav",
+            "mountPoint": "/mnt/nas",
+            "volumeName": null,
+            "extraFlags": null,
+            "autoMount": null,
+            "cacheDir": "/cache",
+            "cacheMaxSize": "5G",
+        }))
+        .unwrap();
+        let mut args = vec![
+            "nas:".into(),
+            "/mnt/nas".into(),
+            "--vfs-cache-max-size".into(),
+            "1G".into(),
+        ];
+
+        insert_cache_flags(&mut args, &config);
+
+        assert_eq!(
+            args,
+            vec![
+                "nas:",
+                "/mnt/nas",
+                "--cache-dir=/cache",
+                "--vfs-cache-max-size",
+                "1G"
+            ]
+        );
+    }
+
+    #[test]
+    fn preserves_explicit_vfs_cache_mode() {
+        for cache_mode in [
+            vec!["--vfs-cache-mode=full".into()],
+            vec!["--vfs-cache-mode".into(), "off".into()],
+        ] {
+            let mut args = vec!["remote:".into(), "mount-point".into()];
+            args.extend(cache_mode);
+            let expected = args.clone();
+
+            ensure_vfs_write_cache(&mut args);
+
+            assert_eq!(args, expected);
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn inserts_network_mode_after_mount_positionals() {
+        let mut args = vec![
+            "remote:".into(),
+            "mount-point".into(),
+            "--log-file".into(),
+            "--".into(),
+        ];
+
+        insert_network_mode(&mut args, true);
+
+        assert_eq!(
+            args,
+            vec![
+                "remote:",
+                "mount-point",
+                "--network-mode=true",
+                "--log-file",
+                "--"
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_a_mount_point_once_at_a_time() {
+        let pending = PendingCheck::start("/mnt/hung").unwrap();
+        assert!(PendingCheck::start("/mnt/hung").is_none());
+        assert!(PendingCheck::start("/mnt/other").is_some());
+        drop(pending);
+        assert!(PendingCheck::start("/mnt/hung").is_some());
+    }
 }
@@ -0,0 +1,272 @@
+use std::path::{Component, Path};
+
+use percent_encoding::percent_decode_str;
+use tauri::State;
+use url::Url;
+
+use crate::api::client::ApiError;
+use crate::api::types::NewShare;
+use crate::cmd::network::network_access_info;
+use crate::cmd::openlist_api::profile_name;
+use crate::cmd::openlist_core::core_base_url;
+use crate::conf::config::MergedSettings;
+use crate::conf::core::OpenListCoreConfig;
+use crate::is_local_openlist_url;
+use crate::object::structs::{AppState, OpenListLink, OpenListLinkKind};
+use crate::utils::network::join_url_path;
+
+/// Maps a local path inside an rclone mount of OpenList's WebDAV endpoint to
+/// the OpenList path it shows.
+pub fn openlist_path_in_mount(
+    local: &Path,
+    mount_point: &Path,
+    webdav_url: &str,
+) -> Option<String> {
+    let relative = local.strip_prefix(mount_point).ok()?;
+    let url = Url::parse(webdav_url).ok()?;
+    let dav_root = url.path().trim_end_matches('/').strip_prefix("/dav")?;
+    if !dav_root.is_empty() && !dav_root.starts_with('/') {
+        return None;
+    }
+
+    let mut segments: Vec<String> = dav_root
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+        .collect();
+    segments.extend(
+        relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+                _ => None,
+            }),
+    );
+    Some(format!("/{}", segments.join("/")))
+}
+
+/// Whether two URLs point at the same OpenList server, treating every
+/// loopback spelling as the same host.
+pub fn same_server(a: &str, b: &str) -> bool {
+    let (Ok(left), Ok(right)) = (Url::parse(a), Url::parse(b)) else {
+        return false;
+    };
+    left.scheme() == right.scheme()
+        && left.port_or_known_default() == right.port_or_known_default()
+        && (left.host() == right.host() || (is_local_openlist_url(a) && is_local_openlist_url(b)))
+}
+
+/// Resolves `path` to a core profile and OpenList path. Paths inside a
+/// configured rclone mount are translated; any other absolute `/` path is
+/// taken as an OpenList path.
+fn resolve_target(
+    settings: &MergedSettings,
+    path: &str,
+    profile: Option<String>,
+) -> Result<(String, String), String> {
+    let mounts = settings.rclone.mount_config.iter().flatten();
+    for (_, mount) in mounts {
+        let Some(mount_point) = mount.mount_point.as_deref().filter(|p| !p.is_empty()) else {
+            continue;
+        };
+        let Some(openlist_path) =
+            openlist_path_in_mount(Path::new(path), Path::new(mount_point), &mount.url)
+        else {
+            continue;
+        };
+        let profile = match profile {
+            Some(profile) => profile,
+            None => settings
+                .core_profile_names()
+                .into_iter()
+                .find(|name| {
+                    settings
+                        .core_profile(name)
+                        .ok()
+                        .and_then(|config| core_base_url(config).ok())
+                        .is_some_and(|base| same_server(&base, &mount.url))
+                })
+                .ok_or_else(|| {
+                    format!(
+                        "Mount '{}' is not served by a configured OpenList profile",
+                        mount.name
+                    )
+                })?,
+        };
+        return Ok((profile, openlist_path));
+    }
+
+    if path.starts_with('/') {
+        Ok((profile_name(profile), path.to_string()))
+    } else {
+        Err(format!(
+            "'{path}' is neither inside an rclone mount nor an OpenList path"
+        ))
+    }
+}
+
+/// Base URLs a link can be built on, LAN addresses first since links are
+/// usually sent to other machines.
+fn link_bases(config: &OpenListCoreConfig) -> Result<Vec<String>, String> {
+    if config.is_remote() {
+        return Ok(vec![config.remote_url()?]);
+    }
+    let access = network_access_info(config)?;
+    let mut bases: Vec<String> = access.lan_urls.into_iter().map(|lan| lan.url).collect();
+    bases.extend(access.local_url);
+    if bases.is_empty() {
+        bases.push(core_base_url(config)?);
+    }
+    Ok(bases)
+}
+
+pub(crate) async fn create_link(
+    state: &AppState,
+    path: &str,
+    kind: OpenListLinkKind,
+    expires_in_secs: Option<u64>,
+    password: Option<String>,
+    profile: Option<String>,
+) -> Result<OpenListLink, ApiError> {
+    let settings = state.get_settings().ok_or("Failed to read app settings")?;
+    let (profile, path) = resolve_target(&settings, path, profile)?;
+    let bases = link_bases(settings.core_profile(&profile)?)?;
+    let client = state.api_client(&profile)?;
+    let password = password.filter(|password| !password.is_empty());
+
+    let (urls, expires_at) = match kind {
+        OpenListLinkKind::Direct => {
+            if password.is_some() || expires_in_secs.is_some() {
+                return Err(
+                    "Direct links can't have a password or expiry, create a share link instead"
+                        .into(),
+                );
+            }
+            let file = client.get_file(&path).await?;
+            if file.is_dir {
+                return Err(format!("'{path}' is a folder, only files have direct links").into());
+            }
+            let urls = bases
+                .iter()
+                .map(|base| {
+                    let url = join_url_path(&join_url_path(base, "d")?, &path)?;
+                    Ok(if file.sign.is_empty() {
+                        url
+                    } else {
+                        format!("{url}?sign={}", file.sign)
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            (urls, None)
+        }
+        OpenListLinkKind::Share => {
+            let expires_at = expires_in_secs.map(|secs| {
+                (chrono::Utc::now() + chrono::Duration::seconds(secs as i64)).to_rfc3339()
+            });
+            let id = client
+                .create_share(&NewShare {
+                    files: vec![path.clone()],
+                    expires: expires_at.clone(),
+                    pwd: password.clone().unwrap_or_default(),
+                    remark: "Shared from OpenList Desktop".into(),
+                    ..Default::default()
+                })
+                .await?;
+            let urls = bases
+                .iter()
+                .map(|base| join_url_path(&join_url_path(base, "@s")?, &id))
+                .collect::<Result<Vec<_>, String>>()?;
+            (urls, expires_at)
+        }
+    };
+
+    let mut urls = urls.into_iter();
+    Ok(OpenListLink {
+        kind,
+        profile,
+        path,
+        url: urls.next().unwrap_or_default(),
+        alternate_urls: urls.collect(),
+        expires_at,
+        password_protected: password.is_some(),
+    })
+}
+
+/// Builds a direct download link or a share link for a path inside an rclone
+/// mount of OpenList, or for an OpenList path.
+#[tauri::command]
+pub async fn create_openlist_link(
+    path: String,
+    kind: OpenListLinkKind,
+    expires_in_secs: Option<u64>,
+    password: Option<String>,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<OpenListLink, ApiError> {
+    create_link(&state, &path, kind, expires_in_secs, password, profile).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{openlist_path_in_mount, same_server};
+
+    #[test]
+    fn maps_mounted_paths_to_openlist_paths() {
+        assert_eq!(
+            openlist_path_in_mount(
+                Path::new("/mnt/openlist/Team/report.pdf"),
+                Path::new("/mnt/openlist"),
+                "http://127.0.0.1:5244/dav",
+            )
+            .as_deref(),
+            Some("/Team/report.pdf")
+        );
+        assert_eq!(
+            openlist_path_in_mount(
+                Path::new("/mnt/media/a b.mkv"),
+                Path::new("/mnt/media"),
+                "http://127.0.0.1:5244/dav/My%20Media/",
+            )
+            .as_deref(),
+            Some("/My Media/a b.mkv")
+        );
+        assert_eq!(
+            openlist_path_in_mount(
+                Path::new("/home/me/file"),
+                Path::new("/mnt/openlist"),
+                "http://127.0.0.1:5244/dav",
+            ),
+            None
+        );
+        assert_eq!(
+            openlist_path_in_mount(
+                Path::new("/mnt/other/file"),
+                Path::new("/mnt/other"),
+                "https://example.com/webdav",
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn loopback_spellings_are_the_same_server() {
+        assert!(same_server(
+            "http://localhost:5244",
+            "http://127.0.0.1:5244/dav"
+        ));
+        assert!(!same_server(
+            "http://localhost:5244",
+            "http://127.0.0.1:5245/dav"
+        ));
+        assert!(same_server(
+            "https://files.example.com",
+            "https://files.example.com:443/dav"
+        ));
+        assert!(!same_server(
+            "http://192.168.1.2:5244",
+            "http://127.0.0.1:5244"
+        ));
+    }
+}
@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, State};
+
+use crate::api::client::ApiError;
+use crate::cmd::openlist_api::profile_name;
+use crate::core::transfers::{self, Transfer};
+use crate::object::structs::AppState;
+
+/// Queues a local file, or every file below a local folder, for upload into
+/// `remote_dir`.
+#[tauri::command]
+pub async fn upload_to_openlist(
+    local_path: String,
+    remote_dir: String,
+    profile: Option<String>,
+    app_handle: AppHandle,
+) -> Result<Vec<Transfer>, String> {
+    let local = PathBuf::from(&local_path);
+    if !local.exists() {
+        return Err(format!("'{local_path}' does not exist"));
+    }
+    transfers::enqueue_upload(&app_handle, &profile_name(profile), &local, &remote_dir)
+}
+
+/// Queues an OpenList file, or every file below an OpenList folder, for
+/// download into `local_dir`.
+#[tauri::command]
+pub async fn download_from_openlist(
+    remote_path: String,
+    local_dir: String,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<Vec<Transfer>, ApiError> {
+    let profile = profile_name(profile);
+    let client = state.api_client(&profile)?;
+    let local_dir = PathBuf::from(&local_dir);
+    if !local_dir.is_dir() {
+        return Err(format!("'{}' is not a directory", local_dir.display()).into());
+    }
+    transfers::enqueue_download(&app_handle, &client, &profile, &remote_path, &local_dir).await
+}
+
+#[tauri::command]
+pub async fn list_transfers(state: State<'_, AppState>) -> Result<Vec<Transfer>, String> {
+    Ok(state.transfers.list())
+}
+
+#[tauri::command]
+pub async fn pause_transfer(id: u64, state: State<'_, AppState>) -> Result<Transfer, String> {
+    state.transfers.pause(id)
+}
+
+#[tauri::command]
+pub async fn resume_transfer(
+    id: u64,
+    state: State<'_, AppState>,
+    app_handle: AppHandle,
+) -> Result<Transfer, String> {
+    let transfer = state.transfers.resume(id)?;
+    transfers::pump(&app_handle);
+    Ok(transfer)
+}
+
+#[tauri::command]
+pub async fn cancel_transfer(id: u64, state: State<'_, AppState>) -> Result<Transfer, String> {
+    state.transfers.cancel(id)
+}
+
+#[tauri::command]
+pub async fn clear_finished_transfers(state: State<'_, AppState>) -> Result<(), String> {
+    state.transfers.clear_finished();
+    Ok(())
+}
@@ -0,0 +1,310 @@
+use serde_json::Value;
+use tauri::State;
+
+use crate::api::client::{ApiError, OpenListClient};
+use crate::api::types::{
+    NewUser, ROLE_ADMIN, ROLE_GENERAL, USER_PERMISSION_BITS, UserInfo, UserPatch,
+};
+use crate::cmd::admin_pass::{
+    generate_random_password, random_admin_password, run_openlist_cli, store_admin_password,
+};
+use crate::cmd::openlist_api::profile_name;
+use crate::cmd::openlist_core::get_openlist_core_process_status;
+use crate::object::structs::AppState;
+
+fn validate_permission(permission: i64) -> Result<(), String> {
+    if !(0..1 << USER_PERMISSION_BITS).contains(&permission) {
+        return Err(format!("Invalid permission bits {permission:#b}"));
+    }
+    Ok(())
+}
+
+fn normalize_base_path(base_path: &str) -> String {
+    let trimmed = base_path.trim().trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".into()
+    } else if trimmed.starts_with('/') {
+        trimmed.to_string()
+    } else {
+        format!("/{trimmed}")
+    }
+}
+
+/// Applies `patch` to a user as returned by the admin API, keeping the
+/// fields the desktop doesn't manage.
+pub fn apply_user_patch(user: &mut Value, patch: &UserPatch) -> Result<(), String> {
+    let object = user.as_object_mut().ok_or("Unexpected user record")?;
+    if let Some(base_path) = &patch.base_path {
+        object.insert("base_path".into(), normalize_base_path(base_path).into());
+    }
+    if let Some(permission) = patch.permission {
+        validate_permission(permission)?;
+        object.insert("permission".into(), permission.into());
+    }
+    if let Some(disabled) = patch.disabled {
+        object.insert("disabled".into(), disabled.into());
+    }
+    // An empty password tells the core to keep the current one.
+    let password = patch.password.clone().unwrap_or_default();
+    object.insert("password".into(), password.into());
+    Ok(())
+}
+
+/// Whether the profile's core can answer admin API calls right now.
+pub(crate) async fn core_online(profile: &str, state: &AppState) -> Result<bool, String> {
+    let settings = state.get_settings().ok_or("Failed to read app settings")?;
+    if settings.core_profile(profile)?.is_remote() {
+        return Ok(true);
+    }
+    Ok(get_openlist_core_process_status(profile)
+        .await
+        .is_ok_and(|info| info.is_running))
+}
+
+/// The desktop signs in to local cores with their stored admin password, so
+/// it has to follow admin password changes there.
+fn keeps_admin_password(profile: &str, state: &AppState) -> bool {
+    state.get_settings().is_some_and(|settings| {
+        settings
+            .core_profile(profile)
+            .is_ok_and(|config| !config.is_remote())
+    })
+}
+
+fn offline_error(profile: &str) -> ApiError {
+    format!(
+        "OpenList Core '{profile}' is not running; only a generated admin password or clearing the admin's 2FA work while it is stopped"
+    )
+    .into()
+}
+
+async fn raw_user(client: &OpenListClient, id: u64) -> Result<Value, ApiError> {
+    client
+        .list_users_raw()
+        .await?
+        .content
+        .into_iter()
+        .find(|user| user.get("id").and_then(Value::as_u64) == Some(id))
+        .ok_or_else(|| format!("OpenList user {id} not found").into())
+}
+
+/// Resolves `id`, or the admin account when it's `None`.
+async fn target_user(client: &OpenListClient, id: Option<u64>) -> Result<Value, ApiError> {
+    match id {
+        Some(id) => raw_user(client, id).await,
+        None => client
+            .list_users_raw()
+            .await?
+            .content
+            .into_iter()
+            .find(|user| user.get("role").and_then(Value::as_i64) == Some(ROLE_ADMIN))
+            .ok_or_else(|| "OpenList has no admin account".into()),
+    }
+}
+
+fn is_admin(user: &Value) -> bool {
+    user.get("role").and_then(Value::as_i64) == Some(ROLE_ADMIN)
+}
+
+/// Changes a user's password, or the admin's when `id` is `None`, through
+/// the admin API. Returns whether the user is the admin.
+pub(crate) async fn set_user_password(
+    client: &OpenListClient,
+    id: Option<u64>,
+    password: &str,
+) -> Result<bool, ApiError> {
+    let mut user = target_user(client, id).await?;
+    apply_user_patch(
+        &mut user,
+        &UserPatch {
+            password: Some(password.to_string()),
+            ..Default::default()
+        },
+    )?;
+    client.update_user(&user).await?;
+    Ok(is_admin(&user))
+}
+
+#[tauri::command]
+pub async fn list_openlist_users(
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<UserInfo>, ApiError> {
+    let profile = profile_name(profile);
+    if !core_online(&profile, &state).await? {
+        return Err(offline_error(&profile));
+    }
+    Ok(state.api_client(&profile)?.list_users().await?.content)
+}
+
+#[tauri::command]
+pub async fn create_openlist_user(
+    mut user: NewUser,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<UserInfo, ApiError> {
+    let profile = profile_name(profile);
+    if !core_online(&profile, &state).await? {
+        return Err(offline_error(&profile));
+    }
+    user.username = user.username.trim().to_string();
+    if user.username.is_empty() {
+        return Err("Username cannot be empty".into());
+    }
+    if user.password.is_empty() {
+        return Err("Password cannot be empty".into());
+    }
+    validate_permission(user.permission)?;
+    user.base_path = normalize_base_path(&user.base_path);
+    user.role = ROLE_GENERAL;
+
+    let client = state.api_client(&profile)?;
+    client.create_user(&user).await?;
+    client
+        .list_users()
+        .await?
+        .content
+        .into_iter()
+        .find(|created| created.username == user.username)
+        .ok_or_else(|| format!("OpenList user '{}' was not created", user.username).into())
+}
+
+/// Changes a user's base path, permissions, password or disabled flag.
+#[tauri::command]
+pub async fn update_openlist_user(
+    id: u64,
+    patch: UserPatch,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<UserInfo, ApiError> {
+    let profile = profile_name(profile);
+    if !core_online(&profile, &state).await? {
+        return Err(offline_error(&profile));
+    }
+    let client = state.api_client(&profile)?;
+    let mut user = raw_user(&client, id).await?;
+    if is_admin(&user) && patch.disabled == Some(true) {
+        return Err("The admin account cannot be disabled".into());
+    }
+    apply_user_patch(&mut user, &patch)?;
+    client.update_user(&user).await?;
+    if is_admin(&user)
+        && keeps_admin_password(&profile, &state)
+        && let Some(password) = patch.password.as_deref().filter(|p| !p.is_empty())
+    {
+        store_admin_password(&profile, password, &state);
+    }
+    serde_json::from_value(user).map_err(|e| ApiError::Decode {
+        message: format!("Failed to decode OpenList user: {e}"),
+    })
+}
+
+/// Sets a new password, generating one when none is given, and returns it.
+/// `id` defaults to the admin account, which can also get a generated
+/// password through the `openlist` CLI while a local core is stopped.
+#[tauri::command]
+pub async fn reset_openlist_user_password(
+    id: Option<u64>,
+    password: Option<String>,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, ApiError> {
+    let profile = profile_name(profile);
+    let password = password.filter(|password| !password.is_empty());
+
+    let (password, admin) = if core_online(&profile, &state).await? {
+        let password = password.unwrap_or_else(|| generate_random_password(16));
+        let client = state.api_client(&profile)?;
+        let admin = set_user_password(&client, id, &password).await?;
+        (password, admin)
+    } else if id.is_none() && password.is_none() {
+        let settings = state.get_settings().ok_or("Failed to read app settings")?;
+        (
+            random_admin_password(settings.core_profile(&profile)?)?,
+            true,
+        )
+    } else {
+        return Err(offline_error(&profile));
+    };
+
+    if admin && keeps_admin_password(&profile, &state) {
+        store_admin_password(&profile, &password, &state);
+    }
+    log::info!("Reset OpenList password for user {id:?} of profile '{profile}'");
+    Ok(password)
+}
+
+/// Turns off two-factor authentication for a user, or for the admin account
+/// when `id` is `None`.
+#[tauri::command]
+pub async fn clear_openlist_user_2fa(
+    id: Option<u64>,
+    profile: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), ApiError> {
+    let profile = profile_name(profile);
+    if core_online(&profile, &state).await? {
+        let client = state.api_client(&profile)?;
+        let id = match id {
+            Some(id) => id,
+            None => target_user(&client, None)
+                .await?
+                .get("id")
+                .and_then(Value::as_u64)
+                .ok_or("OpenList admin account has no id")?,
+        };
+        client.cancel_user_2fa(id).await
+    } else if id.is_none() {
+        let settings = state.get_settings().ok_or("Failed to read app settings")?;
+        run_openlist_cli(settings.core_profile(&profile)?, &["cancel2fa"])?;
+        Ok(())
+    } else {
+        Err(offline_error(&profile))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{apply_user_patch, normalize_base_path};
+    use crate::api::types::UserPatch;
+
+    #[test]
+    fn patches_only_the_given_fields() {
+        let mut user = json!({
+            "id": 3,
+            "username": "kid",
+            "password": "",
+            "base_path": "/",
+            "role": 0,
+            "permission": 0,
+            "disabled": false,
+            "sso_id": "abc",
+        });
+        let patch = UserPatch {
+            base_path: Some("Family/Photos/".into()),
+            permission: Some(0b1000),
+            ..Default::default()
+        };
+        apply_user_patch(&mut user, &patch).unwrap();
+        assert_eq!(user["base_path"], "/Family/Photos");
+        assert_eq!(user["permission"], 8);
+        assert_eq!(user["disabled"], false);
+        assert_eq!(user["sso_id"], "abc");
+        assert_eq!(user["password"], "");
+
+        let invalid = UserPatch {
+            permission: Some(1 << 20),
+            ..Default::default()
+        };
+        assert!(apply_user_patch(&mut user, &invalid).is_err());
+    }
+
+    #[test]
+    fn normalizes_base_paths() {
+        assert_eq!(normalize_base_path(""), "/");
+        assert_eq!(normalize_base_path("/"), "/");
+        assert_eq!(normalize_base_path("team"), "/team");
+    }
+}
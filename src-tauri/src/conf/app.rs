@@ -12,6 +12,8 @@ pub struct AppConfig {
     pub log_filter_level: Option<String>,
     pub log_filter_source: Option<String>,
     pub hide_dock_icon: Option<bool>,
+    pub task_notifications: Option<bool>,
+    pub transfer_concurrency: Option<u32>,
 }
 
 impl AppConfig {
@@ -27,6 +29,8 @@ impl AppConfig {
             log_filter_level: Some("all".to_string()),
             log_filter_source: Some("openlist".to_string()),
             hide_dock_icon: Some(false),
+            task_notifications: Some(true),
+            transfer_concurrency: Some(3),
         }
     }
 }
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
@@ -6,12 +7,18 @@ use super::app::AppConfig;
 use crate::conf::core::OpenListCoreConfig;
 use crate::conf::rclone::RcloneConfig;
 use crate::utils::path::{app_config_file_path, get_default_openlist_data_dir};
+use crate::utils::secret;
+
+/// Name under which the top-level `openlist` config is exposed as a profile.
+pub const DEFAULT_CORE_PROFILE: &str = "default";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MergedSettings {
     pub openlist: OpenListCoreConfig,
     pub rclone: RcloneConfig,
     pub app: AppConfig,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, OpenListCoreConfig>,
 }
 
 impl Default for MergedSettings {
@@ -26,7 +33,92 @@ impl MergedSettings {
             openlist: OpenListCoreConfig::new(),
             rclone: RcloneConfig::new(),
             app: AppConfig::new(),
+            profiles: BTreeMap::new(),
+        }
+    }
+
+    /// Profile names with the default profile first.
+    pub fn core_profile_names(&self) -> Vec<String> {
+        std::iter::once(DEFAULT_CORE_PROFILE.to_string())
+            .chain(self.profiles.keys().cloned())
+            .collect()
+    }
+
+    pub fn core_profile(&self, name: &str) -> Result<&OpenListCoreConfig, String> {
+        if name == DEFAULT_CORE_PROFILE {
+            return Ok(&self.openlist);
+        }
+        self.profiles
+            .get(name)
+            .ok_or_else(|| format!("OpenList Core profile '{name}' not found"))
+    }
+
+    pub fn core_profile_mut(&mut self, name: &str) -> Result<&mut OpenListCoreConfig, String> {
+        if name == DEFAULT_CORE_PROFILE {
+            return Ok(&mut self.openlist);
+        }
+        self.profiles
+            .get_mut(name)
+            .ok_or_else(|| format!("OpenList Core profile '{name}' not found"))
+    }
+
+    /// The admin password the desktop signs in to a local profile's core with.
+    pub fn admin_password(&self, profile: &str) -> Option<&str> {
+        let password = if profile == DEFAULT_CORE_PROFILE {
+            self.app.admin_password.as_deref()
+        } else {
+            self.profiles.get(profile)?.admin_password.as_deref()
+        };
+        password.filter(|password| !password.is_empty())
+    }
+
+    pub fn set_admin_password(&mut self, profile: &str, password: &str) -> Result<(), String> {
+        let slot = if profile == DEFAULT_CORE_PROFILE {
+            &mut self.app.admin_password
+        } else {
+            &mut self.core_profile_mut(profile)?.admin_password
+        };
+        *slot = Some(password.to_string());
+        Ok(())
+    }
+
+    fn core_profiles_mut(&mut self) -> impl Iterator<Item = &mut OpenListCoreConfig> {
+        std::iter::once(&mut self.openlist).chain(self.profiles.values_mut())
+    }
+
+    pub fn validate_core_profiles(&self) -> Result<(), String> {
+        let mut data_dirs = Vec::new();
+        for name in self.core_profile_names() {
+            let config = self.core_profile(&name)?;
+            if name != DEFAULT_CORE_PROFILE {
+                if name.is_empty()
+                    || !name
+                        .chars()
+                        .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_'))
+                {
+                    return Err(format!(
+                        "Invalid profile name '{name}', use letters, digits, '-' or '_'"
+                    ));
+                }
+                if !config.is_remote() && config.data_dir.trim().is_empty() {
+                    return Err(format!("Profile '{name}' needs its own data directory"));
+                }
+            }
+            config
+                .validate()
+                .map_err(|e| format!("Profile '{name}': {e}"))?;
+            if config.is_remote() {
+                continue;
+            }
+            let data_dir = Self::get_data_config_path_for_dir(Some(&config.data_dir))?;
+            if let Some((other, _)) = data_dirs.iter().find(|(_, dir)| *dir == data_dir) {
+                return Err(format!(
+                    "Profiles '{other}' and '{name}' use the same data directory"
+                ));
+            }
+            data_dirs.push((name, data_dir));
         }
+        Ok(())
     }
 
     pub fn get_data_config_path_for_dir(data_dir: Option<&str>) -> Result<PathBuf, String> {
@@ -61,13 +153,94 @@ impl MergedSettings {
             .map(|port| port as u16))
     }
 
+    pub(crate) fn get_address_from_data_config_for_dir(
+        data_dir: Option<&str>,
+    ) -> Result<Option<String>, String> {
+        let config = Self::read_data_config_for_dir(data_dir)?;
+        Ok(config
+            .get("scheme")
+            .and_then(|scheme| scheme.get("address"))
+            .and_then(|address| address.as_str())
+            .map(|address| address.to_string()))
+    }
+
+    /// Pulls the port and bind address edited in the core's own config.json
+    /// back into the profile.
+    fn sync_from_data_config(config: &mut OpenListCoreConfig) -> bool {
+        if config.is_remote() {
+            return false;
+        }
+        let data_dir = Some(config.data_dir.as_str()).filter(|d| !d.is_empty());
+        let mut changed = false;
+
+        if let Ok(Some(port)) = Self::get_port_from_data_config_for_dir(data_dir, false)
+            && config.port != port
+        {
+            config.port = port;
+            changed = true;
+        }
+
+        if let Ok(Some(address)) = Self::get_address_from_data_config_for_dir(data_dir) {
+            changed |= config.sync_bind_address(&address);
+        }
+        changed
+    }
+
+    /// Passwords kept encrypted in settings.json: the admin passwords of the
+    /// local profiles and the one of every remote instance.
+    fn secrets_mut(&mut self) -> Vec<&mut Option<String>> {
+        let mut secrets = vec![&mut self.app.admin_password];
+        if let Some(remote) = self.openlist.remote.as_mut() {
+            secrets.push(&mut remote.password);
+        }
+        for config in self.profiles.values_mut() {
+            secrets.push(&mut config.admin_password);
+            if let Some(remote) = config.remote.as_mut() {
+                secrets.push(&mut remote.password);
+            }
+        }
+        secrets
+    }
+
+    /// Decrypts the stored passwords in place, returning whether any was
+    /// still kept in plaintext and should be saved again.
+    fn decrypt_secrets(&mut self) -> bool {
+        let mut plaintext = false;
+        for value in self.secrets_mut() {
+            let Some(stored) = value.clone().filter(|p| !p.is_empty()) else {
+                continue;
+            };
+            if !secret::is_encrypted(&stored) {
+                plaintext = true;
+                continue;
+            }
+            match secret::decrypt(&stored) {
+                Ok(password) => *value = Some(password),
+                Err(e) => {
+                    log::warn!("Discarding a stored password: {e}");
+                    *value = None;
+                }
+            }
+        }
+        plaintext
+    }
+
     pub fn save(&self) -> Result<(), String> {
         let path = app_config_file_path().map_err(|e| e.to_string())?;
         if let Some(dir) = path.parent() {
             std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
         }
+        let mut stored = self.clone();
+        for value in stored.secrets_mut() {
+            if let Some(password) = value
+                .as_deref()
+                .filter(|p| !p.is_empty() && !secret::is_encrypted(p))
+            {
+                *value = Some(secret::encrypt(password)?);
+            }
+        }
         let file = std::fs::File::create(&path).map_err(|e| e.to_string())?;
-        serde_json::to_writer_pretty(file, &self).map_err(|e| e.to_string())
+        serde_json::to_writer_pretty(file, &stored).map_err(|e| e.to_string())
     }
 
     pub fn load() -> Result<Self, String> {
@@ -85,25 +258,141 @@ impl MergedSettings {
             default
         };
 
+        if settings.decrypt_secrets() {
+            match settings.save() {
+                Ok(()) => log::info!("Encrypted the plaintext passwords in settings"),
+                Err(e) => log::warn!("Failed to encrypt the stored passwords: {e}"),
+            }
+        }
+
         if settings.rclone.normalize_network_mode()
             && let Err(e) = settings.save()
         {
             log::warn!("Failed to persist migrated Rclone network mode settings: {e}");
         }
 
-        let data_dir = if settings.openlist.data_dir.is_empty() {
-            None
-        } else {
-            Some(settings.openlist.data_dir.as_str())
-        };
-
-        if let Ok(Some(port)) = Self::get_port_from_data_config_for_dir(data_dir, false)
-            && settings.openlist.port != port
+        if settings.rclone.clear_stored_passwords()
+            && let Err(e) = settings.save()
         {
-            settings.openlist.port = port;
+            log::warn!("Failed to remove Rclone passwords from settings: {e}");
+        }
+
+        let mut changed = false;
+        for config in settings.core_profiles_mut() {
+            changed |= Self::sync_from_data_config(config);
+        }
+        if changed {
             settings.save()?;
         }
 
         Ok(settings)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::MergedSettings;
+    use crate::conf::core::{ConnectionMode, OpenListCoreConfig, RemoteInstanceConfig};
+
+    fn profile(data_dir: &str, port: u16) -> OpenListCoreConfig {
+        OpenListCoreConfig {
+            data_dir: data_dir.into(),
+            port,
+            ..OpenListCoreConfig::new()
+        }
+    }
+
+    #[test]
+    fn lists_default_profile_first() {
+        let mut settings = MergedSettings::new();
+        settings
+            .profiles
+            .insert("team".into(), profile("/srv/team", 5300));
+        settings
+            .profiles
+            .insert("personal".into(), profile("/srv/personal", 5250));
+        assert_eq!(
+            settings.core_profile_names(),
+            vec!["default", "personal", "team"]
+        );
+        assert_eq!(settings.core_profile("team").unwrap().port, 5300);
+        assert!(settings.core_profile("missing").is_err());
+    }
+
+    #[test]
+    fn migrates_plaintext_remote_passwords() {
+        let mut settings = MergedSettings::new();
+        assert!(!settings.decrypt_secrets());
+
+        let remote = OpenListCoreConfig {
+            connection_mode: ConnectionMode::Remote,
+            remote: Some(RemoteInstanceConfig {
+                url: "https://nas.example.com".into(),
+                username: Some("admin".into()),
+                password: Some("hunter2".into()),
+            }),
+            ..profile("", 5244)
+        };
+        settings.profiles.insert("nas".into(), remote);
+        assert_eq!(settings.secrets_mut().len(), 3);
+        // Plaintext values are kept as they are and flagged for saving.
+        assert!(settings.decrypt_secrets());
+        assert_eq!(
+            settings.profiles["nas"]
+                .remote
+                .as_ref()
+                .unwrap()
+                .password
+                .as_deref(),
+            Some("hunter2")
+        );
+    }
+
+    #[test]
+    fn signs_in_to_each_local_profile_with_its_own_password() {
+        let mut settings = MergedSettings::new();
+        settings
+            .profiles
+            .insert("team".into(), profile("/srv/team", 5300));
+        settings.set_admin_password("default", "first").unwrap();
+        assert_eq!(settings.admin_password("team"), None);
+
+        settings.set_admin_password("team", "second").unwrap();
+        assert_eq!(settings.admin_password("default"), Some("first"));
+        assert_eq!(settings.admin_password("team"), Some("second"));
+        assert!(settings.set_admin_password("missing", "x").is_err());
+
+        settings.profiles.get_mut("team").unwrap().admin_password = Some("plain".into());
+        assert_eq!(settings.secrets_mut().len(), 2);
+        assert!(settings.decrypt_secrets());
+    }
+
+    #[test]
+    fn rejects_profiles_sharing_a_data_directory() {
+        let mut settings = MergedSettings::new();
+        settings.openlist = profile("/srv/openlist", 5244);
+        settings
+            .profiles
+            .insert("team".into(), profile("/srv/team", 5300));
+        assert!(settings.validate_core_profiles().is_ok());
+
+        settings
+            .profiles
+            .insert("copy".into(), profile("/srv/openlist", 5301));
+        assert!(settings.validate_core_profiles().is_err());
+    }
+
+    #[test]
+    fn named_profiles_need_a_valid_name_and_data_dir() {
+        let mut settings = MergedSettings::new();
+        settings.openlist = profile("/srv/openlist", 5244);
+        settings.profiles.insert("team".into(), profile("", 5300));
+        assert!(settings.validate_core_profiles().is_err());
+
+        settings.profiles.clear();
+        settings
+            .profiles
+            .insert("bad name".into(), profile("/srv/team", 5300));
+        assert!(settings.validate_core_profiles().is_err());
+    }
+}
@@ -1,4 +1,39 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+
 use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::utils::args::{validate_core_args, validate_env_vars};
+use crate::utils::network::{interface_bind_address, interface_name_for_ip};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BindMode {
+    Loopback,
+    #[default]
+    All,
+    Interface,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionMode {
+    /// The desktop app runs and manages its own OpenList process.
+    #[default]
+    Local,
+    /// The desktop app only talks to an OpenList server running elsewhere.
+    Remote,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RemoteInstanceConfig {
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OpenListCoreConfig {
@@ -7,6 +42,24 @@ pub struct OpenListCoreConfig {
     pub binary_path: Option<String>,
     pub auto_launch: bool,
     pub ssl_enabled: bool,
+    #[serde(default)]
+    pub auto_select_port: bool,
+    #[serde(default)]
+    pub bind_mode: BindMode,
+    #[serde(default)]
+    pub bind_interface: Option<String>,
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub connection_mode: ConnectionMode,
+    #[serde(default)]
+    pub remote: Option<RemoteInstanceConfig>,
+    /// Admin password of a local profile's own core. The default profile
+    /// keeps it in `app.admin_password`.
+    #[serde(default)]
+    pub admin_password: Option<String>,
 }
 
 impl OpenListCoreConfig {
@@ -17,6 +70,159 @@ impl OpenListCoreConfig {
             binary_path: None,
             auto_launch: false,
             ssl_enabled: false,
+            auto_select_port: false,
+            bind_mode: BindMode::All,
+            bind_interface: None,
+            extra_args: Vec::new(),
+            env: HashMap::new(),
+            connection_mode: ConnectionMode::Local,
+            remote: None,
+            admin_password: None,
+        }
+    }
+
+    pub fn is_remote(&self) -> bool {
+        self.connection_mode == ConnectionMode::Remote
+    }
+
+    /// Base URL of the configured remote instance, without a trailing slash.
+    pub fn remote_url(&self) -> Result<String, String> {
+        let raw = self
+            .remote
+            .as_ref()
+            .map(|remote| remote.url.trim())
+            .filter(|url| !url.is_empty())
+            .ok_or("No URL configured for the remote OpenList instance")?;
+        let url =
+            Url::parse(raw).map_err(|e| format!("Invalid remote OpenList URL '{raw}': {e}"))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+            return Err(format!(
+                "Remote OpenList URL must be an http(s) address, got '{raw}'"
+            ));
+        }
+        Ok(url.as_str().trim_end_matches('/').to_string())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.is_remote() {
+            self.remote_url()?;
+        }
+        self.launch_args().map(|_| ())
+    }
+
+    /// Extra `openlist server` arguments, split and checked against the flags
+    /// the app manages itself.
+    pub fn launch_args(&self) -> Result<Vec<String>, String> {
+        validate_env_vars(&self.env)?;
+        validate_core_args(&self.extra_args)
+    }
+
+    /// Resolves the value written to `scheme.address` in the core's data config.
+    pub fn bind_address(&self) -> Result<IpAddr, String> {
+        match self.bind_mode {
+            BindMode::Loopback => Ok(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            BindMode::All => Ok(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            BindMode::Interface => {
+                let name = self
+                    .bind_interface
+                    .as_deref()
+                    .filter(|name| !name.is_empty())
+                    .ok_or("No network interface selected for OpenList Core")?;
+                interface_bind_address(name)
+            }
         }
     }
+
+    /// Host the desktop uses to reach its own core, which differs from the bind
+    /// address when the core only listens on a single interface.
+    pub fn local_host(&self) -> String {
+        match self.bind_mode {
+            BindMode::Interface => self
+                .bind_address()
+                .map(|ip| match ip {
+                    IpAddr::V4(ip) => ip.to_string(),
+                    IpAddr::V6(ip) => format!("[{ip}]"),
+                })
+                .unwrap_or_else(|_| "localhost".to_string()),
+            _ => "localhost".to_string(),
+        }
+    }
+
+    pub fn is_exposed(&self) -> bool {
+        self.bind_mode != BindMode::Loopback
+    }
+
+    /// Mirrors a `scheme.address` edited outside the app back into the settings.
+    pub fn sync_bind_address(&mut self, address: &str) -> bool {
+        let ip = match address.trim() {
+            "" => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            "localhost" => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            other => match other.parse::<IpAddr>() {
+                Ok(ip) => ip,
+                Err(_) => return false,
+            },
+        };
+
+        if ip.is_unspecified() || ip.is_loopback() {
+            let mode = if ip.is_loopback() {
+                BindMode::Loopback
+            } else {
+                BindMode::All
+            };
+            let changed = self.bind_mode != mode;
+            self.bind_mode = mode;
+            return changed;
+        }
+
+        if self.bind_mode == BindMode::Interface && self.bind_address().is_ok_and(|cur| cur == ip) {
+            return false;
+        }
+        let Some(name) = interface_name_for_ip(ip) else {
+            return false;
+        };
+        self.bind_mode = BindMode::Interface;
+        self.bind_interface = Some(name);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConnectionMode, OpenListCoreConfig, RemoteInstanceConfig};
+
+    fn remote(url: &str) -> OpenListCoreConfig {
+        OpenListCoreConfig {
+            connection_mode: ConnectionMode::Remote,
+            remote: Some(RemoteInstanceConfig {
+                url: url.into(),
+                ..Default::default()
+            }),
+            ..OpenListCoreConfig::new()
+        }
+    }
+
+    #[test]
+    fn normalizes_remote_url() {
+        assert_eq!(
+            remote("https://openlist.example.com/")
+                .remote_url()
+                .unwrap(),
+            "https://openlist.example.com"
+        );
+        assert_eq!(
+            remote("http://10.0.0.5:5244/sub/").remote_url().unwrap(),
+            "http://10.0.0.5:5244/sub"
+        );
+    }
+
+    #[test]
+    fn rejects_missing_or_unsupported_remote_urls() {
+        assert!(remote("").validate().is_err());
+        assert!(remote("ftp://openlist.example.com").validate().is_err());
+        assert!(remote("not a url").validate().is_err());
+
+        let mut local = remote("");
+        local.connection_mode = ConnectionMode::Local;
+        assert!(local.validate().is_ok());
+    }
 }
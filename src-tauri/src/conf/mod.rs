@@ -3,3 +3,6 @@ pub mod config;
 pub mod core;
 pub mod rclone;
 pub mod rclone_config;
+pub mod rclone_crypt;
+pub mod rclone_ini;
+pub mod rclone_providers;
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
@@ -8,9 +9,13 @@ use crate::utils::args::remove_network_mode_flags_from_groups;
 pub struct RcloneMountConfig {
     pub name: String,
     pub r#type: String,
+    /// WebDAV connection details; other backends keep theirs in rclone.conf.
+    #[serde(default)]
     pub url: String,
     pub vendor: Option<String>,
+    #[serde(default)]
     pub user: String,
+    #[serde(default)]
     pub pass: String,
     #[serde(rename = "mountPoint")]
     pub mount_point: Option<String>,
@@ -22,9 +27,76 @@ pub struct RcloneMountConfig {
     pub auto_mount: Option<bool>,
     #[serde(default, rename = "networkMode")]
     pub network_mode: Option<bool>,
+    /// VFS cache location, rclone's default cache dir when unset.
+    #[serde(default, rename = "cacheDir")]
+    pub cache_dir: Option<String>,
+    #[serde(default, rename = "cacheMaxSize")]
+    pub cache_max_size: Option<String>,
+    #[serde(default, rename = "cacheMaxAge")]
+    pub cache_max_age: Option<String>,
+    /// Let the watchdog remount this auto-mount when it goes stale.
+    #[serde(default)]
+    pub watchdog: Option<bool>,
+    /// Checks in a row a mount must fail before it is remounted.
+    #[serde(default, rename = "watchdogFailedChecks")]
+    pub watchdog_failed_checks: Option<u32>,
+    #[serde(default, rename = "watchdogMaxRetries")]
+    pub watchdog_max_retries: Option<u32>,
+    /// Seconds before the first retry, doubled after each one.
+    #[serde(default, rename = "watchdogRetryDelay")]
+    pub watchdog_retry_delay: Option<u64>,
 }
 
+/// How the watchdog recovers one mount.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchdogPolicy {
+    pub failed_checks: u32,
+    pub max_retries: u32,
+    pub retry_delay: Duration,
+}
+
+const DEFAULT_WATCHDOG_FAILED_CHECKS: u32 = 2;
+const DEFAULT_WATCHDOG_MAX_RETRIES: u32 = 5;
+const DEFAULT_WATCHDOG_RETRY_DELAY: u64 = 10;
+
 impl RcloneMountConfig {
+    /// Mount flags for the configured VFS cache settings.
+    pub fn cache_flags(&self) -> Vec<String> {
+        [
+            ("--cache-dir", &self.cache_dir),
+            ("--vfs-cache-max-size", &self.cache_max_size),
+            ("--vfs-cache-max-age", &self.cache_max_age),
+        ]
+        .into_iter()
+        .filter_map(|(flag, value)| {
+            let value = value.as_deref()?.trim();
+            (!value.is_empty()).then(|| format!("{flag}={value}"))
+        })
+        .collect()
+    }
+
+    /// Watchdog thresholds, or `None` when the mount isn't watched. Only
+    /// auto-mounts are.
+    pub fn watchdog_policy(&self) -> Option<WatchdogPolicy> {
+        if !self.auto_mount.unwrap_or(false) || !self.watchdog.unwrap_or(true) {
+            return None;
+        }
+        Some(WatchdogPolicy {
+            failed_checks: self
+                .watchdog_failed_checks
+                .unwrap_or(DEFAULT_WATCHDOG_FAILED_CHECKS)
+                .max(1),
+            max_retries: self
+                .watchdog_max_retries
+                .unwrap_or(DEFAULT_WATCHDOG_MAX_RETRIES),
+            retry_delay: Duration::from_secs(
+                self.watchdog_retry_delay
+                    .unwrap_or(DEFAULT_WATCHDOG_RETRY_DELAY)
+                    .max(1),
+            ),
+        })
+    }
+
     pub fn normalize_network_mode(&mut self) -> bool {
         let Some(extra_flags) = self.extra_flags.take() else {
             return false;
@@ -45,6 +117,9 @@ pub struct RcloneConfig {
     pub mount_config: Option<HashMap<String, RcloneMountConfig>>,
     pub binary_path: Option<String>,
     pub rclone_conf_path: Option<String>,
+    /// Serve every mount from one `rclone rcd` process instead of one
+    /// `rclone mount` process each.
+    pub daemon_mode: Option<bool>,
 }
 
 impl Default for RcloneConfig {
@@ -59,9 +134,14 @@ impl RcloneConfig {
             mount_config: Some(HashMap::new()),
             binary_path: None,
             rclone_conf_path: None,
+            daemon_mode: Some(false),
         }
     }
 
+    pub fn use_daemon(&self) -> bool {
+        self.daemon_mode.unwrap_or(false)
+    }
+
     pub fn normalize_network_mode(&mut self) -> bool {
         let Some(configs) = self.mount_config.as_mut() else {
             return false;
@@ -72,11 +152,30 @@ impl RcloneConfig {
         }
         changed
     }
+
+    /// Drops passwords older versions copied into the app settings; rclone.conf
+    /// is the only place they're kept.
+    pub fn clear_stored_passwords(&mut self) -> bool {
+        let Some(configs) = self.mount_config.as_mut() else {
+            return false;
+        };
+        let mut changed = false;
+        for config in configs
+            .values_mut()
+            .filter(|config| !config.pass.is_empty())
+        {
+            config.pass.clear();
+            changed = true;
+        }
+        changed
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::RcloneMountConfig;
+    use std::time::Duration;
+
+    use super::{RcloneConfig, RcloneMountConfig};
 
     fn mount_config(extra_flags: Vec<&str>, network_mode: Option<bool>) -> RcloneMountConfig {
         RcloneMountConfig {
@@ -91,6 +190,13 @@ mod tests {
             extra_flags: Some(extra_flags.into_iter().map(String::from).collect()),
             auto_mount: Some(false),
             network_mode,
+            cache_dir: None,
+            cache_max_size: None,
+            cache_max_age: None,
+            watchdog: None,
+            watchdog_failed_checks: None,
+            watchdog_max_retries: None,
+            watchdog_retry_delay: None,
         }
     }
 
@@ -115,6 +221,57 @@ mod tests {
         assert_eq!(config.extra_flags, Some(vec!["--read-only".into()]));
     }
 
+    #[test]
+    fn builds_vfs_cache_flags() {
+        let mut config = mount_config(vec![], None);
+        assert!(config.cache_flags().is_empty());
+
+        config.cache_dir = Some("/var/cache/rclone".into());
+        config.cache_max_size = Some(" 20G ".into());
+        config.cache_max_age = Some(String::new());
+        assert_eq!(
+            config.cache_flags(),
+            vec!["--cache-dir=/var/cache/rclone", "--vfs-cache-max-size=20G"]
+        );
+    }
+
+    #[test]
+    fn watches_auto_mounts_only() {
+        let mut config = mount_config(vec![], None);
+        assert_eq!(config.watchdog_policy(), None);
+
+        config.auto_mount = Some(true);
+        let policy = config.watchdog_policy().unwrap();
+        assert_eq!(policy.failed_checks, 2);
+        assert_eq!(policy.max_retries, 5);
+        assert_eq!(policy.retry_delay, Duration::from_secs(10));
+
+        config.watchdog_failed_checks = Some(0);
+        config.watchdog_max_retries = Some(0);
+        config.watchdog_retry_delay = Some(60);
+        let policy = config.watchdog_policy().unwrap();
+        assert_eq!(policy.failed_checks, 1);
+        assert_eq!(policy.max_retries, 0);
+        assert_eq!(policy.retry_delay, Duration::from_secs(60));
+
+        config.watchdog = Some(false);
+        assert_eq!(config.watchdog_policy(), None);
+    }
+
+    #[test]
+    fn clears_passwords_copied_into_settings() {
+        let mut settings = RcloneConfig::new();
+        settings
+            .mount_config
+            .as_mut()
+            .unwrap()
+            .insert("remote".into(), mount_config(vec![], None));
+
+        assert!(settings.clear_stored_passwords());
+        assert_eq!(settings.mount_config.as_ref().unwrap()["remote"].pass, "");
+        assert!(!settings.clear_stored_passwords());
+    }
+
     #[test]
     fn missing_network_mode_field_defaults_without_deserialization_failure() {
         let config: RcloneMountConfig = serde_json::from_value(serde_json::json!({
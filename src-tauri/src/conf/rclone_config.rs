@@ -1,66 +1,66 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::{BufRead, BufReader, Write};
+use std::io::Write;
 use std::path::Path;
-use std::process::Command;
 
+use aes::cipher::{KeyIvInit, StreamCipher};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use parking_lot::Mutex;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
+use crate::conf::rclone_crypt;
+use crate::conf::rclone_ini::IniDocument;
+use crate::conf::rclone_providers::RcloneBackend;
 use crate::object::structs::AppState;
-use crate::utils::path::{get_rclone_binary_path_with_custom, get_rclone_config_path_with_custom};
+use crate::utils::path::get_rclone_config_path_with_custom;
+
+/// Fixed key rclone uses to obscure passwords in its config. Obscuring only
+/// keeps them from being read at a glance, it isn't encryption.
+const OBSCURE_KEY: [u8; 32] = [
+    0x9c, 0x93, 0x5b, 0x48, 0x73, 0x0a, 0x55, 0x4d, 0x6b, 0xfd, 0x7c, 0x63, 0xc8, 0x86, 0xa9, 0x2b,
+    0xd3, 0x90, 0x19, 0x8e, 0xb8, 0x12, 0x8a, 0xfb, 0xf4, 0xde, 0x16, 0x2b, 0x8b, 0x95, 0xf6, 0x38,
+];
+const OBSCURE_IV_LEN: usize = 16;
+
+type Aes256Ctr = ctr::Ctr128BE<aes::Aes256>;
+
+fn obscure_with_iv(password: &str, iv: [u8; OBSCURE_IV_LEN]) -> String {
+    let mut data = password.as_bytes().to_vec();
+    Aes256Ctr::new(&OBSCURE_KEY.into(), &iv.into()).apply_keystream(&mut data);
+    let mut payload = iv.to_vec();
+    payload.extend(data);
+    URL_SAFE_NO_PAD.encode(payload)
+}
 
-pub fn obscure_password(password: &str, state: State<'_, AppState>) -> Result<String, String> {
+/// Same as `rclone obscure`: AES-CTR with rclone's key and a random IV,
+/// base64url encoded.
+pub fn obscure_password(password: &str) -> Result<String, String> {
     if password.is_empty() {
         return Ok(String::new());
     }
-
-    let rclone_bin = get_rclone_binary_path_with_custom(state)?;
-
-    let mut cmd = Command::new(&rclone_bin);
-    cmd.args(["obscure", password]);
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-    }
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to run rclone obscure: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("rclone obscure failed: {stderr}"));
-    }
-
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    let mut iv = [0u8; OBSCURE_IV_LEN];
+    rand::rng().fill_bytes(&mut iv);
+    Ok(obscure_with_iv(password, iv))
 }
 
-pub fn reveal_password(obscured: &str, state: State<'_, AppState>) -> Result<String, String> {
+/// Same as `rclone reveal`.
+pub fn reveal_password(obscured: &str) -> Result<String, String> {
     if obscured.is_empty() {
         return Ok(String::new());
     }
-
-    let rclone_bin = get_rclone_binary_path_with_custom(state)?;
-
-    let mut cmd = Command::new(&rclone_bin);
-
-    cmd.args(["reveal", obscured]);
-    #[cfg(windows)]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-    }
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to run rclone reveal: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("rclone reveal failed: {stderr}"));
+    let payload = URL_SAFE_NO_PAD
+        .decode(obscured.trim_end_matches('='))
+        .map_err(|e| format!("Failed to reveal password, is it obscured? {e}"))?;
+    if payload.len() < OBSCURE_IV_LEN {
+        return Err("Failed to reveal password, input is too short to be obscured".into());
     }
-
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    let (iv, data) = payload.split_at(OBSCURE_IV_LEN);
+    let mut data = data.to_vec();
+    Aes256Ctr::new(&OBSCURE_KEY.into(), iv.into()).apply_keystream(&mut data);
+    String::from_utf8(data).map_err(|e| format!("Revealed password is not UTF-8: {e}"))
 }
 
 /// Represents a remote configuration entry in rclone.conf
@@ -69,14 +69,45 @@ pub struct RcloneRemoteConfig {
     pub name: String,
     pub remote_type: String,
     #[serde(flatten)]
-    pub options: HashMap<String, String>,
+    pub options: BTreeMap<String, String>,
 }
 
 /// Represents the full rclone configuration file
-#[derive(Debug, Clone, Default)]
+#[derive(Clone, Default)]
 pub struct RcloneConfigFile {
-    pub remotes: HashMap<String, RcloneRemoteConfig>,
+    document: IniDocument,
     custom_config_path: Option<String>,
+    /// Set when the file is encrypted, so it's written back encrypted.
+    password: Option<String>,
+}
+
+pub const CONFIG_LOCKED: &str = "The rclone config is encrypted, unlock it with its password first";
+
+/// Password of an encrypted rclone.conf, kept in memory for the session.
+static CONFIG_PASSWORD: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn config_password() -> Option<String> {
+    CONFIG_PASSWORD.lock().clone()
+}
+
+pub fn set_config_password(password: Option<String>) {
+    *CONFIG_PASSWORD.lock() = password;
+}
+
+/// Checks a remote name against the rules rclone applies to config
+/// sections.
+pub fn validate_remote_name(name: &str) -> Result<(), String> {
+    let allowed = |c: char| c.is_alphanumeric() || "_-.+@ ".contains(c);
+    if name.is_empty()
+        || !name.chars().all(allowed)
+        || name.starts_with(['-', ' '])
+        || name.ends_with(' ')
+    {
+        return Err(format!(
+            "Invalid remote name '{name}': use letters, numbers, _, -, ., +, @ and spaces, not starting with - or a space nor ending with a space"
+        ));
+    }
+    Ok(())
 }
 
 impl RcloneConfigFile {
@@ -99,60 +130,47 @@ impl RcloneConfigFile {
             return Ok(Self::default());
         }
 
-        let file =
-            fs::File::open(path).map_err(|e| format!("Failed to open rclone config: {e}"))?;
-        let reader = BufReader::new(file);
-
-        let mut config = Self::default();
-        let mut current_section: Option<String> = None;
-        let mut current_options: HashMap<String, String> = HashMap::new();
-        let mut current_type: Option<String> = None;
-
-        for line in reader.lines() {
-            let line = line.map_err(|e| format!("Failed to read line: {e}"))?;
-            let trimmed = line.trim();
-
-            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
-                continue;
-            }
-            if trimmed.starts_with('[') && trimmed.ends_with(']') {
-                if let Some(section_name) = current_section.take() {
-                    let remote = RcloneRemoteConfig {
-                        name: section_name.clone(),
-                        remote_type: current_type.take().unwrap_or_default(),
-                        options: std::mem::take(&mut current_options),
-                    };
-                    config.remotes.insert(section_name, remote);
-                }
-
-                current_section = Some(trimmed[1..trimmed.len() - 1].to_string());
-                current_options = HashMap::new();
-                current_type = None;
-                continue;
-            }
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read rclone config: {e}"))?;
+        Self::from_text(&text, config_password())
+    }
 
-            if let Some((key, value)) = trimmed.split_once('=') {
-                let key = key.trim().to_string();
-                let value = value.trim().to_string();
+    pub fn is_encrypted_at(path: &Path) -> Result<bool, String> {
+        if !path.exists() {
+            return Ok(false);
+        }
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read rclone config: {e}"))?;
+        Ok(rclone_crypt::is_encrypted(&text))
+    }
 
-                if key == "type" {
-                    current_type = Some(value);
-                } else {
-                    current_options.insert(key, value);
-                }
-            }
+    fn from_text(text: &str, password: Option<String>) -> Result<Self, String> {
+        if !rclone_crypt::is_encrypted(text) {
+            return Ok(Self {
+                document: IniDocument::parse(text),
+                ..Self::default()
+            });
         }
+        let password = password.ok_or(CONFIG_LOCKED)?;
+        let plain = rclone_crypt::decrypt(text, &password)?;
+        Ok(Self {
+            document: IniDocument::parse(&plain),
+            custom_config_path: None,
+            password: Some(password),
+        })
+    }
 
-        if let Some(section_name) = current_section.take() {
-            let remote = RcloneRemoteConfig {
-                name: section_name.clone(),
-                remote_type: current_type.take().unwrap_or_default(),
-                options: current_options,
-            };
-            config.remotes.insert(section_name, remote);
+    fn to_text(&self) -> Result<String, String> {
+        let text = self.document.render();
+        match &self.password {
+            Some(password) => rclone_crypt::encrypt(&text, password),
+            None => Ok(text),
         }
+    }
 
-        Ok(config)
+    /// Encrypts the file with `password` the next time it's saved.
+    pub fn set_password(&mut self, password: String) {
+        self.password = Some(password);
     }
 
     pub fn save(&self, state: State<'_, AppState>) -> Result<(), String> {
@@ -167,76 +185,346 @@ impl RcloneConfigFile {
                 .map_err(|e| format!("Failed to create config directory: {e}"))?;
         }
 
-        let mut content = String::new();
+        let text = self.to_text()?;
+        let mut file =
+            fs::File::create(path).map_err(|e| format!("Failed to create rclone config: {e}"))?;
+        file.write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to write rclone config: {e}"))?;
 
-        for (name, remote) in &self.remotes {
-            content.push_str(&format!("[{name}]\n"));
-            content.push_str(&format!("type = {}\n", remote.remote_type));
+        Ok(())
+    }
 
-            for (key, value) in &remote.options {
-                content.push_str(&format!("{key} = {value}\n"));
+    pub fn remote(&self, name: &str) -> Option<RcloneRemoteConfig> {
+        let mut remote_type = String::new();
+        let mut options = BTreeMap::new();
+        for (key, value) in self.document.entries(name)? {
+            if key == "type" {
+                remote_type = value;
+            } else {
+                options.insert(key, value);
             }
-
-            content.push('\n');
         }
+        Some(RcloneRemoteConfig {
+            name: name.to_string(),
+            remote_type,
+            options,
+        })
+    }
 
-        let mut file =
-            fs::File::create(path).map_err(|e| format!("Failed to create rclone config: {e}"))?;
-        file.write_all(content.as_bytes())
-            .map_err(|e| format!("Failed to write rclone config: {e}"))?;
+    pub fn option(&self, remote: &str, key: &str) -> Option<String> {
+        self.document.get(remote, key)
+    }
 
-        Ok(())
+    /// Remotes in the order they appear in the file.
+    pub fn remotes(&self) -> Vec<RcloneRemoteConfig> {
+        self.list_remotes()
+            .iter()
+            .filter_map(|name| self.remote(name))
+            .collect()
     }
 
+    /// Replaces a remote's options, keeping the lines of unchanged ones
+    /// exactly as they were.
     pub fn set_remote(&mut self, remote: RcloneRemoteConfig) {
-        self.remotes.insert(remote.name.clone(), remote);
+        let name = &remote.name;
+        self.document.set(name, "type", &remote.remote_type);
+        for (key, _) in self.document.entries(name).unwrap_or_default() {
+            if key != "type" && !remote.options.contains_key(&key) {
+                self.document.remove(name, &key);
+            }
+        }
+        for (key, value) in &remote.options {
+            self.document.set(name, key, value);
+        }
     }
 
     pub fn remove_remote(&mut self, name: &str) -> Option<RcloneRemoteConfig> {
-        self.remotes.remove(name)
+        let remote = self.remote(name)?;
+        self.document.remove_section(name);
+        Some(remote)
     }
 
     pub fn list_remotes(&self) -> Vec<String> {
-        self.remotes.keys().cloned().collect()
+        self.document.section_names()
     }
 
     pub fn has_remote(&self, name: &str) -> bool {
-        self.remotes.contains_key(name)
+        self.document.has_section(name)
     }
 }
 
-/// WebDAV specific configuration helper
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WebDavRemoteConfig {
-    pub name: String,
-    pub url: String,
-    pub vendor: Option<String>,
-    pub user: String,
-    pub pass: String,
+/// Placeholder the UI gets instead of a stored secret. Sending it back keeps
+/// the secret as it is.
+pub const SECRET_MASK: &str = "••••••••";
+
+/// Builds a remote of any backend from the options entered in the UI,
+/// obscuring every option the backend marks as a password. Options of
+/// `existing` the UI didn't send are kept, empty values clear an option so
+/// rclone falls back to its default, and masked secrets are kept as stored.
+pub fn build_remote_config(
+    name: &str,
+    backend: &RcloneBackend,
+    options: BTreeMap<String, String>,
+    existing: Option<&RcloneRemoteConfig>,
+) -> Result<RcloneRemoteConfig, String> {
+    // Options of another backend don't carry over when the type changes.
+    let existing = existing.filter(|remote| remote.remote_type == backend.name);
+    let mut stored = existing
+        .map(|remote| remote.options.clone())
+        .unwrap_or_default();
+    for (key, value) in options {
+        if key == "type" {
+            continue;
+        }
+        if value.is_empty() {
+            stored.remove(&key);
+            continue;
+        }
+        if backend.option(&key).is_none() {
+            return Err(format!(
+                "Unknown option '{key}' for rclone backend '{}'",
+                backend.name
+            ));
+        }
+        if value == SECRET_MASK {
+            continue;
+        }
+        let value = if backend.is_password(&key) {
+            obscure_password(&value)?
+        } else {
+            value
+        };
+        stored.insert(key, value);
+    }
+
+    if let Some(missing) = backend.options.iter().find(|option| {
+        option.required && option.default_str.is_empty() && !stored.contains_key(&option.name)
+    }) {
+        return Err(format!(
+            "Option '{}' is required for rclone backend '{}'",
+            missing.name, backend.name
+        ));
+    }
+
+    Ok(RcloneRemoteConfig {
+        name: name.to_string(),
+        remote_type: backend.name.clone(),
+        options: stored,
+    })
 }
 
-impl WebDavRemoteConfig {
-    pub fn to_rclone_config_with_obscured_pass(
-        &self,
-        state: State<'_, AppState>,
-    ) -> Result<RcloneRemoteConfig, String> {
-        let obscured_pass = obscure_password(&self.pass, state)?;
-
-        let mut options = HashMap::new();
-        options.insert("url".to_string(), self.url.clone());
-        options.insert("user".to_string(), self.user.clone());
-        options.insert("pass".to_string(), obscured_pass);
-
-        if let Some(vendor) = &self.vendor
-            && !vendor.is_empty()
-        {
-            options.insert("vendor".to_string(), vendor.clone());
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{
+        CONFIG_LOCKED, RcloneConfigFile, RcloneRemoteConfig, SECRET_MASK, build_remote_config,
+        obscure_with_iv, reveal_password, validate_remote_name,
+    };
+    use crate::conf::rclone_providers::RcloneBackend;
+
+    fn backend() -> RcloneBackend {
+        serde_json::from_value(serde_json::json!({
+            "Name": "sftp",
+            "Options": [
+                { "Name": "host", "Required": true },
+                { "Name": "port", "Required": true, "DefaultStr": "22" },
+                { "Name": "user" },
+                { "Name": "pass", "IsPassword": true },
+                { "Name": "key_file_pass", "IsPassword": true, "Advanced": true },
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn options(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn obscures_like_rclone() {
+        // Vectors from rclone's own obscure tests.
+        for (plain, iv, obscured) in [
+            ("", [b'a'; 16], "YWFhYWFhYWFhYWFhYWFhYQ"),
+            ("potato", [b'a'; 16], "YWFhYWFhYWFhYWFhYWFhYXMaGgIlEQ"),
+            ("potato", [b'b'; 16], "YmJiYmJiYmJiYmJiYmJiYp3gcEWbAw"),
+        ] {
+            assert_eq!(obscure_with_iv(plain, iv), obscured);
+            assert_eq!(reveal_password(obscured).unwrap(), plain);
         }
+        assert!(reveal_password("not obscured!").is_err());
+        assert!(reveal_password("c2hvcnQ").is_err());
+    }
 
-        Ok(RcloneRemoteConfig {
-            name: self.name.clone(),
-            remote_type: "webdav".to_string(),
-            options,
-        })
+    #[test]
+    fn obscures_every_password_option() {
+        let remote = build_remote_config(
+            "nas",
+            &backend(),
+            options(&[
+                ("host", "nas.lan"),
+                ("user", ""),
+                ("pass", "secret"),
+                ("key_file_pass", "phrase"),
+            ]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(remote.remote_type, "sftp");
+        assert_eq!(remote.options["host"], "nas.lan");
+        assert_ne!(remote.options["pass"], "secret");
+        assert_eq!(reveal_password(&remote.options["pass"]).unwrap(), "secret");
+        assert_eq!(
+            reveal_password(&remote.options["key_file_pass"]).unwrap(),
+            "phrase"
+        );
+        assert!(!remote.options.contains_key("user"));
+    }
+
+    #[test]
+    fn masked_secrets_keep_the_stored_value() {
+        let stored = build_remote_config(
+            "nas",
+            &backend(),
+            options(&[("host", "nas.lan"), ("pass", "secret")]),
+            None,
+        )
+        .unwrap();
+        let updated = build_remote_config(
+            "nas",
+            &backend(),
+            options(&[("host", "nas.home"), ("pass", SECRET_MASK)]),
+            Some(&stored),
+        )
+        .unwrap();
+        assert_eq!(updated.options["host"], "nas.home");
+        assert_eq!(updated.options["pass"], stored.options["pass"]);
+
+        let created = build_remote_config(
+            "box",
+            &backend(),
+            options(&[("host", "box.lan"), ("pass", SECRET_MASK)]),
+            None,
+        )
+        .unwrap();
+        assert!(!created.options.contains_key("pass"));
+    }
+
+    #[test]
+    fn updates_keep_options_that_were_not_sent() {
+        let mut stored = build_remote_config(
+            "nas",
+            &backend(),
+            options(&[("host", "nas.lan"), ("user", "me"), ("pass", "secret")]),
+            None,
+        )
+        .unwrap();
+        // Added by hand, unknown to the schema.
+        stored
+            .options
+            .insert("tuning".to_string(), "fast".to_string());
+
+        let updated = build_remote_config(
+            "nas",
+            &backend(),
+            options(&[("host", "nas.home"), ("user", "")]),
+            Some(&stored),
+        )
+        .unwrap();
+        assert_eq!(updated.options["host"], "nas.home");
+        assert_eq!(updated.options["pass"], stored.options["pass"]);
+        assert_eq!(updated.options["tuning"], "fast");
+        assert!(!updated.options.contains_key("user"));
+
+        let other = RcloneRemoteConfig {
+            remote_type: "webdav".into(),
+            ..stored
+        };
+        let retyped = build_remote_config(
+            "nas",
+            &backend(),
+            options(&[("host", "nas.lan")]),
+            Some(&other),
+        )
+        .unwrap();
+        assert_eq!(retyped.options.keys().collect::<Vec<_>>(), vec!["host"]);
+    }
+
+    #[test]
+    fn rejects_unknown_and_missing_options() {
+        assert!(build_remote_config("nas", &backend(), options(&[]), None).is_err());
+        assert!(
+            build_remote_config(
+                "nas",
+                &backend(),
+                options(&[("host", "nas.lan"), ("bucket", "x")]),
+                None,
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn updating_a_remote_leaves_the_rest_of_the_file_alone() {
+        let path = std::env::temp_dir().join(format!("rclone-{}.conf", std::process::id()));
+        let original =
+            "# hand edited\n[b]\ntype = local\n\n[a]\ntype = webdav\nurl = http://old\nuser = me\n";
+        std::fs::write(&path, original).unwrap();
+
+        let mut config = RcloneConfigFile::load_from_path(&path).unwrap();
+        assert_eq!(config.list_remotes(), vec!["b", "a"]);
+        config.set_remote(RcloneRemoteConfig {
+            name: "a".into(),
+            remote_type: "webdav".into(),
+            options: [("url".to_string(), "http://new".to_string())].into(),
+        });
+        config.save_to_path(&path).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            saved,
+            "# hand edited\n[b]\ntype = local\n\n[a]\ntype = webdav\nurl = http://new\n"
+        );
+    }
+
+    #[test]
+    fn validates_remote_names() {
+        for name in ["nas", "my remote", "s3.backup-1", "照片@home"] {
+            assert!(validate_remote_name(name).is_ok(), "{name}");
+        }
+        for name in ["", "-x", " x", "x ", "a:b", "a/b", "[x]"] {
+            assert!(validate_remote_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn keeps_encrypted_configs_encrypted() {
+        let encrypted = crate::conf::rclone_crypt::encrypt("[a]\ntype = local\n", "pw").unwrap();
+        assert_eq!(
+            RcloneConfigFile::from_text(&encrypted, None)
+                .err()
+                .as_deref(),
+            Some(CONFIG_LOCKED)
+        );
+        assert!(RcloneConfigFile::from_text(&encrypted, Some("bad".into())).is_err());
+
+        let mut config = RcloneConfigFile::from_text(&encrypted, Some("pw".into())).unwrap();
+        assert_eq!(config.list_remotes(), vec!["a"]);
+        config.set_remote(RcloneRemoteConfig {
+            name: "b".into(),
+            remote_type: "local".into(),
+            options: Default::default(),
+        });
+        let saved = config.to_text().unwrap();
+        assert!(crate::conf::rclone_crypt::is_encrypted(&saved));
+        let reloaded = RcloneConfigFile::from_text(&saved, Some("pw".into())).unwrap();
+        assert_eq!(reloaded.list_remotes(), vec!["a", "b"]);
+
+        let mut plain = RcloneConfigFile::from_text("[a]\ntype = local\n", None).unwrap();
+        plain.set_password("new".into());
+        assert!(crate::conf::rclone_crypt::decrypt(&plain.to_text().unwrap(), "new").is_ok());
     }
 }
@@ -0,0 +1,116 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use crypto_secretbox::aead::{Aead, KeyInit};
+use crypto_secretbox::{Nonce, XSalsa20Poly1305};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use unicode_normalization::UnicodeNormalization;
+
+/// First line after the comments of a config encrypted with
+/// `rclone config encryption set`.
+pub const ENCRYPTED_HEADER: &str = "RCLONE_ENCRYPT_V0:";
+const NONCE_LEN: usize = 24;
+
+/// Whether `text` is an encrypted rclone.conf rather than plain INI.
+pub fn is_encrypted(text: &str) -> bool {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| line == ENCRYPTED_HEADER)
+}
+
+/// Derives the secretbox key the way rclone does: SHA-256 over the NFKC
+/// normalized password wrapped in `[...][rclone-config]`.
+fn config_key(password: &str) -> Result<[u8; 32], String> {
+    if password.trim().is_empty() {
+        return Err("The rclone config password cannot be empty".into());
+    }
+    let password: String = password.nfkc().collect();
+    Ok(Sha256::digest(format!("[{password}][rclone-config]")).into())
+}
+
+pub fn decrypt(text: &str, password: &str) -> Result<String, String> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty() || line.starts_with('#'));
+    if lines.next() != Some(ENCRYPTED_HEADER) {
+        return Err("The rclone config is not encrypted".into());
+    }
+    let payload = STANDARD
+        .decode(lines.collect::<String>())
+        .map_err(|e| format!("Encrypted rclone config is malformed: {e}"))?;
+    if payload.len() < NONCE_LEN {
+        return Err("Encrypted rclone config is truncated".into());
+    }
+
+    let (nonce, sealed) = payload.split_at(NONCE_LEN);
+    let key = config_key(password)?;
+    let plain = XSalsa20Poly1305::new(&key.into())
+        .decrypt(Nonce::from_slice(nonce), sealed)
+        .map_err(|_| "Wrong rclone config password".to_string())?;
+    String::from_utf8(plain).map_err(|e| format!("Decrypted rclone config is not UTF-8: {e}"))
+}
+
+fn encrypt_with_nonce(
+    text: &str,
+    password: &str,
+    nonce: [u8; NONCE_LEN],
+) -> Result<String, String> {
+    let key = config_key(password)?;
+    let sealed = XSalsa20Poly1305::new(&key.into())
+        .encrypt(Nonce::from_slice(&nonce), text.as_bytes())
+        .map_err(|_| "Failed to encrypt rclone config".to_string())?;
+    let mut payload = nonce.to_vec();
+    payload.extend(sealed);
+    Ok(format!(
+        "# Encrypted rclone configuration File\n\n{ENCRYPTED_HEADER}\n{}",
+        STANDARD.encode(payload)
+    ))
+}
+
+/// Encrypts a plain rclone.conf into the format rclone writes.
+pub fn encrypt(text: &str, password: &str) -> Result<String, String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rng().fill_bytes(&mut nonce);
+    encrypt_with_nonce(text, password, nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, encrypt, encrypt_with_nonce, is_encrypted};
+
+    const PLAIN: &str = "[nas]\ntype = sftp\nhost = nas.lan\n";
+    // Sealed independently with NaCl secretbox, nonce 0..24, key from
+    // "pässwörd".
+    const SEALED: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYX284EG7eDi+x1iGVjdyafIgOemrluN2Bvn58Dy1NxtOH0W45Ax7WHylitv9BIs+BKHg==";
+
+    #[test]
+    fn matches_rclone_encrypted_format() {
+        let nonce: [u8; 24] = std::array::from_fn(|i| i as u8);
+        let encrypted = encrypt_with_nonce(PLAIN, "pässwörd", nonce).unwrap();
+        assert_eq!(
+            encrypted,
+            format!("# Encrypted rclone configuration File\n\nRCLONE_ENCRYPT_V0:\n{SEALED}")
+        );
+        assert!(is_encrypted(&encrypted));
+        assert!(!is_encrypted(PLAIN));
+
+        // rclone may wrap the payload over several lines.
+        let wrapped = format!(
+            "RCLONE_ENCRYPT_V0:\r\n{}\r\n{}\r\n",
+            &SEALED[..40],
+            &SEALED[40..]
+        );
+        assert_eq!(decrypt(&wrapped, "pässwörd").unwrap(), PLAIN);
+    }
+
+    #[test]
+    fn rejects_wrong_passwords() {
+        let encrypted = encrypt(PLAIN, "right").unwrap();
+        assert_eq!(decrypt(&encrypted, "right").unwrap(), PLAIN);
+        assert!(decrypt(&encrypted, "wrong").is_err());
+        assert!(decrypt(PLAIN, "right").is_err());
+        assert!(encrypt(PLAIN, "  ").is_err());
+    }
+}
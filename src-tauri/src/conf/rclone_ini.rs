@@ -0,0 +1,403 @@
+/// Quotes tried in order when a value starts with one, as rclone's config
+/// parser does. Only the triple quote and backtick may span several lines.
+const QUOTES: [&str; 4] = ["\"\"\"", "`", "\"", "'"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    /// Blank lines, comments and anything unparsable, kept verbatim.
+    Raw(String),
+    Entry(Entry),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    key: String,
+    value: String,
+    /// The lines the entry was read from, dropped once its value changes.
+    raw: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Section {
+    name: String,
+    header: String,
+    lines: Vec<Line>,
+}
+
+/// An rclone.conf kept line by line, so saving it only rewrites the entries
+/// that were changed and leaves order, comments and global settings alone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IniDocument {
+    /// Lines before the first section, such as global settings.
+    preamble: Vec<Line>,
+    sections: Vec<Section>,
+    crlf: bool,
+    missing_final_newline: bool,
+}
+
+fn section_header(trimmed: &str) -> Option<String> {
+    trimmed
+        .strip_prefix('[')?
+        .strip_suffix(']')
+        .map(|name| name.trim().to_string())
+}
+
+fn split_entry(trimmed: &str) -> Option<(String, &str)> {
+    let split = trimmed.find(['=', ':'])?;
+    let key = trimmed[..split].trim();
+    (!key.is_empty()).then(|| (key.to_string(), trimmed[split + 1..].trim()))
+}
+
+/// Reads the value starting at `rest`, consuming the continuation lines of
+/// multi-line quotes and trailing backslashes from `lines[*next..]`.
+fn read_value(rest: &str, lines: &[&str], next: &mut usize) -> String {
+    for quote in QUOTES {
+        if rest.len() < 2 || !rest.starts_with(quote) {
+            continue;
+        }
+        let body = &rest[quote.len()..];
+        if let Some(end) = body.rfind(quote) {
+            return body[..end].to_string();
+        }
+        if quote == "\"\"\"" || quote == "`" {
+            let mut value = body.to_string();
+            for (offset, line) in lines[*next..].iter().enumerate() {
+                value.push('\n');
+                if let Some(end) = line.rfind(quote) {
+                    value.push_str(&line[..end]);
+                    *next += offset + 1;
+                    return value;
+                }
+                value.push_str(line);
+            }
+        }
+        // rclone refuses unterminated quotes; keep the text rather than
+        // failing to read the whole file.
+        return rest.to_string();
+    }
+
+    let mut value = rest.to_string();
+    while value.ends_with('\\') && *next < lines.len() {
+        value.pop();
+        value.push_str(lines[*next].trim());
+        *next += 1;
+    }
+    value
+}
+
+/// Formats a value so rclone reads it back unchanged.
+pub fn quote_value(value: &str) -> String {
+    if value.contains('\n') || (value.contains('`') && value.contains('"')) {
+        format!("\"\"\"{value}\"\"\"")
+    } else if value.contains('"') {
+        format!("`{value}`")
+    } else if value.contains('`')
+        || value.trim() != value
+        || value.ends_with('\\')
+        || QUOTES.iter().any(|quote| value.starts_with(quote))
+    {
+        format!("\"{value}\"")
+    } else {
+        value.to_string()
+    }
+}
+
+fn render_line(line: &Line, lines: &mut Vec<String>) {
+    match line {
+        Line::Raw(raw) => lines.push(raw.clone()),
+        Line::Entry(Entry { raw: Some(raw), .. }) => lines.extend(raw.iter().cloned()),
+        Line::Entry(Entry { key, value, .. }) => lines.extend(
+            format!("{key} = {}", quote_value(value))
+                .split('\n')
+                .map(str::to_string),
+        ),
+    }
+}
+
+fn is_blank(line: &Line) -> bool {
+    matches!(line, Line::Raw(raw) if raw.trim().is_empty())
+}
+
+impl IniDocument {
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut document = Self {
+            crlf: text.contains("\r\n"),
+            missing_final_newline: !text.is_empty() && !text.ends_with('\n'),
+            ..Self::default()
+        };
+
+        let mut next = 0;
+        while next < lines.len() {
+            let line = lines[next];
+            let trimmed = line.trim();
+            let start = next;
+            next += 1;
+
+            if let Some(name) = section_header(trimmed) {
+                document.sections.push(Section {
+                    name,
+                    header: line.to_string(),
+                    lines: Vec::new(),
+                });
+                continue;
+            }
+            let parsed = match split_entry(trimmed) {
+                Some((key, rest)) if !trimmed.starts_with(['#', ';']) => {
+                    let value = read_value(rest, &lines, &mut next);
+                    Line::Entry(Entry {
+                        key,
+                        value,
+                        raw: Some(lines[start..next].iter().map(|l| l.to_string()).collect()),
+                    })
+                }
+                _ => Line::Raw(line.to_string()),
+            };
+            match document.sections.last_mut() {
+                Some(section) => section.lines.push(parsed),
+                None => document.preamble.push(parsed),
+            }
+        }
+        document
+    }
+
+    pub fn render(&self) -> String {
+        let mut lines = Vec::new();
+        self.preamble
+            .iter()
+            .for_each(|line| render_line(line, &mut lines));
+        for section in &self.sections {
+            lines.push(section.header.clone());
+            section
+                .lines
+                .iter()
+                .for_each(|line| render_line(line, &mut lines));
+        }
+
+        let newline = if self.crlf { "\r\n" } else { "\n" };
+        let mut text = lines.join(newline);
+        if !text.is_empty() && !self.missing_final_newline {
+            text.push_str(newline);
+        }
+        text
+    }
+
+    fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    fn section_mut(&mut self, name: &str) -> Option<&mut Section> {
+        self.sections
+            .iter_mut()
+            .find(|section| section.name == name)
+    }
+
+    pub fn section_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for section in &self.sections {
+            if !names.contains(&section.name) {
+                names.push(section.name.clone());
+            }
+        }
+        names
+    }
+
+    pub fn has_section(&self, name: &str) -> bool {
+        self.section(name).is_some()
+    }
+
+    /// Key-value pairs of a section in file order.
+    pub fn entries(&self, name: &str) -> Option<Vec<(String, String)>> {
+        let section = self.section(name)?;
+        Some(
+            section
+                .lines
+                .iter()
+                .filter_map(|line| match line {
+                    Line::Entry(entry) => Some((entry.key.clone(), entry.value.clone())),
+                    Line::Raw(_) => None,
+                })
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, name: &str, key: &str) -> Option<String> {
+        self.entries(name)?
+            .into_iter()
+            .find(|(entry, _)| entry == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Appends an empty section, separated from the previous content by a
+    /// blank line.
+    pub fn add_section(&mut self, name: &str) {
+        let has_sections = !self.sections.is_empty();
+        let previous = match self.sections.last_mut() {
+            Some(section) => &mut section.lines,
+            None => &mut self.preamble,
+        };
+        if previous.last().is_some_and(|line| !is_blank(line))
+            || (previous.is_empty() && has_sections)
+        {
+            previous.push(Line::Raw(String::new()));
+        }
+        self.sections.push(Section {
+            name: name.to_string(),
+            header: format!("[{name}]"),
+            lines: Vec::new(),
+        });
+    }
+
+    /// Sets `key` in place, or after the section's last entry when it's new,
+    /// creating the section if needed.
+    pub fn set(&mut self, name: &str, key: &str, value: &str) {
+        if !self.has_section(name) {
+            self.add_section(name);
+        }
+        let Some(section) = self.section_mut(name) else {
+            return;
+        };
+        let existing = section.lines.iter_mut().find_map(|line| match line {
+            Line::Entry(entry) if entry.key == key => Some(entry),
+            _ => None,
+        });
+        if let Some(entry) = existing {
+            if entry.value != value {
+                entry.value = value.to_string();
+                entry.raw = None;
+            }
+            return;
+        }
+
+        let position = section
+            .lines
+            .iter()
+            .rposition(|line| matches!(line, Line::Entry(_)))
+            .map_or(0, |last| last + 1);
+        section.lines.insert(
+            position,
+            Line::Entry(Entry {
+                key: key.to_string(),
+                value: value.to_string(),
+                raw: None,
+            }),
+        );
+    }
+
+    pub fn remove(&mut self, name: &str, key: &str) -> bool {
+        let Some(section) = self.section_mut(name) else {
+            return false;
+        };
+        let before = section.lines.len();
+        section
+            .lines
+            .retain(|line| !matches!(line, Line::Entry(entry) if entry.key == key));
+        section.lines.len() != before
+    }
+
+    pub fn remove_section(&mut self, name: &str) -> bool {
+        let before = self.sections.len();
+        self.sections.retain(|section| section.name != name);
+        self.sections.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IniDocument, quote_value};
+
+    const CONFIG: &str = "\
+# managed by hand
+[global]
+log_level = INFO
+
+[nas]
+type = sftp
+host = nas.lan
+; comment inside
+user = me
+
+[s3]
+type = s3
+provider = AWS
+";
+
+    #[test]
+    fn round_trips_untouched_files_exactly() {
+        for text in [
+            CONFIG,
+            "[a]\r\ntype = local\r\n",
+            "[a]\ntype = local",
+            "",
+            "stray line\n[x]\nkey : value\n",
+        ] {
+            assert_eq!(IniDocument::parse(text).render(), text);
+        }
+    }
+
+    #[test]
+    fn edits_only_the_changed_entries() {
+        let mut document = IniDocument::parse(CONFIG);
+        document.set("nas", "host", "nas.home");
+        document.set("nas", "port", "2222");
+        document.remove("s3", "provider");
+        let rendered = document.render();
+        assert!(rendered.starts_with("# managed by hand\n[global]\nlog_level = INFO\n\n[nas]\n"));
+        assert!(
+            rendered.contains("host = nas.home\n; comment inside\nuser = me\nport = 2222\n\n[s3]")
+        );
+        assert!(rendered.ends_with("[s3]\ntype = s3\n"));
+
+        document.remove_section("nas");
+        document.set("box", "type", "local");
+        assert_eq!(document.section_names(), vec!["global", "s3", "box"]);
+        assert!(
+            document
+                .render()
+                .ends_with("type = s3\n\n[box]\ntype = local\n")
+        );
+    }
+
+    #[test]
+    fn reads_quotes_and_continuations() {
+        let document = IniDocument::parse(
+            "[r]\n\
+             a = \"  padded  \"\n\
+             b = `say \"hi\"`\n\
+             c = \"\"\"first\n\
+             second\"\"\"\n\
+             d = one\\\n\
+             \x20  two\n\
+             e = http://host:80/dav\n",
+        );
+        assert_eq!(document.get("r", "a").as_deref(), Some("  padded  "));
+        assert_eq!(document.get("r", "b").as_deref(), Some("say \"hi\""));
+        assert_eq!(document.get("r", "c").as_deref(), Some("first\nsecond"));
+        assert_eq!(document.get("r", "d").as_deref(), Some("onetwo"));
+        assert_eq!(
+            document.get("r", "e").as_deref(),
+            Some("http://host:80/dav")
+        );
+    }
+
+    #[test]
+    fn quoted_values_read_back_unchanged() {
+        for value in [
+            "plain",
+            " padded ",
+            "say \"hi\"",
+            "tick`and\"quote",
+            "back`tick",
+            "'single'",
+            "trailing\\",
+            "multi\nline",
+            "",
+        ] {
+            let mut document = IniDocument::default();
+            document.set("r", "k", value);
+            let parsed = IniDocument::parse(&document.render());
+            assert_eq!(parsed.get("r", "k").as_deref(), Some(value), "{value:?}");
+        }
+        assert_eq!(quote_value("plain"), "plain");
+    }
+}
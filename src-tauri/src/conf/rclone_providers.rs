@@ -0,0 +1,240 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::object::structs::AppState;
+use crate::utils::path::get_rclone_binary_path_with_custom;
+
+/// One backend as described by `rclone config providers --json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct RcloneBackend {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub options: Vec<RcloneBackendOption>,
+    #[serde(default)]
+    pub hide: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct RcloneBackendOption {
+    pub name: String,
+    #[serde(default)]
+    pub help: String,
+    /// Comma separated providers this option applies to, `!` negates.
+    #[serde(default)]
+    pub provider: String,
+    #[serde(default)]
+    pub default_str: String,
+    #[serde(default, rename(deserialize = "Type"))]
+    pub option_type: String,
+    #[serde(default)]
+    pub examples: Option<Vec<RcloneOptionExample>>,
+    #[serde(default)]
+    pub required: bool,
+    /// Stored obscured in rclone.conf.
+    #[serde(default)]
+    pub is_password: bool,
+    /// Stored as is but shouldn't be shown or logged.
+    #[serde(default)]
+    pub sensitive: bool,
+    #[serde(default)]
+    pub advanced: bool,
+    /// Only one of `examples` is allowed.
+    #[serde(default)]
+    pub exclusive: bool,
+    #[serde(default)]
+    pub hide: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct RcloneOptionExample {
+    pub value: String,
+    #[serde(default)]
+    pub help: String,
+    #[serde(default)]
+    pub provider: String,
+}
+
+impl RcloneBackend {
+    pub fn option(&self, name: &str) -> Option<&RcloneBackendOption> {
+        self.options.iter().find(|option| option.name == name)
+    }
+
+    pub fn is_password(&self, name: &str) -> bool {
+        self.option(name).is_some_and(|option| option.is_password)
+    }
+
+    /// Whether the option holds a secret, obscured or not.
+    pub fn is_secret(&self, name: &str) -> bool {
+        self.option(name)
+            .is_some_and(|option| option.is_password || option.sensitive)
+    }
+}
+
+/// Options rclone backends store obscured, for when rclone can't be asked
+/// for its schema.
+const PASSWORD_OPTIONS: [&str; 4] = ["pass", "password", "password2", "key_file_pass"];
+
+/// Options rclone backends store as is but treat as secrets.
+const SENSITIVE_OPTIONS: [&str; 6] = [
+    "token",
+    "bearer_token",
+    "client_secret",
+    "secret_access_key",
+    "session_token",
+    "key_pem",
+];
+
+fn is_common_password_option(name: &str) -> bool {
+    PASSWORD_OPTIONS.contains(&name)
+}
+
+/// Whether `name` is obscured in rclone.conf, using the usual password
+/// options when the backend is unknown.
+pub fn is_password_option(backend: Option<&RcloneBackend>, name: &str) -> bool {
+    backend.map_or_else(
+        || is_common_password_option(name),
+        |backend| backend.is_password(name),
+    )
+}
+
+/// Whether `name` holds a secret that shouldn't reach the UI unasked.
+pub fn is_secret_option(backend: Option<&RcloneBackend>, name: &str) -> bool {
+    backend.map_or_else(
+        || is_common_password_option(name) || SENSITIVE_OPTIONS.contains(&name),
+        |backend| backend.is_secret(name),
+    )
+}
+
+/// Stand-in for a backend when rclone isn't available: accepts the given
+/// options as they are and obscures the usual password options.
+pub fn fallback_backend(name: &str, options: impl IntoIterator<Item = String>) -> RcloneBackend {
+    RcloneBackend {
+        name: name.to_string(),
+        description: String::new(),
+        prefix: name.to_string(),
+        options: options
+            .into_iter()
+            .map(|option| RcloneBackendOption {
+                is_password: is_common_password_option(&option),
+                name: option,
+                ..Default::default()
+            })
+            .collect(),
+        hide: false,
+    }
+}
+
+pub fn find_backend<'a>(backends: &'a [RcloneBackend], name: &str) -> Option<&'a RcloneBackend> {
+    backends.iter().find(|backend| backend.name == name)
+}
+
+type ProviderCache = Option<(PathBuf, Arc<Vec<RcloneBackend>>)>;
+
+static PROVIDERS: Mutex<ProviderCache> = Mutex::new(None);
+
+fn query_providers(rclone_bin: &Path) -> Result<Vec<RcloneBackend>, String> {
+    let mut cmd = Command::new(rclone_bin);
+    cmd.args(["config", "providers", "--json"]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run rclone config providers: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("rclone config providers failed: {stderr}"));
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse rclone providers: {e}"))
+}
+
+/// Backend schema of the configured rclone binary, cached until the binary
+/// changes.
+pub fn rclone_backends(state: State<'_, AppState>) -> Result<Arc<Vec<RcloneBackend>>, String> {
+    let rclone_bin = get_rclone_binary_path_with_custom(state)?;
+    let mut cache = PROVIDERS.lock();
+    if let Some((path, backends)) = cache.as_ref()
+        && *path == rclone_bin
+    {
+        return Ok(backends.clone());
+    }
+    let backends = Arc::new(query_providers(&rclone_bin)?);
+    log::info!(
+        "Loaded {} rclone backends from {rclone_bin:?}",
+        backends.len()
+    );
+    *cache = Some((rclone_bin, backends.clone()));
+    Ok(backends)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        RcloneBackend, fallback_backend, find_backend, is_password_option, is_secret_option,
+    };
+
+    #[test]
+    fn parses_rclone_provider_schema() {
+        let json = r#"[{
+            "Name": "sftp",
+            "Description": "SSH/SFTP",
+            "Prefix": "sftp",
+            "Options": [
+                {"Name": "host", "Help": "SSH host to connect to.", "Provider": "", "Default": "",
+                 "Value": null, "ShortOpt": "", "Hide": 0, "Required": true, "IsPassword": false,
+                 "NoPrefix": false, "Advanced": false, "Exclusive": false, "Sensitive": false,
+                 "DefaultStr": "", "ValueStr": "", "Type": "string"},
+                {"Name": "pass", "Help": "SSH password.", "Provider": "", "Default": "",
+                 "Required": false, "IsPassword": true, "Sensitive": false, "Advanced": false,
+                 "DefaultStr": "", "Type": "string"},
+                {"Name": "key_file_pass", "IsPassword": true, "Advanced": true, "Type": "string"},
+                {"Name": "key_pem", "Sensitive": true, "Advanced": true, "Type": "string"}
+            ],
+            "CommandHelp": null,
+            "Aliases": null,
+            "Hide": false,
+            "MetadataInfo": null
+        }]"#;
+        let backends: Vec<RcloneBackend> = serde_json::from_str(json).unwrap();
+        let sftp = find_backend(&backends, "sftp").unwrap();
+        assert!(sftp.option("host").unwrap().required);
+        assert_eq!(sftp.option("host").unwrap().option_type, "string");
+        assert!(sftp.is_password("pass"));
+        assert!(sftp.is_password("key_file_pass"));
+        assert!(!sftp.is_password("host"));
+        assert!(sftp.is_secret("pass"));
+        assert!(sftp.is_secret("key_pem"));
+        assert!(!sftp.is_password("key_pem"));
+        assert!(!sftp.is_secret("host"));
+        assert!(find_backend(&backends, "s3").is_none());
+    }
+
+    #[test]
+    fn fallback_obscures_common_password_options() {
+        let backend = fallback_backend("ftp", ["host".to_string(), "pass".to_string()]);
+        assert!(backend.option("host").is_some());
+        assert!(backend.is_password("pass"));
+        assert!(!backend.is_password("host"));
+
+        assert!(is_password_option(None, "pass"));
+        assert!(!is_password_option(None, "token"));
+        assert!(is_secret_option(None, "token"));
+        assert!(!is_secret_option(None, "url"));
+        assert!(!is_secret_option(Some(&backend), "token"));
+    }
+}
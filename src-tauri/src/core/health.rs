@@ -0,0 +1,87 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+const HEALTH_HISTORY_LEN: usize = 120;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CoreOwnership {
+    /// Spawned by this session of the desktop app.
+    Managed,
+    /// Recovered from the persisted process state of a previous session.
+    Adopted,
+    /// Answering health checks but not tracked by the process manager.
+    External,
+    /// A remote instance configured in place of a local core.
+    Remote,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HealthSample {
+    pub healthy: bool,
+    pub latency_ms: Option<u64>,
+    pub http_status: Option<u16>,
+}
+
+#[derive(Debug, Default)]
+pub struct HealthHistory {
+    samples: VecDeque<HealthSample>,
+    version: Option<String>,
+}
+
+impl HealthHistory {
+    pub fn record(&mut self, sample: HealthSample) {
+        if self.samples.len() == HEALTH_HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Share of recent health checks that succeeded, in percent.
+    pub fn availability(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let healthy = self.samples.iter().filter(|s| s.healthy).count();
+        Some(healthy as f64 * 100.0 / self.samples.len() as f64)
+    }
+
+    pub fn version(&self) -> Option<String> {
+        self.version.clone()
+    }
+
+    pub fn set_version(&mut self, version: String) {
+        self.version = Some(version);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HEALTH_HISTORY_LEN, HealthHistory, HealthSample};
+
+    fn sample(healthy: bool) -> HealthSample {
+        HealthSample {
+            healthy,
+            latency_ms: healthy.then_some(3),
+            http_status: healthy.then_some(200),
+        }
+    }
+
+    #[test]
+    fn availability_is_none_without_samples() {
+        assert_eq!(HealthHistory::default().availability(), None);
+    }
+
+    #[test]
+    fn availability_covers_only_the_rolling_window() {
+        let mut history = HealthHistory::default();
+        for _ in 0..HEALTH_HISTORY_LEN {
+            history.record(sample(false));
+        }
+        for _ in 0..HEALTH_HISTORY_LEN / 2 {
+            history.record(sample(true));
+        }
+        assert_eq!(history.availability(), Some(50.0));
+    }
+}
@@ -1 +1,8 @@
+pub mod health;
+pub mod mount_monitor;
+pub mod mount_watchdog;
 pub mod process_manager;
+pub mod rclone_daemon;
+pub mod rclone_rc;
+pub mod task_monitor;
+pub mod transfers;
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter};
+
+use crate::cmd::rclone_mount::collect_mount_infos;
+use crate::object::structs::RcloneMountInfo;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+pub const MOUNT_STATS_EVENT: &str = "rclone-mount-stats";
+
+/// Polls the status and transfer stats of every mount for the lifetime of the
+/// app, emitting the list whenever it changes.
+pub fn start_mount_monitor(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last: Vec<RcloneMountInfo> = Vec::new();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let mounts = collect_mount_infos().await;
+            if mounts == last {
+                continue;
+            }
+            if let Err(e) = app.emit(MOUNT_STATS_EVENT, &mounts) {
+                log::error!("Failed to emit {MOUNT_STATS_EVENT} event: {e}");
+            }
+            last = mounts;
+        }
+    });
+}
@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::cmd::rclone_mount::{collect_mount_infos, mount_input, mount_remote, stop_mount};
+use crate::conf::rclone::{RcloneMountConfig, WatchdogPolicy};
+use crate::object::structs::AppState;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// Longest wait between two remount attempts.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(600);
+
+pub const MOUNT_WATCHDOG_EVENT: &str = "rclone-mount-watchdog";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchdogEventKind {
+    Remounting,
+    RemountFailed,
+    Recovered,
+    GaveUp,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchdogEvent {
+    pub name: String,
+    pub kind: WatchdogEventKind,
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchAction {
+    None,
+    /// Remount, this being the given attempt.
+    Remount(u32),
+    Recovered,
+    GiveUp,
+}
+
+/// Health of one mount between checks.
+#[derive(Debug, Default)]
+pub struct MountWatch {
+    failed_checks: u32,
+    attempts: u32,
+    next_attempt: Option<Instant>,
+    gave_up: bool,
+}
+
+impl MountWatch {
+    /// Records one check and decides what to do about the mount.
+    pub fn observe(&mut self, healthy: bool, policy: &WatchdogPolicy, now: Instant) -> WatchAction {
+        if healthy {
+            let recovered = self.attempts > 0 || self.gave_up;
+            *self = Self::default();
+            return if recovered {
+                WatchAction::Recovered
+            } else {
+                WatchAction::None
+            };
+        }
+
+        self.failed_checks += 1;
+        if self.gave_up
+            || self.failed_checks < policy.failed_checks
+            || self.next_attempt.is_some_and(|at| now < at)
+        {
+            return WatchAction::None;
+        }
+        if self.attempts >= policy.max_retries {
+            self.gave_up = true;
+            return WatchAction::GiveUp;
+        }
+        self.attempts += 1;
+        self.next_attempt = Some(now + retry_delay(policy.retry_delay, self.attempts));
+        WatchAction::Remount(self.attempts)
+    }
+}
+
+fn retry_delay(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Mounts seen healthy this session, by remote name. Only these are
+/// recovered, so the watchdog never mounts what the user unmounted or what
+/// failed to mount in the first place.
+static WATCHES: Mutex<Option<HashMap<String, MountWatch>>> = Mutex::new(None);
+
+pub fn unwatch(name: &str) {
+    if let Some(watches) = WATCHES.lock().as_mut() {
+        watches.remove(name);
+    }
+}
+
+pub fn unwatch_all() {
+    *WATCHES.lock() = None;
+}
+
+fn is_watched(name: &str) -> bool {
+    WATCHES
+        .lock()
+        .as_ref()
+        .is_some_and(|watches| watches.contains_key(name))
+}
+
+/// Mounts with a remount running. A daemon mount can take up to the rc
+/// timeout, so remounts run on their own and their mounts aren't checked
+/// meanwhile.
+static REMOUNTING: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Marks a mount as being remounted until dropped.
+struct Remounting(String);
+
+impl Remounting {
+    fn start(name: &str) -> Option<Self> {
+        REMOUNTING
+            .lock()
+            .get_or_insert_with(HashSet::new)
+            .insert(name.to_string())
+            .then(|| Self(name.to_string()))
+    }
+}
+
+impl Drop for Remounting {
+    fn drop(&mut self) {
+        if let Some(remounting) = REMOUNTING.lock().as_mut() {
+            remounting.remove(&self.0);
+        }
+    }
+}
+
+fn is_remounting(name: &str) -> bool {
+    REMOUNTING
+        .lock()
+        .as_ref()
+        .is_some_and(|remounting| remounting.contains(name))
+}
+
+fn emit(app: &AppHandle, event: WatchdogEvent) {
+    if let Err(e) = app.emit(MOUNT_WATCHDOG_EVENT, event) {
+        log::error!("Failed to emit {MOUNT_WATCHDOG_EVENT} event: {e}");
+    }
+}
+
+async fn remount(app: &AppHandle, config: &RcloneMountConfig) -> Result<(), String> {
+    stop_mount(&config.name).await?;
+    // The user may have unmounted it in the meantime.
+    if !is_watched(&config.name) {
+        return Ok(());
+    }
+    mount_remote(mount_input(config), app.state::<AppState>())
+        .await
+        .map(|_| ())
+}
+
+async fn check(app: &AppHandle) {
+    let Some(settings) = app.state::<AppState>().get_settings() else {
+        return;
+    };
+    let configs: HashMap<String, (RcloneMountConfig, WatchdogPolicy)> = settings
+        .rclone
+        .mount_config
+        .unwrap_or_default()
+        .into_values()
+        .filter_map(|config| {
+            let policy = config.watchdog_policy()?;
+            Some((config.name.clone(), (config, policy)))
+        })
+        .collect();
+
+    let mounts = if configs.is_empty() {
+        Vec::new()
+    } else {
+        collect_mount_infos().await
+    };
+    let now = Instant::now();
+    let mut actions = Vec::new();
+    {
+        let mut watches = WATCHES.lock();
+        let watches = watches.get_or_insert_with(HashMap::new);
+        watches.retain(|name, _| configs.contains_key(name));
+        for (name, (_, policy)) in &configs {
+            if is_remounting(name) {
+                continue;
+            }
+            let mount = mounts.iter().find(|mount| &mount.name == name);
+            let healthy = mount.is_some_and(|mount| mount.status == "mounted");
+            if !healthy && !watches.contains_key(name) {
+                continue;
+            }
+            let action = watches
+                .entry(name.clone())
+                .or_default()
+                .observe(healthy, policy, now);
+            if action != WatchAction::None {
+                let error = mount.and_then(|mount| mount.error_msg.clone());
+                actions.push((name.clone(), action, error));
+            }
+        }
+    }
+
+    for (name, action, error) in actions {
+        let (config, policy) = &configs[&name];
+        let event = |kind, attempt, error| WatchdogEvent {
+            name: name.clone(),
+            kind,
+            attempt,
+            max_retries: policy.max_retries,
+            error,
+        };
+        match action {
+            WatchAction::None => {}
+            WatchAction::Remount(attempt) => {
+                let Some(remounting) = Remounting::start(&name) else {
+                    continue;
+                };
+                log::warn!(
+                    "Mount '{name}' is stale ({}), remounting (attempt {attempt}/{})",
+                    error.as_deref().unwrap_or("not mounted"),
+                    policy.max_retries
+                );
+                emit(app, event(WatchdogEventKind::Remounting, attempt, error));
+                let failed = event(WatchdogEventKind::RemountFailed, attempt, None);
+                let (app, config) = (app.clone(), config.clone());
+                tauri::async_runtime::spawn(async move {
+                    let _remounting = remounting;
+                    if let Err(e) = remount(&app, &config).await {
+                        log::error!("Failed to remount '{}': {e}", config.name);
+                        emit(
+                            &app,
+                            WatchdogEvent {
+                                error: Some(e),
+                                ..failed
+                            },
+                        );
+                    }
+                });
+            }
+            WatchAction::Recovered => {
+                log::info!("Mount '{name}' recovered");
+                emit(app, event(WatchdogEventKind::Recovered, 0, None));
+            }
+            WatchAction::GiveUp => {
+                log::error!(
+                    "Giving up on mount '{name}' after {} remount attempts",
+                    policy.max_retries
+                );
+                emit(
+                    app,
+                    event(WatchdogEventKind::GaveUp, policy.max_retries, error),
+                );
+            }
+        }
+    }
+}
+
+/// Checks every auto-mount for the lifetime of the app and remounts the ones
+/// that went stale, backing off between attempts.
+pub fn start_mount_watchdog(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            check(&app).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{MountWatch, Remounting, WatchAction, is_remounting, retry_delay};
+    use crate::conf::rclone::WatchdogPolicy;
+
+    fn policy() -> WatchdogPolicy {
+        WatchdogPolicy {
+            failed_checks: 2,
+            max_retries: 2,
+            retry_delay: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn remounts_with_backoff_then_gives_up() {
+        let policy = policy();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut watch = MountWatch::default();
+
+        assert_eq!(watch.observe(true, &policy, at(0)), WatchAction::None);
+        // One failed check may be a hiccup.
+        assert_eq!(watch.observe(false, &policy, at(10)), WatchAction::None);
+        assert_eq!(
+            watch.observe(false, &policy, at(20)),
+            WatchAction::Remount(1)
+        );
+        assert_eq!(watch.observe(false, &policy, at(25)), WatchAction::None);
+        assert_eq!(
+            watch.observe(false, &policy, at(30)),
+            WatchAction::Remount(2)
+        );
+        // The second retry waits twice as long.
+        assert_eq!(watch.observe(false, &policy, at(45)), WatchAction::None);
+        assert_eq!(watch.observe(false, &policy, at(50)), WatchAction::GiveUp);
+        assert_eq!(watch.observe(false, &policy, at(500)), WatchAction::None);
+
+        // Mounted again by hand.
+        assert_eq!(
+            watch.observe(true, &policy, at(510)),
+            WatchAction::Recovered
+        );
+        assert_eq!(watch.observe(true, &policy, at(520)), WatchAction::None);
+        assert_eq!(watch.observe(false, &policy, at(530)), WatchAction::None);
+        assert_eq!(
+            watch.observe(false, &policy, at(540)),
+            WatchAction::Remount(1)
+        );
+    }
+
+    #[test]
+    fn caps_retry_delay() {
+        let base = Duration::from_secs(10);
+        assert_eq!(retry_delay(base, 1), base);
+        assert_eq!(retry_delay(base, 3), Duration::from_secs(40));
+        assert_eq!(retry_delay(base, 40), Duration::from_secs(600));
+    }
+
+    #[test]
+    fn remounts_a_mount_once_at_a_time() {
+        let remounting = Remounting::start("nas").unwrap();
+        assert!(is_remounting("nas"));
+        assert!(Remounting::start("nas").is_none());
+        drop(remounting);
+        assert!(!is_remounting("nas"));
+    }
+}
@@ -20,6 +20,9 @@ pub struct ProcessConfig {
     pub log_file: String,
     pub working_dir: Option<String>,
     pub env_vars: Option<HashMap<String, String>>,
+    /// Like `env_vars`, but never persisted or sent to the UI.
+    #[serde(skip)]
+    pub secret_env_vars: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -533,6 +536,7 @@ impl ProcessManager {
                 cmd.env(key, value);
             }
         }
+        cmd.envs(&config.secret_env_vars);
 
         #[cfg(target_os = "windows")]
         {
@@ -738,6 +742,15 @@ impl ProcessManager {
         self.processes.read().contains_key(id)
     }
 
+    /// Whether the process was recovered from a previous session rather than
+    /// spawned by this one.
+    pub fn is_adopted(&self, id: &str) -> bool {
+        self.processes
+            .read()
+            .get(id)
+            .is_some_and(|managed| managed.child.is_none() && managed.external_pid.is_some())
+    }
+
     pub fn is_running(&self, id: &str) -> bool {
         self.get_status(id).is_ok_and(|info| info.is_running)
     }
@@ -0,0 +1,390 @@
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde_json::{Map, Value, json};
+
+use crate::core::process_manager::{PROCESS_MANAGER, ProcessConfig, ProcessInfo};
+use crate::core::rclone_rc::RcloneRc;
+
+pub const RCD_PROCESS_ID: &str = "rclone_rcd";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Credentials only live in memory, so a daemon adopted from a previous
+/// session can't be talked to and gets restarted.
+static DAEMON: Mutex<Option<RcloneRc>> = Mutex::new(None);
+static START_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// The shared `rclone rcd` process, if this session started it and it's
+/// still running.
+pub fn running_daemon() -> Option<RcloneRc> {
+    if !PROCESS_MANAGER.is_running(RCD_PROCESS_ID) {
+        return None;
+    }
+    DAEMON.lock().clone()
+}
+
+pub fn daemon_process() -> Result<ProcessInfo, String> {
+    PROCESS_MANAGER.get_status(RCD_PROCESS_ID)
+}
+
+fn discard_process() {
+    if PROCESS_MANAGER.is_registered(RCD_PROCESS_ID) {
+        let _ = PROCESS_MANAGER.stop(RCD_PROCESS_ID);
+        let _ = PROCESS_MANAGER.remove(RCD_PROCESS_ID);
+    }
+}
+
+/// Returns the running daemon or starts it from `config`, an `rclone rcd`
+/// process. The API listens on a random loopback port behind random
+/// credentials passed by environment.
+pub async fn ensure_daemon(mut config: ProcessConfig) -> Result<RcloneRc, String> {
+    let _guard = START_LOCK.lock().await;
+    if let Some(daemon) = running_daemon() {
+        return Ok(daemon);
+    }
+    if PROCESS_MANAGER.is_registered(RCD_PROCESS_ID) {
+        log::info!("Restarting rclone rcd, its credentials are unknown to this session");
+    }
+    discard_process();
+
+    let daemon = RcloneRc::random_local()?;
+    config.id = RCD_PROCESS_ID.to_string();
+    config.args.extend(["--rc-addr".into(), daemon.addr()]);
+    config.secret_env_vars.extend(daemon.env_vars());
+    PROCESS_MANAGER.register_and_start(config)?;
+
+    if let Err(e) = daemon
+        .wait_ready(STARTUP_TIMEOUT, || {
+            PROCESS_MANAGER.is_running(RCD_PROCESS_ID)
+        })
+        .await
+    {
+        discard_process();
+        return Err(format!("rclone rcd did not start: {e}"));
+    }
+    log::info!("Started rclone rcd on {}", daemon.addr());
+    *DAEMON.lock() = Some(daemon.clone());
+    Ok(daemon)
+}
+
+/// Unmounts everything the daemon serves and stops it.
+pub async fn stop_daemon() -> Result<(), String> {
+    let _guard = START_LOCK.lock().await;
+    if let Some(daemon) = running_daemon()
+        && let Err(e) = daemon.call("mount/unmountall", json!({})).await
+    {
+        log::warn!("Failed to unmount rclone rcd mounts: {e}");
+    }
+    *DAEMON.lock() = None;
+    discard_process();
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum OptionKind {
+    Bool,
+    Text,
+    Int,
+    /// Permission bits written in octal, like `--umask 022`.
+    Octal,
+    List,
+}
+
+/// Mount flags `mount/mount` can apply per mount, as (flag, parameter,
+/// option name). Other flags only make sense for the whole daemon.
+const MOUNT_OPTIONS: &[(&str, &str, &str, OptionKind)] = &[
+    (
+        "allow-non-empty",
+        "mountOpt",
+        "AllowNonEmpty",
+        OptionKind::Bool,
+    ),
+    ("allow-other", "mountOpt", "AllowOther", OptionKind::Bool),
+    ("allow-root", "mountOpt", "AllowRoot", OptionKind::Bool),
+    ("async-read", "mountOpt", "AsyncRead", OptionKind::Bool),
+    ("attr-timeout", "mountOpt", "AttrTimeout", OptionKind::Text),
+    (
+        "daemon-timeout",
+        "mountOpt",
+        "DaemonTimeout",
+        OptionKind::Text,
+    ),
+    (
+        "default-permissions",
+        "mountOpt",
+        "DefaultPermissions",
+        OptionKind::Bool,
+    ),
+    ("devname", "mountOpt", "DeviceName", OptionKind::Text),
+    ("direct-io", "mountOpt", "DirectIO", OptionKind::Bool),
+    ("fuse-flag", "mountOpt", "ExtraFlags", OptionKind::List),
+    (
+        "max-read-ahead",
+        "mountOpt",
+        "MaxReadAhead",
+        OptionKind::Text,
+    ),
+    ("network-mode", "mountOpt", "NetworkMode", OptionKind::Bool),
+    (
+        "no-apple-double",
+        "mountOpt",
+        "NoAppleDouble",
+        OptionKind::Bool,
+    ),
+    (
+        "no-apple-xattr",
+        "mountOpt",
+        "NoAppleXattr",
+        OptionKind::Bool,
+    ),
+    ("o", "mountOpt", "ExtraOptions", OptionKind::List),
+    ("option", "mountOpt", "ExtraOptions", OptionKind::List),
+    ("volname", "mountOpt", "VolumeName", OptionKind::Text),
+    (
+        "write-back-cache",
+        "mountOpt",
+        "WritebackCache",
+        OptionKind::Bool,
+    ),
+    ("dir-cache-time", "vfsOpt", "DirCacheTime", OptionKind::Text),
+    ("dir-perms", "vfsOpt", "DirPerms", OptionKind::Octal),
+    ("file-perms", "vfsOpt", "FilePerms", OptionKind::Octal),
+    ("gid", "vfsOpt", "GID", OptionKind::Int),
+    ("no-checksum", "vfsOpt", "NoChecksum", OptionKind::Bool),
+    ("no-modtime", "vfsOpt", "NoModTime", OptionKind::Bool),
+    ("no-seek", "vfsOpt", "NoSeek", OptionKind::Bool),
+    ("poll-interval", "vfsOpt", "PollInterval", OptionKind::Text),
+    ("read-only", "vfsOpt", "ReadOnly", OptionKind::Bool),
+    ("uid", "vfsOpt", "UID", OptionKind::Int),
+    ("umask", "vfsOpt", "Umask", OptionKind::Octal),
+    (
+        "vfs-cache-max-age",
+        "vfsOpt",
+        "CacheMaxAge",
+        OptionKind::Text,
+    ),
+    (
+        "vfs-cache-max-size",
+        "vfsOpt",
+        "CacheMaxSize",
+        OptionKind::Text,
+    ),
+    (
+        "vfs-cache-min-free-space",
+        "vfsOpt",
+        "CacheMinFreeSpace",
+        OptionKind::Text,
+    ),
+    ("vfs-cache-mode", "vfsOpt", "CacheMode", OptionKind::Text),
+    (
+        "vfs-cache-poll-interval",
+        "vfsOpt",
+        "CachePollInterval",
+        OptionKind::Text,
+    ),
+    (
+        "vfs-case-insensitive",
+        "vfsOpt",
+        "CaseInsensitive",
+        OptionKind::Bool,
+    ),
+    (
+        "vfs-fast-fingerprint",
+        "vfsOpt",
+        "FastFingerprint",
+        OptionKind::Bool,
+    ),
+    ("vfs-read-ahead", "vfsOpt", "ReadAhead", OptionKind::Text),
+    (
+        "vfs-read-chunk-size",
+        "vfsOpt",
+        "ChunkSize",
+        OptionKind::Text,
+    ),
+    (
+        "vfs-read-chunk-size-limit",
+        "vfsOpt",
+        "ChunkSizeLimit",
+        OptionKind::Text,
+    ),
+    (
+        "vfs-read-chunk-streams",
+        "vfsOpt",
+        "ChunkStreams",
+        OptionKind::Int,
+    ),
+    ("vfs-read-wait", "vfsOpt", "ReadWait", OptionKind::Text),
+    ("vfs-refresh", "vfsOpt", "Refresh", OptionKind::Bool),
+    ("vfs-used-is-size", "vfsOpt", "UsedIsSize", OptionKind::Bool),
+    ("vfs-write-back", "vfsOpt", "WriteBack", OptionKind::Text),
+    ("vfs-write-wait", "vfsOpt", "WriteWait", OptionKind::Text),
+    ("buffer-size", "_config", "BufferSize", OptionKind::Text),
+    ("checkers", "_config", "Checkers", OptionKind::Int),
+    ("contimeout", "_config", "ConnectTimeout", OptionKind::Text),
+    (
+        "low-level-retries",
+        "_config",
+        "LowLevelRetries",
+        OptionKind::Int,
+    ),
+    (
+        "multi-thread-streams",
+        "_config",
+        "MultiThreadStreams",
+        OptionKind::Int,
+    ),
+    ("retries", "_config", "Retries", OptionKind::Int),
+    ("timeout", "_config", "Timeout", OptionKind::Text),
+    ("transfers", "_config", "Transfers", OptionKind::Int),
+    ("user-agent", "_config", "UserAgent", OptionKind::Text),
+];
+
+fn option_value(flag: &str, kind: OptionKind, value: &str) -> Result<Value, String> {
+    let invalid = || format!("Invalid value '{value}' for --{flag}");
+    match kind {
+        OptionKind::Bool => value
+            .parse::<bool>()
+            .map(Value::from)
+            .map_err(|_| invalid()),
+        OptionKind::Int => value.parse::<i64>().map(Value::from).map_err(|_| invalid()),
+        OptionKind::Octal => u32::from_str_radix(value, 8)
+            .map(Value::from)
+            .map_err(|_| invalid()),
+        OptionKind::Text | OptionKind::List => Ok(Value::from(value)),
+    }
+}
+
+/// Turns `rclone mount` arguments (`remote:path`, mount point, flags) into
+/// `mount/mount` parameters. Flags that can't be set per mount are returned
+/// so the caller can report them.
+pub fn mount_params(args: &[String]) -> Result<(Value, Vec<String>), String> {
+    let mut positionals: Vec<&str> = Vec::new();
+    let mut sections: Map<String, Value> = Map::new();
+    let mut ignored = Vec::new();
+
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            positionals.extend(args.by_ref().map(String::as_str));
+            break;
+        }
+        let Some(flag) = arg
+            .strip_prefix("--")
+            .or_else(|| arg.strip_prefix('-'))
+            .filter(|flag| !flag.is_empty())
+        else {
+            positionals.push(arg);
+            continue;
+        };
+        let (name, inline) = match flag.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (flag, None),
+        };
+
+        let Some(&(_, section, key, kind)) = MOUNT_OPTIONS.iter().find(|(n, ..)| *n == name) else {
+            ignored.push(arg.clone());
+            // Past the positionals, a bare word can only be this flag's value.
+            if inline.is_none()
+                && positionals.len() >= 2
+                && args.peek().is_some_and(|next| !next.starts_with('-'))
+            {
+                ignored.extend(args.next().cloned());
+            }
+            continue;
+        };
+        let value = match (kind, inline) {
+            (_, Some(value)) => value,
+            (OptionKind::Bool, None) => "true",
+            (_, None) => args
+                .next()
+                .map(String::as_str)
+                .ok_or_else(|| format!("Missing value for --{name}"))?,
+        };
+        let value = option_value(name, kind, value)?;
+
+        let options = sections
+            .entry(section)
+            .or_insert_with(|| Value::Object(Map::new()));
+        match kind {
+            OptionKind::List => {
+                let list = options
+                    .as_object_mut()
+                    .map(|options| options.entry(key).or_insert_with(|| json!([])));
+                if let Some(Value::Array(list)) = list {
+                    list.push(value);
+                }
+            }
+            _ => {
+                options[key] = value;
+            }
+        }
+    }
+
+    let [fs, mount_point, ..] = positionals[..] else {
+        return Err("A remote and a mount point are required".into());
+    };
+    sections.insert("fs".into(), json!(fs));
+    sections.insert("mountPoint".into(), json!(mount_point));
+    Ok((Value::Object(sections), ignored))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::mount_params;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn converts_mount_flags_to_rc_options() {
+        let (params, ignored) = mount_params(&args(&[
+            "nas:/media",
+            "/mnt/nas",
+            "--vfs-cache-mode=full",
+            "--read-only",
+            "--umask",
+            "022",
+            "--allow-other=false",
+            "-o",
+            "uid=1000",
+            "--option=gid=1000",
+            "--transfers",
+            "8",
+            "--log-file",
+            "/tmp/rclone.log",
+            "--cache-dir=/var/cache/nas",
+            "-v",
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            params,
+            json!({
+                "fs": "nas:/media",
+                "mountPoint": "/mnt/nas",
+                "vfsOpt": { "CacheMode": "full", "ReadOnly": true, "Umask": 18 },
+                "mountOpt": { "AllowOther": false, "ExtraOptions": ["uid=1000", "gid=1000"] },
+                "_config": { "Transfers": 8 },
+            })
+        );
+        assert_eq!(
+            ignored,
+            vec![
+                "--log-file",
+                "/tmp/rclone.log",
+                "--cache-dir=/var/cache/nas",
+                "-v"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_mount_arguments() {
+        assert!(mount_params(&args(&["nas:"])).is_err());
+        assert!(mount_params(&args(&["nas:", "/mnt", "--uid=me"])).is_err());
+        assert!(mount_params(&args(&["nas:", "/mnt", "--vfs-cache-mode"])).is_err());
+    }
+}
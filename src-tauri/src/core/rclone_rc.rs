@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, TcpListener};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rand::distr::{Alphanumeric, SampleString};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::time::{Instant, sleep};
+
+/// Mounting waits for rclone to reach the remote, so allow more than a ping.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Client for the remote-control API of an rclone process.
+#[derive(Clone)]
+pub struct RcloneRc {
+    port: u16,
+    base_url: String,
+    user: String,
+    pass: String,
+    http: reqwest::Client,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RcMount {
+    pub fs: String,
+    pub mount_point: String,
+}
+
+/// Transfer and VFS cache figures of one mount.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RcloneMountStats {
+    /// Bytes per second over the current transfers.
+    pub speed: f64,
+    pub transfers: u64,
+    pub queued_uploads: u64,
+    pub uploads_in_progress: u64,
+    pub cache_size: u64,
+    pub cache_files: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+    /// Transfer figures cover every mount of the shared daemon.
+    pub shared: bool,
+}
+
+/// Endpoints of `rclone mount` processes started with `--rc`, by process id.
+/// Like the daemon's, their credentials only live in memory.
+static ENDPOINTS: Mutex<Option<HashMap<String, RcloneRc>>> = Mutex::new(None);
+
+pub fn register_endpoint(process_id: &str, rc: RcloneRc) {
+    ENDPOINTS
+        .lock()
+        .get_or_insert_with(HashMap::new)
+        .insert(process_id.to_string(), rc);
+}
+
+pub fn endpoint(process_id: &str) -> Option<RcloneRc> {
+    ENDPOINTS.lock().as_ref()?.get(process_id).cloned()
+}
+
+pub fn forget_endpoint(process_id: &str) {
+    if let Some(endpoints) = ENDPOINTS.lock().as_mut() {
+        endpoints.remove(process_id);
+    }
+}
+
+fn free_local_port() -> Result<u16, String> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("Failed to find a free port for the rclone rc API: {e}"))
+}
+
+impl RcloneRc {
+    fn new(port: u16, base_url: String, user: String, pass: String) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .no_proxy()
+            .build()
+            .map_err(|e| format!("Failed to create rclone rc client: {e}"))?;
+        Ok(Self {
+            port,
+            base_url,
+            user,
+            pass,
+            http,
+        })
+    }
+
+    /// An endpoint on a free loopback port with random credentials, for a
+    /// process that is yet to be started with [`Self::addr`] and
+    /// [`Self::env_vars`].
+    pub fn random_local() -> Result<Self, String> {
+        let port = free_local_port()?;
+        Self::new(
+            port,
+            format!("http://127.0.0.1:{port}"),
+            Alphanumeric.sample_string(&mut rand::rng(), 16),
+            Alphanumeric.sample_string(&mut rand::rng(), 32),
+        )
+    }
+
+    pub fn addr(&self) -> String {
+        format!("127.0.0.1:{}", self.port)
+    }
+
+    /// Credentials for rclone, passed by environment to keep them out of the
+    /// process list.
+    pub fn env_vars(&self) -> [(String, String); 2] {
+        [
+            ("RCLONE_RC_USER".into(), self.user.clone()),
+            ("RCLONE_RC_PASS".into(), self.pass.clone()),
+        ]
+    }
+
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+        let response = self
+            .http
+            .post(format!("{}/{method}", self.base_url))
+            .basic_auth(&self.user, Some(&self.pass))
+            .json(&params)
+            .send()
+            .await
+            .map_err(|e| format!("rclone rc {method} failed: {e}"))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| format!("rclone rc {method} failed: {e}"))?;
+        let body: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+        if !status.is_success() {
+            let error = body
+                .get("error")
+                .and_then(Value::as_str)
+                .unwrap_or(text.trim());
+            return Err(format!("rclone rc {method} failed ({status}): {error}"));
+        }
+        Ok(body)
+    }
+
+    /// Waits until the API answers, giving up early once `alive` says the
+    /// process exited.
+    pub async fn wait_ready(
+        &self,
+        timeout: Duration,
+        alive: impl Fn() -> bool,
+    ) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.call("rc/noop", json!({})).await {
+                Ok(_) => return Ok(()),
+                Err(e) if Instant::now() >= deadline || !alive() => return Err(e),
+                Err(_) => sleep(Duration::from_millis(200)).await,
+            }
+        }
+    }
+
+    pub async fn list_mounts(&self) -> Result<Vec<RcMount>, String> {
+        let body = self.call("mount/listmounts", json!({})).await?;
+        let mounts = body.get("mountPoints").cloned().unwrap_or(Value::Null);
+        if mounts.is_null() {
+            return Ok(Vec::new());
+        }
+        serde_json::from_value(mounts).map_err(|e| format!("Invalid rclone mount list: {e}"))
+    }
+
+    pub async fn mount(&self, params: Value) -> Result<(), String> {
+        // Remotes may have been edited since the daemon first opened them.
+        if let Err(e) = self.call("fscache/clear", json!({})).await {
+            log::debug!("Failed to clear the rclone fs cache: {e}");
+        }
+        self.call("mount/mount", params).await.map(|_| ())
+    }
+
+    pub async fn unmount(&self, mount_point: &str) -> Result<(), String> {
+        self.call("mount/unmount", json!({ "mountPoint": mount_point }))
+            .await
+            .map(|_| ())
+    }
+
+    /// Stats of the mount of `fs`, which must be given when the process
+    /// serves several mounts.
+    pub async fn mount_stats(&self, fs: Option<&str>) -> Result<RcloneMountStats, String> {
+        let core = self.call("core/stats", json!({})).await?;
+        let vfs_params = fs.map_or_else(|| json!({}), |fs| json!({ "fs": fs }));
+        let vfs = self.call("vfs/stats", vfs_params).await?;
+        Ok(parse_mount_stats(&core, &vfs, fs.is_some()))
+    }
+}
+
+fn parse_mount_stats(core: &Value, vfs: &Value, shared: bool) -> RcloneMountStats {
+    let count = |value: &Value, key: &str| value.get(key).and_then(Value::as_u64).unwrap_or(0);
+    let disk_cache = vfs.get("diskCache").unwrap_or(&Value::Null);
+    RcloneMountStats {
+        speed: core.get("speed").and_then(Value::as_f64).unwrap_or(0.0),
+        transfers: core
+            .get("transferring")
+            .and_then(Value::as_array)
+            .map_or(0, |transferring| transferring.len() as u64),
+        queued_uploads: count(disk_cache, "uploadsQueued"),
+        uploads_in_progress: count(disk_cache, "uploadsInProgress"),
+        cache_size: count(disk_cache, "bytesUsed"),
+        cache_files: count(disk_cache, "files"),
+        errors: count(core, "errors") + count(disk_cache, "erroredFiles"),
+        last_error: core
+            .get("lastError")
+            .and_then(Value::as_str)
+            .filter(|error| !error.is_empty())
+            .map(str::to_string),
+        shared,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::{Matcher, Server};
+    use serde_json::json;
+
+    use super::{RcloneRc, parse_mount_stats};
+
+    #[tokio::test]
+    async fn authenticates_and_reports_rc_errors() {
+        let mut server = Server::new_async().await;
+        let auth = Matcher::Exact("Basic dXNlcjpwYXNz".into());
+        server
+            .mock("POST", "/mount/listmounts")
+            .match_header("authorization", auth.clone())
+            .with_body(r#"{"mountPoints":[{"Fs":"nas:","MountPoint":"/mnt/nas","MountedOn":"2024-01-01T00:00:00Z"}]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/mount/unmount")
+            .match_header("authorization", auth)
+            .with_status(500)
+            .with_body(r#"{"error":"mount not found","status":500}"#)
+            .create_async()
+            .await;
+
+        let rc = RcloneRc::new(0, server.url(), "user".into(), "pass".into()).unwrap();
+        let mounts = rc.list_mounts().await.unwrap();
+        assert_eq!(mounts[0].fs, "nas:");
+        assert_eq!(mounts[0].mount_point, "/mnt/nas");
+        let error = rc.unmount("/mnt/other").await.unwrap_err();
+        assert!(error.contains("mount not found"), "{error}");
+    }
+
+    #[test]
+    fn reads_transfer_and_cache_stats() {
+        let core = json!({
+            "speed": 1048576.5,
+            "errors": 1,
+            "lastError": "upload failed",
+            "transferring": [{ "name": "a.bin" }, { "name": "b.bin" }],
+        });
+        let vfs = json!({
+            "fs": "nas:",
+            "diskCache": {
+                "bytesUsed": 4096,
+                "erroredFiles": 1,
+                "files": 3,
+                "uploadsInProgress": 1,
+                "uploadsQueued": 2,
+            },
+        });
+        let stats = parse_mount_stats(&core, &vfs, false);
+        assert_eq!(stats.speed, 1048576.5);
+        assert_eq!(stats.transfers, 2);
+        assert_eq!(stats.queued_uploads, 2);
+        assert_eq!(stats.uploads_in_progress, 1);
+        assert_eq!(stats.cache_size, 4096);
+        assert_eq!(stats.cache_files, 3);
+        assert_eq!(stats.errors, 2);
+        assert_eq!(stats.last_error.as_deref(), Some("upload failed"));
+
+        // Without a VFS cache there is no diskCache section.
+        let idle = parse_mount_stats(&json!({ "lastError": "" }), &json!({}), true);
+        assert_eq!(idle.cache_size, 0);
+        assert_eq!(idle.last_error, None);
+        assert!(idle.shared);
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::client::{ApiError, OpenListClient};
+use crate::api::types::{TaskInfo, TaskKind};
+use crate::cmd::openlist_core::get_openlist_core_process_status;
+use crate::object::structs::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// Longest wait between polls of a core that keeps failing.
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+/// Tasks that ran at least this long raise a notification when they finish.
+const LONG_TASK: Duration = Duration::from_secs(30);
+
+pub const TASKS_EVENT: &str = "openlist-tasks";
+pub const TASK_FINISHED_EVENT: &str = "openlist-task-finished";
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MonitoredTask {
+    pub kind: TaskKind,
+    #[serde(flatten)]
+    pub task: TaskInfo,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TaskSnapshot {
+    pub profile: String,
+    pub tasks: Vec<MonitoredTask>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FinishedTask {
+    pub profile: String,
+    pub kind: TaskKind,
+    pub task: TaskInfo,
+    pub failed: bool,
+    pub elapsed_secs: u64,
+    /// Whether the frontend should raise a notification in the UI language.
+    pub notify: bool,
+}
+
+/// Spaces out the polls of a core that keeps failing.
+#[derive(Debug, Default)]
+pub struct PollBackoff {
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl PollBackoff {
+    pub fn ready(&self, now: Instant) -> bool {
+        self.retry_at.is_none_or(|at| now >= at)
+    }
+
+    /// Records a failed poll and returns how long to wait before the next.
+    pub fn failed(&mut self, now: Instant) -> Duration {
+        self.failures = self.failures.saturating_add(1);
+        let delay = POLL_INTERVAL
+            .saturating_mul(1 << (self.failures - 1).min(16))
+            .min(MAX_BACKOFF);
+        self.retry_at = Some(now + delay);
+        delay
+    }
+
+    pub fn succeeded(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Remembers the unfinished tasks of one core between polls.
+#[derive(Debug, Default)]
+pub struct TaskTracker {
+    running: HashMap<(TaskKind, String), Instant>,
+    last: Vec<MonitoredTask>,
+    backoff: PollBackoff,
+    /// Client whose credentials the core rejected; polling waits until the
+    /// stored credentials change instead of logging in again and again.
+    rejected: Option<OpenListClient>,
+}
+
+impl TaskTracker {
+    /// Stores the latest unfinished tasks and returns the ones that left the
+    /// queue since the previous poll, with how long they were observed.
+    pub fn update(
+        &mut self,
+        tasks: Vec<MonitoredTask>,
+        now: Instant,
+    ) -> Vec<(TaskKind, String, Duration)> {
+        let mut running = HashMap::with_capacity(tasks.len());
+        for task in &tasks {
+            let key = (task.kind, task.task.id.clone());
+            let first_seen = self.running.get(&key).copied().unwrap_or(now);
+            running.insert(key, first_seen);
+        }
+        let finished = self
+            .running
+            .drain()
+            .filter(|(key, _)| !running.contains_key(key))
+            .map(|((kind, id), first_seen)| (kind, id, now.duration_since(first_seen)))
+            .collect();
+        self.running = running;
+        self.last = tasks;
+        finished
+    }
+
+    pub fn changed(&self, tasks: &[MonitoredTask]) -> bool {
+        self.last != tasks
+    }
+}
+
+pub async fn list_tasks(
+    client: &OpenListClient,
+    done: bool,
+) -> Result<Vec<MonitoredTask>, ApiError> {
+    let mut tasks = Vec::new();
+    let mut last_error = None;
+    for kind in TaskKind::ALL {
+        match client.list_tasks(kind, done).await {
+            Ok(list) => tasks.extend(list.into_iter().map(|task| MonitoredTask { kind, task })),
+            // Every other queue would fail to log in the same way.
+            Err(e @ (ApiError::Unauthorized { .. } | ApiError::MissingCredentials { .. })) => {
+                return Err(e);
+            }
+            // Older cores don't have every queue; only fail if none answered.
+            Err(e) => {
+                log::debug!("Failed to list OpenList {} tasks: {e}", kind.as_str());
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) if tasks.is_empty() && !done => Err(e),
+        _ => Ok(tasks),
+    }
+}
+
+async fn should_poll(profile: &str, state: &AppState) -> bool {
+    let Some(settings) = state.get_settings() else {
+        return false;
+    };
+    match settings.core_profile(profile) {
+        Ok(config) if config.is_remote() => true,
+        Ok(_) => get_openlist_core_process_status(profile)
+            .await
+            .map(|info| info.is_running)
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+fn notifications_enabled(app: &AppHandle) -> bool {
+    app.state::<AppState>()
+        .get_settings()
+        .and_then(|settings| settings.app.task_notifications)
+        .unwrap_or(true)
+}
+
+async fn poll_profile(app: &AppHandle, profile: &str, tracker: &mut TaskTracker) {
+    let state = app.state::<AppState>();
+    let client = match state.api_client(profile) {
+        Ok(client) => client,
+        Err(e) => {
+            log::debug!("Skipping task poll for OpenList profile '{profile}': {e}");
+            return;
+        }
+    };
+    if let Some(rejected) = &tracker.rejected {
+        if rejected.same_account(&client) {
+            return;
+        }
+        tracker.rejected = None;
+    }
+    if !tracker.backoff.ready(Instant::now()) {
+        return;
+    }
+    let tasks = match list_tasks(&client, false).await {
+        Ok(tasks) => {
+            tracker.backoff.succeeded();
+            tasks
+        }
+        Err(e @ (ApiError::Unauthorized { .. } | ApiError::MissingCredentials { .. })) => {
+            log::warn!(
+                "Pausing task polling for OpenList profile '{profile}' until its credentials change: {e}"
+            );
+            tracker.rejected = Some(client);
+            return;
+        }
+        Err(e) => {
+            let delay = tracker.backoff.failed(Instant::now());
+            log::debug!(
+                "Failed to poll OpenList tasks for profile '{profile}', retrying in {}s: {e}",
+                delay.as_secs()
+            );
+            return;
+        }
+    };
+
+    if tracker.changed(&tasks) {
+        let snapshot = TaskSnapshot {
+            profile: profile.to_string(),
+            tasks: tasks.clone(),
+        };
+        if let Err(e) = app.emit(TASKS_EVENT, snapshot) {
+            log::error!("Failed to emit {TASKS_EVENT} event: {e}");
+        }
+    }
+    let finished = tracker.update(tasks, Instant::now());
+    if finished.is_empty() {
+        return;
+    }
+
+    let done = list_tasks(&client, true).await.unwrap_or_default();
+    let notifications = notifications_enabled(app);
+    for (kind, id, elapsed) in finished {
+        let Some(task) = done
+            .iter()
+            .find(|done| done.kind == kind && done.task.id == id)
+        else {
+            continue;
+        };
+        let finished = FinishedTask {
+            profile: profile.to_string(),
+            kind,
+            task: task.task.clone(),
+            failed: task.task.failed(),
+            elapsed_secs: elapsed.as_secs(),
+            notify: notifications && (task.task.failed() || elapsed >= LONG_TASK),
+        };
+        log::info!(
+            "OpenList {} task '{}' {} after {}s",
+            kind.as_str(),
+            finished.task.name,
+            if finished.failed {
+                "failed"
+            } else {
+                "finished"
+            },
+            finished.elapsed_secs
+        );
+        if let Err(e) = app.emit(TASK_FINISHED_EVENT, finished) {
+            log::error!("Failed to emit {TASK_FINISHED_EVENT} event: {e}");
+        }
+    }
+}
+
+/// Polls the task queues of every running core profile for the lifetime of
+/// the app, emitting snapshots and completion events.
+pub fn start_task_monitor(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut trackers: HashMap<String, TaskTracker> = HashMap::new();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let Some(settings) = app.state::<AppState>().get_settings() else {
+                continue;
+            };
+            let profiles = settings.core_profile_names();
+            trackers.retain(|profile, _| profiles.contains(profile));
+            for profile in profiles {
+                let tracker = trackers.entry(profile.clone()).or_default();
+                if should_poll(&profile, &app.state::<AppState>()).await {
+                    poll_profile(&app, &profile, tracker).await;
+                } else if !tracker.last.is_empty() {
+                    *tracker = TaskTracker::default();
+                    let snapshot = TaskSnapshot {
+                        profile,
+                        tasks: Vec::new(),
+                    };
+                    if let Err(e) = app.emit(TASKS_EVENT, snapshot) {
+                        log::error!("Failed to emit {TASKS_EVENT} event: {e}");
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use mockito::Server;
+    use serde_json::json;
+
+    use super::{MAX_BACKOFF, MonitoredTask, POLL_INTERVAL, PollBackoff, TaskTracker, list_tasks};
+    use crate::api::client::{ApiError, Credentials, OpenListClient};
+    use crate::api::types::{TaskInfo, TaskKind};
+
+    fn task(kind: TaskKind, id: &str) -> MonitoredTask {
+        MonitoredTask {
+            kind,
+            task: serde_json::from_value(serde_json::json!({ "id": id, "name": id })).unwrap(),
+        }
+    }
+
+    #[test]
+    fn reports_tasks_that_left_the_queue() {
+        let start = Instant::now();
+        let mut tracker = TaskTracker::default();
+        let first = vec![task(TaskKind::Copy, "a"), task(TaskKind::Upload, "a")];
+        assert!(tracker.changed(&first));
+        assert!(tracker.update(first.clone(), start).is_empty());
+        assert!(!tracker.changed(&first));
+
+        let later = start + Duration::from_secs(40);
+        let finished = tracker.update(vec![task(TaskKind::Upload, "a")], later);
+        assert_eq!(
+            finished,
+            vec![(TaskKind::Copy, "a".to_string(), Duration::from_secs(40))]
+        );
+    }
+
+    #[test]
+    fn failed_tasks_carry_an_error() {
+        let failed: TaskInfo =
+            serde_json::from_value(serde_json::json!({ "id": "1", "name": "x", "state": 7 }))
+                .unwrap();
+        assert!(failed.failed());
+        let succeeded: TaskInfo = serde_json::from_value(
+            serde_json::json!({ "id": "2", "name": "y", "state": 2, "error": "" }),
+        )
+        .unwrap();
+        assert!(!succeeded.failed());
+    }
+
+    #[test]
+    fn backs_off_after_failed_polls() {
+        let start = Instant::now();
+        let mut backoff = PollBackoff::default();
+        assert!(backoff.ready(start));
+
+        assert_eq!(backoff.failed(start), POLL_INTERVAL);
+        assert!(!backoff.ready(start));
+        assert!(backoff.ready(start + POLL_INTERVAL));
+        assert_eq!(backoff.failed(start), POLL_INTERVAL * 2);
+        assert_eq!(backoff.failed(start), POLL_INTERVAL * 4);
+        for _ in 0..40 {
+            backoff.failed(start);
+        }
+        assert_eq!(backoff.failed(start), MAX_BACKOFF);
+
+        backoff.succeeded();
+        assert!(backoff.ready(start));
+        assert_eq!(backoff.failed(start), POLL_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_first_rejected_login() {
+        let mut server = Server::new_async().await;
+        let login = server
+            .mock("POST", "/api/auth/login")
+            .with_body(
+                json!({ "code": 400, "message": "password is incorrect", "data": null })
+                    .to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let credentials = Credentials {
+            username: "admin".into(),
+            password: "wrong".into(),
+        };
+        let client = OpenListClient::new(&server.url(), Some(credentials.clone()), false).unwrap();
+
+        let error = list_tasks(&client, false).await.unwrap_err();
+        assert!(matches!(error, ApiError::Unauthorized { .. }), "{error:?}");
+        login.assert_async().await;
+
+        let same = OpenListClient::new(&server.url(), Some(credentials), false).unwrap();
+        assert!(client.same_account(&same));
+        let changed = OpenListClient::new(&server.url(), None, false).unwrap();
+        assert!(!client.same_account(&changed));
+    }
+}
@@ -0,0 +1,714 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::api::client::{ApiError, OpenListClient};
+use crate::api::types::{FsGet, FsListRequest};
+use crate::object::structs::AppState;
+
+pub const TRANSFER_EVENT: &str = "openlist-transfer";
+pub const DEFAULT_TRANSFER_CONCURRENCY: usize = 3;
+const CHUNK_SIZE: usize = 256 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+const PARTIAL_SUFFIX: &str = ".part";
+
+const CONTROL_RUN: u8 = 0;
+const CONTROL_PAUSE: u8 = 1;
+const CONTROL_CANCEL: u8 = 2;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferState {
+    Queued,
+    Running,
+    Paused,
+    Completed,
+    Failed,
+    Canceled,
+}
+
+impl TransferState {
+    fn is_finished(self) -> bool {
+        matches!(
+            self,
+            TransferState::Completed | TransferState::Failed | TransferState::Canceled
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Transfer {
+    pub id: u64,
+    pub profile: String,
+    pub direction: TransferDirection,
+    pub local_path: String,
+    pub remote_path: String,
+    pub total_bytes: u64,
+    pub transferred_bytes: u64,
+    pub state: TransferState,
+    pub error: Option<String>,
+}
+
+struct Entry {
+    transfer: Transfer,
+    control: Arc<AtomicU8>,
+}
+
+/// Why a transfer stopped before completing.
+enum Stop {
+    Paused,
+    Canceled,
+    Failed(String),
+}
+
+impl From<ApiError> for Stop {
+    fn from(error: ApiError) -> Self {
+        Stop::Failed(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for Stop {
+    fn from(error: std::io::Error) -> Self {
+        Stop::Failed(error.to_string())
+    }
+}
+
+/// Uploads and downloads through the OpenList API, run a few at a time.
+#[derive(Default)]
+pub struct TransferQueue {
+    entries: Mutex<BTreeMap<u64, Entry>>,
+    next_id: AtomicU64,
+}
+
+impl TransferQueue {
+    pub fn list(&self) -> Vec<Transfer> {
+        self.entries
+            .lock()
+            .values()
+            .map(|entry| entry.transfer.clone())
+            .collect()
+    }
+
+    fn add(
+        &self,
+        profile: &str,
+        direction: TransferDirection,
+        local_path: String,
+        remote_path: String,
+        total_bytes: u64,
+    ) -> Transfer {
+        let transfer = Transfer {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            profile: profile.to_string(),
+            direction,
+            local_path,
+            remote_path,
+            total_bytes,
+            transferred_bytes: 0,
+            state: TransferState::Queued,
+            error: None,
+        };
+        self.entries.lock().insert(
+            transfer.id,
+            Entry {
+                transfer: transfer.clone(),
+                control: Arc::new(AtomicU8::new(CONTROL_RUN)),
+            },
+        );
+        transfer
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut Transfer)) -> Option<Transfer> {
+        let mut entries = self.entries.lock();
+        let entry = entries.get_mut(&id)?;
+        f(&mut entry.transfer);
+        Some(entry.transfer.clone())
+    }
+
+    pub fn pause(&self, id: u64) -> Result<Transfer, String> {
+        let mut entries = self.entries.lock();
+        let entry = entries.get_mut(&id).ok_or("Transfer not found")?;
+        match entry.transfer.state {
+            TransferState::Queued => entry.transfer.state = TransferState::Paused,
+            TransferState::Running => entry.control.store(CONTROL_PAUSE, Ordering::Relaxed),
+            TransferState::Paused => {}
+            _ => return Err("Only queued or running transfers can be paused".into()),
+        }
+        Ok(entry.transfer.clone())
+    }
+
+    pub fn resume(&self, id: u64) -> Result<Transfer, String> {
+        let mut entries = self.entries.lock();
+        let entry = entries.get_mut(&id).ok_or("Transfer not found")?;
+        match entry.transfer.state {
+            TransferState::Paused | TransferState::Failed => {
+                entry.control.store(CONTROL_RUN, Ordering::Relaxed);
+                entry.transfer.state = TransferState::Queued;
+                entry.transfer.error = None;
+            }
+            TransferState::Running => entry.control.store(CONTROL_RUN, Ordering::Relaxed),
+            _ => return Err("Only paused or failed transfers can be resumed".into()),
+        }
+        Ok(entry.transfer.clone())
+    }
+
+    pub fn cancel(&self, id: u64) -> Result<Transfer, String> {
+        let mut entries = self.entries.lock();
+        let entry = entries.get_mut(&id).ok_or("Transfer not found")?;
+        match entry.transfer.state {
+            TransferState::Running => entry.control.store(CONTROL_CANCEL, Ordering::Relaxed),
+            state if state.is_finished() => {}
+            _ => {
+                entry.transfer.state = TransferState::Canceled;
+                if entry.transfer.direction == TransferDirection::Download {
+                    let _ = std::fs::remove_file(partial_path(&entry.transfer.local_path));
+                }
+            }
+        }
+        Ok(entry.transfer.clone())
+    }
+
+    /// Drops completed, failed and canceled transfers from the list.
+    pub fn clear_finished(&self) {
+        self.entries
+            .lock()
+            .retain(|_, entry| !entry.transfer.state.is_finished());
+    }
+
+    /// Marks queued transfers as running while fewer than `limit` are, and
+    /// returns the ones that should start now.
+    fn take_startable(&self, limit: usize) -> Vec<(Transfer, Arc<AtomicU8>)> {
+        let mut entries = self.entries.lock();
+        let running = entries
+            .values()
+            .filter(|entry| entry.transfer.state == TransferState::Running)
+            .count();
+        entries
+            .values_mut()
+            .filter(|entry| entry.transfer.state == TransferState::Queued)
+            .take(limit.saturating_sub(running))
+            .map(|entry| {
+                entry.transfer.state = TransferState::Running;
+                entry.control.store(CONTROL_RUN, Ordering::Relaxed);
+                (entry.transfer.clone(), entry.control.clone())
+            })
+            .collect()
+    }
+}
+
+fn partial_path(local_path: &str) -> PathBuf {
+    PathBuf::from(format!("{local_path}{PARTIAL_SUFFIX}"))
+}
+
+/// Checks that a name the server returned is a single plain file name, so a
+/// malicious listing can't write outside the download folder.
+fn local_name(name: &str) -> Result<&str, ApiError> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) if !name.contains(['/', '\\']) => Ok(name),
+        _ => Err(format!("OpenList returned an unsafe file name '{name}'").into()),
+    }
+}
+
+pub fn join_remote(dir: &str, name: &str) -> String {
+    let dir = dir.trim_end_matches('/');
+    let name = name.trim_start_matches('/');
+    format!("{dir}/{name}")
+}
+
+/// Files below `root` paired with their `/`-separated path relative to the
+/// parent of `root`, so a folder keeps its own name on the other side.
+pub fn collect_upload_files(root: &Path) -> Result<Vec<(PathBuf, String, u64)>, String> {
+    let base = root.parent().unwrap_or(root);
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let metadata =
+            std::fs::metadata(&path).map_err(|e| format!("Failed to read {path:?}: {e}"))?;
+        if metadata.is_dir() {
+            let entries =
+                std::fs::read_dir(&path).map_err(|e| format!("Failed to read {path:?}: {e}"))?;
+            for entry in entries {
+                pending.push(
+                    entry
+                        .map_err(|e| format!("Failed to read {path:?}: {e}"))?
+                        .path(),
+                );
+            }
+        } else {
+            let relative = path
+                .strip_prefix(base)
+                .map_err(|e| format!("Failed to resolve {path:?}: {e}"))?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((path, relative, metadata.len()));
+        }
+    }
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+fn emit(app: &AppHandle, transfer: &Transfer) {
+    if let Err(e) = app.emit(TRANSFER_EVENT, transfer) {
+        log::error!("Failed to emit {TRANSFER_EVENT} event: {e}");
+    }
+}
+
+fn concurrency(app: &AppHandle) -> usize {
+    app.state::<AppState>()
+        .get_settings()
+        .and_then(|settings| settings.app.transfer_concurrency)
+        .map(|limit| limit.max(1) as usize)
+        .unwrap_or(DEFAULT_TRANSFER_CONCURRENCY)
+}
+
+/// Starts as many queued transfers as the concurrency limit allows.
+pub fn pump(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    for (transfer, control) in state.transfers.take_startable(concurrency(app)) {
+        emit(app, &transfer);
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            run(&app, transfer, control).await;
+            pump(&app);
+        });
+    }
+}
+
+async fn run(app: &AppHandle, transfer: Transfer, control: Arc<AtomicU8>) {
+    let state = app.state::<AppState>();
+    let result = match state.api_client(&transfer.profile) {
+        Ok(client) => {
+            let mut progress = Progress {
+                app,
+                id: transfer.id,
+                control: &control,
+                last_emit: Instant::now(),
+            };
+            match transfer.direction {
+                TransferDirection::Upload => upload(&client, &transfer, &mut progress).await,
+                TransferDirection::Download => download(&client, &transfer, &mut progress).await,
+            }
+        }
+        Err(e) => Err(Stop::from(e)),
+    };
+
+    let updated = state.transfers.update(transfer.id, |t| match result {
+        Ok(()) => {
+            t.state = TransferState::Completed;
+            t.transferred_bytes = t.total_bytes;
+        }
+        Err(Stop::Paused) => t.state = TransferState::Paused,
+        Err(Stop::Canceled) => t.state = TransferState::Canceled,
+        Err(Stop::Failed(error)) => {
+            log::warn!("Transfer of '{}' failed: {error}", t.local_path);
+            t.state = TransferState::Failed;
+            t.error = Some(error);
+        }
+    });
+    if let Some(updated) = updated {
+        if updated.state == TransferState::Canceled
+            && updated.direction == TransferDirection::Download
+        {
+            let _ = tokio::fs::remove_file(partial_path(&updated.local_path)).await;
+        }
+        emit(app, &updated);
+    }
+}
+
+struct Progress<'a> {
+    app: &'a AppHandle,
+    id: u64,
+    control: &'a AtomicU8,
+    last_emit: Instant,
+}
+
+impl Progress<'_> {
+    fn check(&self) -> Result<(), Stop> {
+        match self.control.load(Ordering::Relaxed) {
+            CONTROL_PAUSE => Err(Stop::Paused),
+            CONTROL_CANCEL => Err(Stop::Canceled),
+            _ => Ok(()),
+        }
+    }
+
+    fn set(&mut self, transferred: u64, total: Option<u64>) {
+        let state = self.app.state::<AppState>();
+        let updated = state.transfers.update(self.id, |t| {
+            t.transferred_bytes = transferred;
+            if let Some(total) = total {
+                t.total_bytes = total;
+            }
+        });
+        if let Some(updated) = updated
+            && self.last_emit.elapsed() >= PROGRESS_INTERVAL
+        {
+            self.last_emit = Instant::now();
+            emit(self.app, &updated);
+        }
+    }
+}
+
+/// `fs/put` has no way to continue a partial upload, so every attempt sends
+/// the whole file.
+async fn upload(
+    client: &OpenListClient,
+    transfer: &Transfer,
+    progress: &mut Progress<'_>,
+) -> Result<(), Stop> {
+    let file = tokio::fs::File::open(&transfer.local_path).await?;
+    let metadata = file.metadata().await?;
+    let size = metadata.len();
+    let modified_ms = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_millis());
+    progress.set(0, Some(size));
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<u64>(16);
+    let control = Arc::new(AtomicU8::new(progress.control.load(Ordering::Relaxed)));
+    let stream = futures_util::stream::unfold(
+        (file, 0u64, tx, control.clone()),
+        |(mut file, sent, tx, control)| async move {
+            if control.load(Ordering::Relaxed) != CONTROL_RUN {
+                return None;
+            }
+            let mut buf = vec![0; CHUNK_SIZE];
+            match file.read(&mut buf).await {
+                Ok(0) => None,
+                Ok(n) => {
+                    buf.truncate(n);
+                    let sent = sent + n as u64;
+                    let _ = tx.try_send(sent);
+                    Some((Ok(buf), (file, sent, tx, control)))
+                }
+                Err(e) => Some((Err(e), (file, sent, tx, control))),
+            }
+        },
+    );
+
+    let remote_path = transfer.remote_path.clone();
+    let put = client.put_file(
+        &remote_path,
+        size,
+        modified_ms,
+        reqwest::Body::wrap_stream(stream),
+    );
+    tokio::pin!(put);
+    loop {
+        tokio::select! {
+            result = &mut put => {
+                return result.map_err(|e| progress.check().err().unwrap_or(Stop::from(e)));
+            }
+            Some(sent) = rx.recv() => {
+                progress.set(sent, None);
+                if let Err(stop) = progress.check() {
+                    // Ends the body stream; dropping the request aborts it.
+                    control.store(CONTROL_CANCEL, Ordering::Relaxed);
+                    return Err(stop);
+                }
+            }
+        }
+    }
+}
+
+/// Downloads into a `.part` file next to the target, continuing from its
+/// current size when the server honours range requests.
+async fn download(
+    client: &OpenListClient,
+    transfer: &Transfer,
+    progress: &mut Progress<'_>,
+) -> Result<(), Stop> {
+    let info = client.get_file(&transfer.remote_path).await?;
+    if info.is_dir {
+        return Err(Stop::Failed(format!(
+            "'{}' is a directory",
+            transfer.remote_path
+        )));
+    }
+    if info.raw_url.is_empty() {
+        return Err(Stop::Failed("OpenList returned no download URL".into()));
+    }
+    ensure_free(&transfer.local_path).await?;
+    let partial = partial_path(&transfer.local_path);
+    if let Some(parent) = partial.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let offset = tokio::fs::metadata(&partial)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    let offset = if offset >= info.size { 0 } else { offset };
+
+    let mut response = client.open_download(&info.raw_url, offset).await?;
+    let resumed = offset > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&partial)
+        .await?;
+    let mut received = if resumed { offset } else { 0 };
+    progress.set(received, Some(info.size));
+
+    while let Some(chunk) = response.chunk().await.map_err(ApiError::from)? {
+        progress.check()?;
+        file.write_all(&chunk).await?;
+        received += chunk.len() as u64;
+        progress.set(received, None);
+    }
+    file.flush().await?;
+    drop(file);
+    // Something may have been put there while downloading.
+    ensure_free(&transfer.local_path).await?;
+    tokio::fs::rename(&partial, &transfer.local_path).await?;
+    Ok(())
+}
+
+/// Downloads never overwrite local files; the transfer fails instead and can
+/// be resumed once the file is moved away.
+async fn ensure_free(local_path: &str) -> Result<(), Stop> {
+    if tokio::fs::try_exists(local_path).await? {
+        return Err(Stop::Failed(format!("'{local_path}' already exists")));
+    }
+    Ok(())
+}
+
+pub fn enqueue_upload(
+    app: &AppHandle,
+    profile: &str,
+    local_path: &Path,
+    remote_dir: &str,
+) -> Result<Vec<Transfer>, String> {
+    let state = app.state::<AppState>();
+    let transfers = collect_upload_files(local_path)?
+        .into_iter()
+        .map(|(path, relative, size)| {
+            state.transfers.add(
+                profile,
+                TransferDirection::Upload,
+                path.to_string_lossy().into_owned(),
+                join_remote(remote_dir, &relative),
+                size,
+            )
+        })
+        .collect::<Vec<_>>();
+    transfers.iter().for_each(|transfer| emit(app, transfer));
+    pump(app);
+    Ok(transfers)
+}
+
+/// Files below `remote_path` paired with where they go in `local_dir`.
+async fn collect_download_files(
+    client: &OpenListClient,
+    remote_path: &str,
+    local_dir: &Path,
+) -> Result<Vec<(String, PathBuf, u64)>, ApiError> {
+    let info = client.get_file(remote_path).await?;
+    let mut files = Vec::new();
+    let mut pending = vec![(
+        remote_path.to_string(),
+        local_dir.join(local_name(&info.name)?),
+        info,
+    )];
+    while let Some((remote, local, info)) = pending.pop() {
+        if !info.is_dir {
+            files.push((remote, local, info.size));
+            continue;
+        }
+        let listing = client
+            .list_dir(&FsListRequest {
+                path: remote.clone(),
+                ..Default::default()
+            })
+            .await?;
+        for object in listing.content {
+            let child = FsGet {
+                name: object.name.clone(),
+                size: object.size,
+                is_dir: object.is_dir,
+                raw_url: String::new(),
+                sign: String::new(),
+            };
+            pending.push((
+                join_remote(&remote, &object.name),
+                local.join(local_name(&object.name)?),
+                child,
+            ));
+        }
+    }
+    Ok(files)
+}
+
+pub async fn enqueue_download(
+    app: &AppHandle,
+    client: &OpenListClient,
+    profile: &str,
+    remote_path: &str,
+    local_dir: &Path,
+) -> Result<Vec<Transfer>, ApiError> {
+    let files = collect_download_files(client, remote_path, local_dir).await?;
+    let state = app.state::<AppState>();
+    let transfers = files
+        .into_iter()
+        .map(|(remote, local, size)| {
+            state.transfers.add(
+                profile,
+                TransferDirection::Download,
+                local.to_string_lossy().into_owned(),
+                remote,
+                size,
+            )
+        })
+        .collect::<Vec<_>>();
+    transfers.iter().for_each(|transfer| emit(app, transfer));
+    pump(app);
+    Ok(transfers)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use mockito::Server;
+    use serde_json::json;
+
+    use super::{
+        TransferDirection, TransferQueue, TransferState, collect_download_files,
+        collect_upload_files, join_remote, local_name,
+    };
+    use crate::api::client::{Credentials, OpenListClient};
+
+    #[test]
+    fn joins_remote_paths() {
+        assert_eq!(join_remote("/", "a.txt"), "/a.txt");
+        assert_eq!(join_remote("/team/", "/docs/a.txt"), "/team/docs/a.txt");
+    }
+
+    #[test]
+    fn rejects_unsafe_file_names() {
+        assert_eq!(local_name("report.pdf").unwrap(), "report.pdf");
+        assert_eq!(local_name("..hidden").unwrap(), "..hidden");
+        for name in [
+            "",
+            ".",
+            "..",
+            "../x",
+            "/etc/passwd",
+            "a/b",
+            "..\\x",
+            "C:\\x",
+        ] {
+            assert!(local_name(name).is_err(), "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn downloads_stay_inside_the_target_folder() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/auth/login")
+            .with_body(json!({ "code": 200, "data": { "token": "jwt" } }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/api/fs/get")
+            .with_body(
+                json!({
+                    "code": 200,
+                    "message": "success",
+                    "data": { "name": "docs", "is_dir": true }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/api/fs/list")
+            .with_body(
+                json!({
+                    "code": 200,
+                    "message": "success",
+                    "data": { "content": [
+                        { "name": "a.txt", "size": 1, "is_dir": false },
+                        { "name": "../../.bashrc", "size": 1, "is_dir": false },
+                    ] }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let credentials = Credentials {
+            username: "admin".into(),
+            password: "secret".into(),
+        };
+        let client = OpenListClient::new(&server.url(), Some(credentials), false).unwrap();
+
+        let error = collect_download_files(&client, "/docs", Path::new("/tmp/downloads"))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("unsafe file name"), "{error}");
+    }
+
+    #[test]
+    fn uploads_keep_the_folder_name() {
+        let root = std::env::temp_dir().join(format!("openlist-upload-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        std::fs::write(root.join("sub").join("b.txt"), "bb").unwrap();
+
+        let files = collect_upload_files(&root).unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+        let name = root.file_name().unwrap().to_string_lossy();
+        let relative: Vec<_> = files.iter().map(|(_, r, s)| (r.clone(), *s)).collect();
+        assert_eq!(
+            relative,
+            vec![
+                (format!("{name}/a.txt"), 1),
+                (format!("{name}/sub/b.txt"), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn starts_transfers_up_to_the_limit() {
+        let queue = TransferQueue::default();
+        for i in 0..3 {
+            queue.add(
+                "default",
+                TransferDirection::Upload,
+                format!("/tmp/{i}"),
+                format!("/{i}"),
+                1,
+            );
+        }
+        queue.pause(2).unwrap();
+        let started: Vec<_> = queue.take_startable(2).iter().map(|(t, _)| t.id).collect();
+        assert_eq!(started, vec![1, 3]);
+        assert!(queue.take_startable(2).is_empty());
+
+        queue.resume(2).unwrap();
+        assert_eq!(queue.list()[1].state, TransferState::Queued);
+        queue.cancel(2).unwrap();
+        queue.clear_finished();
+        assert_eq!(queue.list().len(), 2);
+    }
+}
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use tauri::Manager;
 use url::{Host, Url};
 
+mod api;
 mod cmd;
 mod conf;
 mod core;
@@ -16,23 +17,41 @@ use cmd::config::{load_settings, reset_settings, save_settings, save_settings_an
 use cmd::firewall::{add_firewall_rule, check_firewall_rule, remove_firewall_rule};
 use cmd::logs::{clear_logs, get_logs};
 use cmd::macos_dock::set_dock_icon_visibility;
+use cmd::network::{get_network_access_info, list_network_interfaces};
+use cmd::openlist_api::{
+    cancel_openlist_task, delete_openlist_storage, export_openlist_config, get_openlist_api_user,
+    import_openlist_config, list_openlist_files, list_openlist_settings, list_openlist_storages,
+    list_openlist_tasks, reload_openlist_storage, retry_openlist_task,
+    set_openlist_storage_enabled, share_local_folder,
+};
 use cmd::openlist_core::{get_openlist_core_status, start_openlist_core, stop_openlist_core};
 use cmd::os_operate::{
     get_available_versions, open_file, open_folder, open_logs_directory, open_openlist_data_dir,
     open_rclone_config_file, open_settings_file, open_url_in_browser, select_directory,
     update_tool_version,
 };
+use cmd::rclone_cache::{get_mount_cache_info, purge_mount_cache, refresh_mount_dir};
 use cmd::rclone_core::check_rclone_available;
 use cmd::rclone_mount::{
-    check_mount_status, get_mount_info_list, mount_remote, rclone_create_remote,
-    rclone_delete_remote, rclone_list_config, rclone_list_remotes, rclone_update_remote,
-    unmount_remote,
+    check_mount_status, encrypt_rclone_config, get_mount_info_list, mount_remote,
+    rclone_backend_schema, rclone_config_status, rclone_create_remote, rclone_delete_remote,
+    rclone_list_config, rclone_list_remotes, rclone_reveal_remote_secret, rclone_update_remote,
+    unlock_rclone_config, unmount_remote,
+};
+use cmd::share_link::create_openlist_link;
+use cmd::transfers::{
+    cancel_transfer, clear_finished_transfers, download_from_openlist, list_transfers,
+    pause_transfer, resume_transfer, upload_to_openlist,
 };
 use cmd::updater::{get_current_version, is_auto_check_enabled, set_auto_check_enabled};
+use cmd::users::{
+    clear_openlist_user_2fa, create_openlist_user, list_openlist_users,
+    reset_openlist_user_password, update_openlist_user,
+};
 use object::structs::*;
 use tauri::Emitter;
 
-use crate::cmd::rclone_mount::{MountProcessInput, get_mount_process_id};
+use crate::cmd::rclone_mount::mount_input;
 use crate::conf::rclone::RcloneMountConfig;
 use crate::conf::rclone_config::RcloneConfigFile;
 
@@ -93,7 +112,7 @@ fn setup_background_update_checker(app_handle: &tauri::AppHandle) {
     });
 }
 
-fn is_local_openlist_url(url: &str) -> bool {
+pub(crate) fn is_local_openlist_url(url: &str) -> bool {
     Url::parse(url)
         .map(|url| match url.host() {
             Some(Host::Domain("localhost")) => true,
@@ -111,18 +130,25 @@ async fn auto_start_openlist_core_on_login(app_handle: &tauri::AppHandle) -> Res
         .read()
         .clone()
         .ok_or("Failed to read app settings")?;
-    if settings.openlist.auto_launch {
-        log::info!("Auto-start on login is enabled, starting OpenList Core process");
-        match start_openlist_core(app_state.clone()).await {
-            Ok(_) => {
-                log::info!("OpenList Core process started successfully on login");
-            }
-            Err(e) => {
-                log::error!("Failed to start OpenList Core process on login: {e}");
+    for profile in settings.core_profile_names() {
+        let config = settings.core_profile(&profile)?;
+        if config.is_remote() {
+            log::info!(
+                "OpenList profile '{profile}' is a remote instance, skipping core auto-start"
+            );
+        } else if config.auto_launch {
+            log::info!("Auto-start on login is enabled, starting OpenList Core '{profile}'");
+            match start_openlist_core(Some(profile.clone()), app_state.clone()).await {
+                Ok(_) => {
+                    log::info!("OpenList Core '{profile}' started successfully on login");
+                }
+                Err(e) => {
+                    log::error!("Failed to start OpenList Core '{profile}' on login: {e}");
+                }
             }
+        } else {
+            log::info!("Auto-start on login is disabled for OpenList Core '{profile}'");
         }
-    } else {
-        log::info!("Auto-start on login is disabled");
     }
     Ok(())
 }
@@ -156,19 +182,17 @@ async fn auto_mount_rclone_remotes_on_login(app_handle: &tauri::AppHandle) -> Re
         .map(|config| {
             remotes_to_mount.iter().any(|remote| {
                 config
-                    .remotes
-                    .get(&remote.name)
-                    .and_then(|remote| remote.options.get("url"))
-                    .is_some_and(|url| is_local_openlist_url(url))
+                    .option(&remote.name, "url")
+                    .is_some_and(|url| is_local_openlist_url(&url))
             })
         })
         .unwrap_or_else(|e| {
             log::error!("Failed to load Rclone config before mounting remotes: {e}");
             false
         });
-    if !settings.openlist.auto_launch && has_local_remote {
+    if !settings.openlist.is_remote() && !settings.openlist.auto_launch && has_local_remote {
         log::info!("Trying to auto-start OpenList Core before mounting local remotes");
-        match start_openlist_core(app_state.clone()).await {
+        match start_openlist_core(None, app_state.clone()).await {
             Ok(_) => {
                 log::info!(
                     "OpenList Core process started successfully before mounting local remotes"
@@ -186,25 +210,7 @@ async fn auto_mount_rclone_remotes_on_login(app_handle: &tauri::AppHandle) -> Re
             "Auto-mount on login is enabled for remote '{}', attempting to mount",
             remote.name
         );
-        let mut args = vec![
-            format!(
-                "{}:{}",
-                remote.name,
-                remote.volume_name.as_deref().unwrap_or("")
-            ),
-            remote.mount_point.as_deref().unwrap_or("").to_string(),
-        ];
-        if let Some(extra_flags) = &remote.extra_flags {
-            args.extend(extra_flags.clone());
-        }
-        let id = get_mount_process_id(&remote.name);
-        let create_remote_config = MountProcessInput {
-            id: id.clone(),
-            name: id.clone(),
-            args,
-            network_mode: remote.network_mode.unwrap_or(false),
-        };
-        match mount_remote(create_remote_config, app_state.clone()).await {
+        match mount_remote(mount_input(&remote), app_state.clone()).await {
             Ok(_) => {
                 log::info!(
                     "Rclone remote '{}' mounted successfully on login",
@@ -250,23 +256,63 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             // OpenList Core management
             start_openlist_core,
             stop_openlist_core,
             get_openlist_core_status,
+            // OpenList API
+            get_openlist_api_user,
+            list_openlist_storages,
+            set_openlist_storage_enabled,
+            reload_openlist_storage,
+            delete_openlist_storage,
+            share_local_folder,
+            export_openlist_config,
+            import_openlist_config,
+            list_openlist_tasks,
+            cancel_openlist_task,
+            retry_openlist_task,
+            list_openlist_files,
+            list_openlist_settings,
+            create_openlist_link,
+            // OpenList users
+            list_openlist_users,
+            create_openlist_user,
+            update_openlist_user,
+            reset_openlist_user_password,
+            clear_openlist_user_2fa,
+            // OpenList transfers
+            upload_to_openlist,
+            download_from_openlist,
+            list_transfers,
+            pause_transfer,
+            resume_transfer,
+            cancel_transfer,
+            clear_finished_transfers,
             // Rclone availability check
             check_rclone_available,
             // Rclone remotes configuration (direct file management)
             rclone_list_config,
             rclone_list_remotes,
+            rclone_reveal_remote_secret,
+            rclone_backend_schema,
             rclone_create_remote,
             rclone_update_remote,
             rclone_delete_remote,
+            rclone_config_status,
+            unlock_rclone_config,
+            encrypt_rclone_config,
             // Rclone mount process management
             mount_remote,
             unmount_remote,
+            // Rclone VFS cache
+            get_mount_cache_info,
+            purge_mount_cache,
+            refresh_mount_dir,
             check_mount_status,
             get_mount_info_list,
             // File operations
@@ -301,6 +347,9 @@ pub fn run() {
             check_firewall_rule,
             add_firewall_rule,
             remove_firewall_rule,
+            // Network
+            list_network_interfaces,
+            get_network_access_info,
             get_current_version,
             set_auto_check_enabled,
             is_auto_check_enabled
@@ -343,8 +392,16 @@ pub fn run() {
             }
 
             setup_background_update_checker(app_handle);
+            core::task_monitor::start_task_monitor(app_handle);
+            core::mount_monitor::start_mount_monitor(app_handle);
+            core::mount_watchdog::start_mount_watchdog(app_handle);
             let app_handle_clone = app_handle.clone();
             tauri::async_runtime::spawn(async move {
+                // Mounts of rclone processes that died with the last session.
+                #[cfg(target_os = "linux")]
+                if let Err(e) = cmd::rclone_mount::clean_stale_fuse_mounts(|_| true).await {
+                    log::warn!("Failed to clean stale rclone mounts: {e}");
+                }
                 match auto_start_openlist_core_on_login(&app_handle_clone).await {
                     Ok(_) => {
                         log::info!("Auto-start openlist core task completed");
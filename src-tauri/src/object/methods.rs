@@ -1,9 +1,13 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use parking_lot::RwLock;
 use tauri::AppHandle;
 
+use crate::api::client::{ApiError, Credentials, OpenListClient};
+use crate::cmd::openlist_core::core_base_url;
 use crate::conf::config::MergedSettings;
+use crate::core::transfers::TransferQueue;
 use crate::object::structs::AppState;
 
 impl AppState {
@@ -12,6 +16,9 @@ impl AppState {
             app_settings: Arc::new(RwLock::new(None)),
             app_handle: Arc::new(RwLock::new(None)),
             version_cache: Arc::new(RwLock::new(None)),
+            core_health: Arc::new(RwLock::new(HashMap::new())),
+            api_clients: Arc::new(RwLock::new(HashMap::new())),
+            transfers: Arc::new(TransferQueue::default()),
         }
     }
 
@@ -51,4 +58,44 @@ impl AppState {
         let mut app_settings = self.app_settings.write();
         *app_settings = Some(settings);
     }
+
+    /// Returns the API client for a core profile, reusing the cached one (and its
+    /// login token) while the address and credentials stay the same.
+    pub fn api_client(&self, profile: &str) -> Result<OpenListClient, ApiError> {
+        let settings = self.get_settings().ok_or("Failed to read app settings")?;
+        let config = settings.core_profile(profile)?;
+        let base_url = core_base_url(config)?;
+        let credentials = if config.is_remote() {
+            config.remote.as_ref().and_then(|remote| {
+                remote.password.clone().map(|password| Credentials {
+                    username: remote
+                        .username
+                        .clone()
+                        .filter(|name| !name.is_empty())
+                        .unwrap_or_else(|| "admin".into()),
+                    password,
+                })
+            })
+        } else {
+            settings
+                .admin_password(profile)
+                .map(|password| Credentials {
+                    username: "admin".into(),
+                    password: password.to_string(),
+                })
+        }
+        .filter(|credentials| !credentials.password.is_empty());
+
+        if let Some(client) = self.api_clients.read().get(profile)
+            && client.is_for(&base_url, credentials.as_ref())
+        {
+            return Ok(client.clone());
+        }
+        let accept_invalid_certs = !config.is_remote() && config.ssl_enabled;
+        let client = OpenListClient::new(&base_url, credentials, accept_invalid_certs)?;
+        self.api_clients
+            .write()
+            .insert(profile.to_string(), client.clone());
+        Ok(client)
+    }
 }
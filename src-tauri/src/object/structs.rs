@@ -1,20 +1,101 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 
+use crate::api::client::OpenListClient;
 use crate::cmd::os_operate::VersionCache;
 use crate::conf::config::MergedSettings;
+use crate::core::health::{CoreOwnership, HealthHistory};
+use crate::core::rclone_rc::RcloneMountStats;
+use crate::core::transfers::TransferQueue;
 
 #[derive(Debug, Serialize, Clone)]
 pub struct ServiceStatus {
     pub running: bool,
     pub pid: Option<u32>,
     pub port: Option<u16>,
+    pub version: Option<String>,
+    pub started_at: Option<u64>,
+    pub uptime_secs: Option<u64>,
+    pub latency_ms: Option<u64>,
+    pub http_status: Option<u16>,
+    pub ownership: Option<CoreOwnership>,
+    pub availability: Option<f64>,
+    pub command_line: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Clone)]
+pub struct AccessUrl {
+    pub interface: String,
+    pub address: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NetworkAccessInfo {
+    pub bind_address: String,
+    pub local_url: Option<String>,
+    pub lan_urls: Vec<AccessUrl>,
+    pub warnings: Vec<NetworkWarning>,
+}
+
+/// Warnings are sent as codes so the frontend can show them in the UI language.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkWarning {
+    PortUnknown,
+    PlainHttp,
+    NoLanAddress,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SharedFolderUrl {
+    pub interface: Option<String>,
+    pub web_url: String,
+    pub webdav_url: String,
+}
+
+/// Login confined to one shared folder.
+#[derive(Debug, Serialize, Clone)]
+pub struct SharedFolderAccount {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SharedFolder {
+    pub storage_id: u64,
+    pub mount_path: String,
+    pub local_path: String,
+    pub urls: Vec<SharedFolderUrl>,
+    pub account: Option<SharedFolderAccount>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenListLinkKind {
+    /// A `/d` download link, signed when the core requires it.
+    Direct,
+    /// A link to a share created through the core, which can expire and
+    /// require a password.
+    Share,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OpenListLink {
+    pub kind: OpenListLinkKind,
+    pub profile: String,
+    pub path: String,
+    pub url: String,
+    pub alternate_urls: Vec<String>,
+    pub expires_at: Option<String>,
+    pub password_protected: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct RcloneMountInfo {
     pub name: String,
     pub process_id: String,
@@ -22,10 +103,14 @@ pub struct RcloneMountInfo {
     pub mount_point: String,
     pub status: String,
     pub error_msg: Option<String>,
+    pub stats: Option<RcloneMountStats>,
 }
 
 pub struct AppState {
     pub app_settings: Arc<RwLock<Option<MergedSettings>>>,
     pub app_handle: Arc<RwLock<Option<AppHandle>>>,
     pub version_cache: Arc<RwLock<Option<VersionCache>>>,
+    pub core_health: Arc<RwLock<HashMap<String, HealthHistory>>>,
+    pub api_clients: Arc<RwLock<HashMap<String, OpenListClient>>>,
+    pub transfers: Arc<TransferQueue>,
 }
@@ -1,10 +1,15 @@
-use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri::{AppHandle, Manager, WebviewWindow};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_notification::NotificationExt;
 
 use crate::cmd;
-use crate::core::process_manager::ProcessInfo;
-use crate::object::structs::AppState;
+use crate::cmd::openlist_core::core_process_id;
+use crate::conf::config::DEFAULT_CORE_PROFILE;
+use crate::core::process_manager::{PROCESS_MANAGER, ProcessInfo};
+use crate::object::structs::{AppState, OpenListLinkKind};
 
 const ID_QUIT: &str = "quit";
 const ID_SHOW: &str = "show";
@@ -12,6 +17,8 @@ const ID_HIDE: &str = "hide";
 const ID_RESTART_APP: &str = "restart";
 const ID_SERVICE_START: &str = "start_service";
 const ID_SERVICE_STOP: &str = "stop_service";
+const ID_COPY_DIRECT_LINK: &str = "copy_direct_link";
+const ID_COPY_SHARE_LINK: &str = "copy_share_link";
 
 pub fn create_tray(app_handle: &AppHandle) -> tauri::Result<()> {
     let menu = build_menu(app_handle, false)?;
@@ -66,8 +73,48 @@ fn build_menu(app: &AppHandle, is_running: bool) -> tauri::Result<Menu<tauri::Wr
         None::<&str>,
     )?;
 
+    let mut profile_items = Vec::new();
+    let profiles = app
+        .state::<AppState>()
+        .get_settings()
+        .map(|settings| settings.profiles)
+        .unwrap_or_default();
+    for (name, _) in profiles.iter().filter(|(_, config)| !config.is_remote()) {
+        let running = PROCESS_MANAGER.is_running(&core_process_id(name));
+        profile_items.push(MenuItem::with_id(
+            app,
+            format!("{ID_SERVICE_START}:{name}"),
+            format!("启动OpenList ({name})"),
+            !running,
+            None::<&str>,
+        )?);
+        profile_items.push(MenuItem::with_id(
+            app,
+            format!("{ID_SERVICE_STOP}:{name}"),
+            format!("停止OpenList ({name})"),
+            running,
+            None::<&str>,
+        )?);
+    }
+
+    let mut service_items: Vec<&dyn IsMenuItem<tauri::Wry>> = vec![&start_s, &stop_s];
+    service_items.extend(
+        profile_items
+            .iter()
+            .map(|item| item as &dyn IsMenuItem<tauri::Wry>),
+    );
     let service_submenu =
-        Submenu::with_id_and_items(app, "service", "核心控制", true, &[&start_s, &stop_s])?;
+        Submenu::with_id_and_items(app, "service", "核心控制", true, &service_items)?;
+
+    let direct_link_i =
+        MenuItem::with_id(app, ID_COPY_DIRECT_LINK, "复制直链...", true, None::<&str>)?;
+    let share_link_i = MenuItem::with_id(
+        app,
+        ID_COPY_SHARE_LINK,
+        "复制分享链接...",
+        true,
+        None::<&str>,
+    )?;
 
     Menu::with_items(
         app,
@@ -76,6 +123,8 @@ fn build_menu(app: &AppHandle, is_running: bool) -> tauri::Result<Menu<tauri::Wr
             &hide_i,
             &PredefinedMenuItem::separator(app)?,
             &service_submenu,
+            &direct_link_i,
+            &share_link_i,
             &PredefinedMenuItem::separator(app)?,
             &restart_i,
             &quit_i,
@@ -109,11 +158,22 @@ async fn handle_menu_event(app: &AppHandle, id: &str) -> Result<(), String> {
         }
         ID_SERVICE_START | ID_SERVICE_STOP => {
             let action = id.replace("_service", "");
-            handle_core_action(app, &action).await?;
+            handle_core_action(app, &action, None).await?;
             let is_running = action != "stop";
             update_tray_menu(app, is_running).map_err(|e| e.to_string())?;
         }
-        _ => log::warn!("Unhandled menu ID: {id}"),
+        ID_COPY_DIRECT_LINK => copy_link(app, OpenListLinkKind::Direct).await?,
+        ID_COPY_SHARE_LINK => copy_link(app, OpenListLinkKind::Share).await?,
+        _ => match id.split_once(':') {
+            Some((action @ (ID_SERVICE_START | ID_SERVICE_STOP), profile)) => {
+                let action = action.replace("_service", "");
+                handle_core_action(app, &action, Some(profile.to_string())).await?;
+                let default_running =
+                    PROCESS_MANAGER.is_running(&core_process_id(DEFAULT_CORE_PROFILE));
+                update_tray_menu(app, default_running).map_err(|e| e.to_string())?;
+            }
+            _ => log::warn!("Unhandled menu ID: {id}"),
+        },
     }
     Ok(())
 }
@@ -134,11 +194,54 @@ fn get_main_window(app: &AppHandle) -> Option<WebviewWindow> {
     app.get_webview_window("main")
 }
 
-async fn handle_core_action(app: &AppHandle, action: &str) -> Result<ProcessInfo, String> {
+async fn handle_core_action(
+    app: &AppHandle,
+    action: &str,
+    profile: Option<String>,
+) -> Result<ProcessInfo, String> {
     let state = app.state::<AppState>();
     match action {
-        "start" => cmd::openlist_core::start_openlist_core(state.clone()).await,
-        "stop" => cmd::openlist_core::stop_openlist_core(state.clone()).await,
+        "start" => cmd::openlist_core::start_openlist_core(profile, state.clone())
+            .await
+            .map_err(Into::into),
+        "stop" => cmd::openlist_core::stop_openlist_core(profile, state.clone()).await,
         _ => Err(format!("Unknown core action: {}", action)),
     }
 }
+
+/// Asks for a file inside an rclone mount and puts a link to it on the
+/// clipboard.
+async fn copy_link(app: &AppHandle, kind: OpenListLinkKind) -> Result<(), String> {
+    let dialog_app = app.clone();
+    let picked = tauri::async_runtime::spawn_blocking(move || {
+        dialog_app
+            .dialog()
+            .file()
+            .set_title("选择挂载目录中的文件")
+            .blocking_pick_file()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some(path) = picked else {
+        return Ok(());
+    };
+
+    let state = app.state::<AppState>();
+    let result = cmd::share_link::create_link(&state, &path.to_string(), kind, None, None, None)
+        .await
+        .map_err(String::from)
+        .and_then(|link| {
+            app.clipboard()
+                .write_text(link.url.clone())
+                .map_err(|e| format!("Failed to copy link: {e}"))
+                .map(|_| link.url)
+        });
+    let (title, body) = match &result {
+        Ok(url) => ("链接已复制", url.clone()),
+        Err(e) => ("生成链接失败", e.clone()),
+    };
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("Failed to show link notification: {e}");
+    }
+    result.map(|_| ())
+}
@@ -37,7 +37,7 @@ pub fn remove_network_mode_flags(args: Vec<String>) -> (Vec<String>, Option<bool
     (filtered, network_mode)
 }
 
-fn quote_arg(arg: &str) -> String {
+pub fn quote_arg(arg: &str) -> String {
     if arg
         .chars()
         .all(|ch| !ch.is_whitespace() && !matches!(ch, '\\' | '\'' | '"'))
@@ -148,9 +148,57 @@ pub fn split_args_vec(args: Vec<String>) -> Vec<String> {
     result
 }
 
+/// Flags the desktop app passes to `openlist server` itself.
+const MANAGED_CORE_FLAGS: &[&str] = &["--data"];
+
+pub fn validate_core_args(groups: &[String]) -> Result<Vec<String>, String> {
+    let args = split_args_vec(groups.to_vec());
+    for arg in &args {
+        if arg == "--" {
+            break;
+        }
+        if let Some(flag) = MANAGED_CORE_FLAGS
+            .iter()
+            .find(|flag| arg == *flag || arg.starts_with(&format!("{flag}=")))
+        {
+            return Err(format!(
+                "{flag} is managed by OpenList Desktop and cannot be set as an extra argument"
+            ));
+        }
+    }
+    Ok(args)
+}
+
+pub fn validate_env_vars<'a>(
+    vars: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> Result<(), String> {
+    for (key, value) in vars {
+        if key.is_empty() || key.contains(['=', '\0']) || key.chars().any(char::is_whitespace) {
+            return Err(format!("Invalid environment variable name: '{key}'"));
+        }
+        if value.contains('\0') {
+            return Err(format!("Environment variable '{key}' contains a NUL byte"));
+        }
+    }
+    Ok(())
+}
+
+pub fn format_command_line(bin_path: &str, args: &[String]) -> String {
+    std::iter::once(bin_path)
+        .chain(args.iter().map(String::as_str))
+        .map(quote_arg)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{remove_network_mode_flags, remove_network_mode_flags_from_groups, split_args_vec};
+    use std::collections::HashMap;
+
+    use super::{
+        format_command_line, remove_network_mode_flags, remove_network_mode_flags_from_groups,
+        split_args_vec, validate_core_args, validate_env_vars,
+    };
 
     #[test]
     fn removes_only_managed_network_mode_flags() {
@@ -236,4 +284,38 @@ mod tests {
         assert_eq!(filtered, vec!["--read-only"]);
         assert_eq!(network_mode, None);
     }
+
+    #[test]
+    fn splits_core_args_and_rejects_managed_flags() {
+        let args = validate_core_args(&["--debug --log-std".into(), "--no-prefix".into()]).unwrap();
+        assert_eq!(args, vec!["--debug", "--log-std", "--no-prefix"]);
+
+        assert!(validate_core_args(&["--data /tmp/other".into()]).is_err());
+        assert!(validate_core_args(&["--data=/tmp/other".into()]).is_err());
+        assert!(validate_core_args(&["--data-extra".into()]).is_ok());
+        assert!(validate_core_args(&["-- --data".into()]).is_ok());
+    }
+
+    #[test]
+    fn rejects_malformed_env_var_names() {
+        let valid = HashMap::from([("HTTP_PROXY".to_string(), "http://proxy:8080".to_string())]);
+        assert!(validate_env_vars(&valid).is_ok());
+
+        for key in ["", "A=B", "WITH SPACE"] {
+            let vars = HashMap::from([(key.to_string(), "1".to_string())]);
+            assert!(
+                validate_env_vars(&vars).is_err(),
+                "{key:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn quotes_command_line_arguments_with_spaces() {
+        let args = vec!["server".into(), "--data".into(), "/srv/open list".into()];
+        assert_eq!(
+            format_command_line("/usr/bin/openlist", &args),
+            "/usr/bin/openlist server --data \"/srv/open list\""
+        );
+    }
 }
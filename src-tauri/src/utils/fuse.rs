@@ -0,0 +1,245 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+const MOUNTINFO: &str = "/proc/self/mountinfo";
+const RCLONE_FS_TYPE: &str = "fuse.rclone";
+const FUSERMOUNT: [&str; 2] = ["fusermount3", "fusermount"];
+/// A live but hung mount can block `stat` for good.
+const STAT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A FUSE mount made by rclone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuseMount {
+    /// The mounted fs, like `nas:` or `nas:/media`.
+    pub source: String,
+    pub mount_point: PathBuf,
+}
+
+/// Undoes the octal escapes the kernel uses for spaces, tabs, newlines and
+/// backslashes in mountinfo fields.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'\\')
+            .then(|| bytes.get(i + 1..i + 4))
+            .flatten()
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn parse_mountinfo(content: &str) -> Vec<FuseMount> {
+    content
+        .lines()
+        .filter_map(|line| {
+            // Optional fields make the count vary, so the fs type and source
+            // are found after the " - " separator.
+            let (mount, fs) = line.split_once(" - ")?;
+            let mount_point = mount.split(' ').nth(4)?;
+            let mut fs = fs.split(' ');
+            if fs.next()? != RCLONE_FS_TYPE {
+                return None;
+            }
+            Some(FuseMount {
+                source: unescape(fs.next()?),
+                mount_point: PathBuf::from(unescape(mount_point)),
+            })
+        })
+        .collect()
+}
+
+pub fn rclone_mounts() -> Vec<FuseMount> {
+    match std::fs::read_to_string(MOUNTINFO) {
+        Ok(content) => parse_mountinfo(&content),
+        Err(e) => {
+            log::warn!("Failed to read {MOUNTINFO}: {e}");
+            Vec::new()
+        }
+    }
+}
+
+/// Mount points with a `stat` still running. A hung mount never answers, so
+/// it gets one probe thread rather than a new one every check.
+static PENDING_PROBES: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// Marks a mount point as being probed until dropped.
+struct PendingProbe(PathBuf);
+
+impl PendingProbe {
+    fn start(mount_point: &Path) -> Option<Self> {
+        PENDING_PROBES
+            .lock()
+            .get_or_insert_with(HashSet::new)
+            .insert(mount_point.to_path_buf())
+            .then(|| Self(mount_point.to_path_buf()))
+    }
+}
+
+impl Drop for PendingProbe {
+    fn drop(&mut self) {
+        if let Some(pending) = PENDING_PROBES.lock().as_mut() {
+            pending.remove(&self.0);
+        }
+    }
+}
+
+/// Whether the rclone process serving `mount_point` is gone, leaving
+/// "Transport endpoint is not connected" behind. A stale mount answers at
+/// once, so one that is still being probed is hung rather than stale.
+fn is_stale(mount_point: &Path) -> bool {
+    let Some(probe) = PendingProbe::start(mount_point) else {
+        return false;
+    };
+    let (tx, rx) = mpsc::channel();
+    let path = mount_point.to_path_buf();
+    thread::spawn(move || {
+        let _probe = probe;
+        let _ = tx.send(std::fs::metadata(path));
+    });
+    matches!(
+        rx.recv_timeout(STAT_TIMEOUT),
+        Ok(Err(e)) if e.kind() == io::ErrorKind::NotConnected
+    )
+}
+
+/// `path` the way mountinfo lists it: absolute, with symlinks resolved. Only
+/// the parent is resolved, since a stale mount point itself can't be read.
+pub fn mountinfo_path(path: &Path) -> PathBuf {
+    let Ok(path) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map(|parent| parent.join(name))
+            .unwrap_or(path),
+        _ => path,
+    }
+}
+
+/// Unmounts with whichever fusermount is installed, lazily if the mount
+/// point is still busy.
+pub fn unmount(mount_point: &Path) -> Result<(), String> {
+    let mut last_error = None;
+    for flag in ["-u", "-uz"] {
+        for bin in FUSERMOUNT {
+            match Command::new(bin).arg(flag).arg(mount_point).output() {
+                Ok(output) if output.status.success() => return Ok(()),
+                Ok(output) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    last_error = Some(format!("{bin} {flag} failed: {}", stderr.trim()));
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => last_error = Some(format!("Failed to run {bin}: {e}")),
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| "Neither fusermount3 nor fusermount is installed".into()))
+}
+
+/// Unmounts the stale rclone mounts `matches` picks and returns their mount
+/// points.
+pub fn clean_stale_mounts(matches: impl Fn(&FuseMount) -> bool) -> Result<Vec<PathBuf>, String> {
+    let mut cleaned = Vec::new();
+    let mut errors = Vec::new();
+    for mount in rclone_mounts()
+        .into_iter()
+        .filter(|mount| matches(mount) && is_stale(&mount.mount_point))
+    {
+        match unmount(&mount.mount_point) {
+            Ok(()) => {
+                log::info!(
+                    "Unmounted stale rclone mount of '{}' at {}",
+                    mount.source,
+                    mount.mount_point.display()
+                );
+                cleaned.push(mount.mount_point);
+            }
+            Err(e) => errors.push(format!(
+                "Failed to unmount stale mount {}: {e}",
+                mount.mount_point.display()
+            )),
+        }
+    }
+    if errors.is_empty() {
+        Ok(cleaned)
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{FuseMount, PendingProbe, mountinfo_path, parse_mountinfo};
+
+    #[test]
+    fn finds_rclone_mounts_in_mountinfo() {
+        let content = "\
+22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+45 22 0:41 / /mnt/nas rw,nosuid,nodev,relatime shared:30 - fuse.rclone nas: rw,user_id=1000,group_id=1000
+46 22 0:42 / /home/me/My\\040Drive rw,nosuid,nodev,relatime - fuse.rclone gdrive:/Shared\\040files rw,user_id=1000
+47 22 0:43 / /mnt/other rw,nosuid,nodev,relatime shared:31 - fuse.sshfs host:/ rw
+";
+        assert_eq!(
+            parse_mountinfo(content),
+            vec![
+                FuseMount {
+                    source: "nas:".into(),
+                    mount_point: PathBuf::from("/mnt/nas"),
+                },
+                FuseMount {
+                    source: "gdrive:/Shared files".into(),
+                    mount_point: PathBuf::from("/home/me/My Drive"),
+                },
+            ]
+        );
+        assert!(parse_mountinfo("garbage\n").is_empty());
+    }
+
+    #[test]
+    fn resolves_mount_points_like_mountinfo() {
+        let root = std::env::temp_dir()
+            .canonicalize()
+            .unwrap()
+            .join(format!("fuse-path-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("real")).unwrap();
+        std::os::unix::fs::symlink(root.join("real"), root.join("link")).unwrap();
+
+        let resolved = mountinfo_path(&root.join("link/nas"));
+        let cwd = std::env::current_dir().unwrap().canonicalize().unwrap();
+        let relative = mountinfo_path(Path::new("nas"));
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(resolved, root.join("real/nas"));
+        assert_eq!(relative, cwd.join("nas"));
+    }
+
+    #[test]
+    fn probes_a_mount_point_once_at_a_time() {
+        let probe = PendingProbe::start(Path::new("/mnt/hung")).unwrap();
+        assert!(PendingProbe::start(Path::new("/mnt/hung")).is_none());
+        drop(probe);
+        assert!(PendingProbe::start(Path::new("/mnt/hung")).is_some());
+    }
+}
@@ -1,4 +1,9 @@
 pub mod args;
+#[cfg(target_os = "linux")]
+pub mod fuse;
 pub mod github_proxy;
 pub mod init_log;
+pub mod network;
 pub mod path;
+pub mod port;
+pub mod secret;
@@ -0,0 +1,91 @@
+use std::net::{IpAddr, SocketAddr};
+
+use serde::Serialize;
+use url::Url;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InterfaceAddress {
+    pub name: String,
+    pub ip: IpAddr,
+    pub is_loopback: bool,
+}
+
+pub fn list_interface_addresses() -> Result<Vec<InterfaceAddress>, String> {
+    let interfaces =
+        if_addrs::get_if_addrs().map_err(|e| format!("Failed to list network interfaces: {e}"))?;
+    let mut addresses: Vec<InterfaceAddress> = interfaces
+        .into_iter()
+        .filter(|iface| !iface.is_link_local())
+        .map(|iface| InterfaceAddress {
+            is_loopback: iface.is_loopback(),
+            ip: iface.ip(),
+            name: iface.name,
+        })
+        .collect();
+    addresses.sort_by_key(|addr| (addr.is_loopback, addr.ip.is_ipv6(), addr.name.clone()));
+    Ok(addresses)
+}
+
+/// Picks the address OpenList should bind to for a named interface, preferring
+/// IPv4 since that is what most LAN clients will use.
+pub fn interface_bind_address(name: &str) -> Result<IpAddr, String> {
+    list_interface_addresses()?
+        .into_iter()
+        .filter(|addr| addr.name == name)
+        .min_by_key(|addr| addr.ip.is_ipv6())
+        .map(|addr| addr.ip)
+        .ok_or_else(|| format!("Network interface '{name}' has no usable address"))
+}
+
+pub fn interface_name_for_ip(ip: IpAddr) -> Option<String> {
+    list_interface_addresses()
+        .ok()?
+        .into_iter()
+        .find(|addr| addr.ip == ip)
+        .map(|addr| addr.name)
+}
+
+pub fn format_url(scheme: &str, ip: IpAddr, port: u16) -> String {
+    format!("{scheme}://{}", SocketAddr::new(ip, port))
+}
+
+/// Appends an OpenList path to a base URL, percent-encoding each segment.
+pub fn join_url_path(base: &str, path: &str) -> Result<String, String> {
+    let mut url = Url::parse(base).map_err(|e| format!("Invalid URL '{base}': {e}"))?;
+    url.path_segments_mut()
+        .map_err(|_| format!("URL '{base}' cannot have a path"))?
+        .pop_if_empty()
+        .extend(path.split('/').filter(|segment| !segment.is_empty()));
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use super::{format_url, join_url_path};
+
+    #[test]
+    fn formats_urls_for_both_address_families() {
+        assert_eq!(
+            format_url("http", IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)), 5244),
+            "http://192.168.1.10:5244"
+        );
+        assert_eq!(
+            format_url("https", IpAddr::V6(Ipv6Addr::LOCALHOST), 5245),
+            "https://[::1]:5245"
+        );
+    }
+
+    #[test]
+    fn joins_and_encodes_paths() {
+        assert_eq!(
+            join_url_path("http://192.168.1.10:5244", "/Team Files/报告").unwrap(),
+            "http://192.168.1.10:5244/Team%20Files/%E6%8A%A5%E5%91%8A"
+        );
+        assert_eq!(
+            join_url_path("https://example.com/dav/", "/share").unwrap(),
+            "https://example.com/dav/share"
+        );
+    }
+}
@@ -83,7 +83,10 @@ pub fn get_openlist_binary_path_with_custom(state: State<'_, AppState>) -> Resul
         .read()
         .clone()
         .ok_or("Failed to read app settings")?;
-    let custom_path = settings.openlist.binary_path;
+    resolve_openlist_binary_path(settings.openlist.binary_path.as_deref())
+}
+
+pub fn resolve_openlist_binary_path(custom_path: Option<&str>) -> Result<PathBuf, String> {
     if let Some(path) = custom_path.filter(|p| !p.is_empty()) {
         let custom = PathBuf::from(path);
         if custom.exists() {
@@ -0,0 +1,215 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+
+use serde::Serialize;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+const PORT_SEARCH_RANGE: u16 = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub name: Option<String>,
+}
+
+pub fn is_port_available(ip: IpAddr, port: u16) -> bool {
+    if !ip.is_unspecified() {
+        return TcpListener::bind(SocketAddr::new(ip, port)).is_ok();
+    }
+    let v4 = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+    if TcpListener::bind(v4).is_err() {
+        return false;
+    }
+    // A dual-stack listener on the same port would still make the core fail to
+    // bind, so check the IPv6 wildcard as well when the host supports it.
+    let v6 = SocketAddr::from((Ipv6Addr::UNSPECIFIED, port));
+    match TcpListener::bind(v6) {
+        Ok(_) => true,
+        Err(e) => e.kind() != std::io::ErrorKind::AddrInUse,
+    }
+}
+
+pub fn find_available_port(ip: IpAddr, start: u16, exclude: &[u16]) -> Option<u16> {
+    (start..=start.saturating_add(PORT_SEARCH_RANGE))
+        .filter(|port| *port != 0 && !exclude.contains(port))
+        .find(|port| is_port_available(ip, *port))
+}
+
+pub fn find_port_owner(port: u16) -> Option<PortOwner> {
+    let pid = find_listening_pid(port)?;
+    let mut sys = System::new();
+    let sys_pid = Pid::from_u32(pid);
+    sys.refresh_processes(ProcessesToUpdate::Some(&[sys_pid]), true);
+    let name = sys
+        .process(sys_pid)
+        .map(|proc| proc.name().to_string_lossy().into_owned());
+    Some(PortOwner { pid, name })
+}
+
+#[cfg(target_os = "linux")]
+fn find_listening_pid(port: u16) -> Option<u32> {
+    use std::fs;
+
+    let mut sockets = Vec::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        if let Ok(content) = fs::read_to_string(table) {
+            sockets.extend(
+                listening_socket_inodes(&content, port)
+                    .into_iter()
+                    .map(|inode| format!("socket:[{inode}]")),
+            );
+        }
+    }
+    if sockets.is_empty() {
+        return None;
+    }
+
+    for entry in fs::read_dir("/proc").ok()?.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            if let Ok(target) = fs::read_link(fd.path())
+                && sockets
+                    .iter()
+                    .any(|socket| target.as_os_str() == socket.as_str())
+            {
+                return Some(pid);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn listening_socket_inodes(content: &str, port: u16) -> Vec<u64> {
+    const TCP_LISTEN: &str = "0A";
+
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (_, local_port) = fields.get(1)?.rsplit_once(':')?;
+            let local_port = u16::from_str_radix(local_port, 16).ok()?;
+            if local_port != port || *fields.get(3)? != TCP_LISTEN {
+                return None;
+            }
+            fields.get(9)?.parse().ok()
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn find_listening_pid(port: u16) -> Option<u32> {
+    let output = std::process::Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{port}"), "-sTCP:LISTEN", "-t"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().parse().ok())
+}
+
+#[cfg(target_os = "windows")]
+fn find_listening_pid(port: u16) -> Option<u32> {
+    use std::os::windows::process::CommandExt;
+
+    let output = std::process::Command::new("netstat")
+        .args(["-ano", "-p", "TCP"])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()?;
+    if let Some(pid) = netstat_listening_pid(&String::from_utf8_lossy(&output.stdout), port) {
+        return Some(pid);
+    }
+    let output = std::process::Command::new("netstat")
+        .args(["-ano", "-p", "TCPv6"])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output()
+        .ok()?;
+    netstat_listening_pid(&String::from_utf8_lossy(&output.stdout), port)
+}
+
+#[cfg(target_os = "windows")]
+fn netstat_listening_pid(output: &str, port: u16) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 5 || !fields[0].starts_with("TCP") || fields[3] != "LISTENING" {
+            return None;
+        }
+        let (_, local_port) = fields[1].rsplit_once(':')?;
+        (local_port.parse::<u16>().ok()? == port)
+            .then(|| fields[4].parse().ok())
+            .flatten()
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn find_listening_pid(_port: u16) -> Option<u32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, TcpListener};
+
+    use super::{find_available_port, is_port_available};
+
+    const ALL: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+    const LOOPBACK: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    #[test]
+    fn detects_ports_held_by_a_listener() {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        assert!(!is_port_available(ALL, port));
+        assert!(!is_port_available(LOOPBACK, port));
+        drop(listener);
+        assert!(is_port_available(ALL, port));
+    }
+
+    #[test]
+    fn skips_busy_and_excluded_ports() {
+        let listener = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let found = find_available_port(ALL, port, &[port + 1]).unwrap();
+
+        assert_ne!(found, port);
+        assert_ne!(found, port + 1);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_listening_sockets_from_proc_net_tcp() {
+        let content = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when \
+                       retrnsmt   uid  timeout inode\n   0: 00000000:147C 00000000:0000 0A \
+                       00000000:00000000 00:00000000 00000000  1000        0 123456 1 \
+                       0000000000000000 100 0 0 10 0\n   1: 0100007F:147C 0100007F:A1B2 01 \
+                       00000000:00000000 00:00000000 00000000  1000        0 654321 1 \
+                       0000000000000000 20 4 30 10 -1\n";
+
+        assert_eq!(super::listening_socket_inodes(content, 5244), vec![123456]);
+        assert!(super::listening_socket_inodes(content, 5245).is_empty());
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn parses_listening_pid_from_netstat() {
+        let output = "\r\nActive Connections\r\n\r\n  Proto  Local Address          Foreign \
+                      Address        State           PID\r\n  TCP    0.0.0.0:5244           \
+                      0.0.0.0:0              LISTENING       4321\r\n  TCP    \
+                      127.0.0.1:5244         127.0.0.1:50000        ESTABLISHED     999\r\n";
+
+        assert_eq!(super::netstat_listening_pid(output, 5244), Some(4321));
+        assert_eq!(super::netstat_listening_pid(output, 5245), None);
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use once_cell::sync::OnceCell;
+use rand::RngCore;
+
+use crate::utils::path::get_app_config_dir;
+
+/// Marks values encrypted by this module in settings.json.
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const KEY_FILE: &str = "secret.key";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+static KEY: OnceCell<[u8; KEY_LEN]> = OnceCell::new();
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Reads the machine-bound key next to the settings, creating it readable
+/// only by the current user on first use.
+fn key() -> Result<&'static [u8; KEY_LEN], String> {
+    KEY.get_or_try_init(|| load_or_create_key(&get_app_config_dir()?.join(KEY_FILE)))
+}
+
+fn load_or_create_key(path: &Path) -> Result<[u8; KEY_LEN], String> {
+    if path.exists() {
+        restrict_permissions(path)?;
+        let bytes = fs::read(path).map_err(|e| format!("Failed to read {path:?}: {e}"))?;
+        return bytes
+            .try_into()
+            .map_err(|_| format!("Secret key {path:?} is corrupted"));
+    }
+
+    let mut key = [0u8; KEY_LEN];
+    rand::rng().fill_bytes(&mut key);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {dir:?}: {e}"))?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("Failed to create {path:?}: {e}"))?;
+    std::io::Write::write_all(&mut file, &key)
+        .map_err(|e| format!("Failed to write {path:?}: {e}"))?;
+    log::info!("Created secret key at {path:?}");
+    Ok(key)
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(path)
+        .map_err(|e| format!("Failed to read {path:?}: {e}"))?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        log::warn!("Secret key {path:?} was accessible to other users, restricting it");
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to restrict {path:?}: {e}"))?;
+    }
+    Ok(())
+}
+
+/// The key lives in the per-user profile directory, which Windows already
+/// limits to its owner.
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn encrypt_with(key: &[u8; KEY_LEN], plain: &str) -> Result<String, String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rng().fill_bytes(&mut nonce);
+    let sealed = cipher
+        .encrypt(Nonce::from_slice(&nonce), plain.as_bytes())
+        .map_err(|_| "Failed to encrypt secret".to_string())?;
+    let mut payload = nonce.to_vec();
+    payload.extend(sealed);
+    Ok(format!("{ENCRYPTED_PREFIX}{}", STANDARD.encode(payload)))
+}
+
+fn decrypt_with(key: &[u8; KEY_LEN], stored: &str) -> Result<String, String> {
+    let encoded = stored
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .ok_or("Secret is not encrypted")?;
+    let payload = STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Encrypted secret is malformed: {e}"))?;
+    if payload.len() < NONCE_LEN {
+        return Err("Encrypted secret is truncated".into());
+    }
+    let (nonce, sealed) = payload.split_at(NONCE_LEN);
+    let plain = ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(nonce), sealed)
+        .map_err(|_| "Failed to decrypt secret, the key may have changed".to_string())?;
+    String::from_utf8(plain).map_err(|e| format!("Decrypted secret is not UTF-8: {e}"))
+}
+
+pub fn encrypt(plain: &str) -> Result<String, String> {
+    encrypt_with(key()?, plain)
+}
+
+pub fn decrypt(stored: &str) -> Result<String, String> {
+    decrypt_with(key()?, stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt_with, encrypt_with, is_encrypted, load_or_create_key};
+
+    #[test]
+    fn round_trips_and_rejects_other_keys() {
+        let key = [7u8; 32];
+        let sealed = encrypt_with(&key, "hunter2").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("hunter2"));
+        assert_ne!(sealed, encrypt_with(&key, "hunter2").unwrap());
+        assert_eq!(decrypt_with(&key, &sealed).unwrap(), "hunter2");
+        assert!(decrypt_with(&[8u8; 32], &sealed).is_err());
+        assert!(decrypt_with(&key, "hunter2").is_err());
+    }
+
+    #[test]
+    fn creates_a_private_key_file_once() {
+        let path = std::env::temp_dir().join(format!("openlist-secret-{}.key", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let created = load_or_create_key(&path).unwrap();
+        let loaded = load_or_create_key(&path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(created, loaded);
+    }
+}
@@ -9,9 +9,98 @@ let pendingUpdateInstance: Awaited<ReturnType<typeof check>> = null
 export class TauriAPI {
   // --- OpenList Core management ---
   static core = {
-    start: (): Promise<ProcessInfo> => invoke('start_openlist_core'),
-    stop: (): Promise<ProcessInfo> => invoke('stop_openlist_core'),
-    getStatus: (): Promise<OpenListCoreStatus> => invoke('get_openlist_core_status'),
+    start: (profile?: string): Promise<ProcessInfo> => invoke('start_openlist_core', { profile }),
+    stop: (profile?: string): Promise<ProcessInfo> => invoke('stop_openlist_core', { profile }),
+    getStatus: (profile?: string): Promise<OpenListCoreStatus> => invoke('get_openlist_core_status', { profile }),
+  }
+
+  // --- OpenList API ---
+  static openlist = {
+    me: (profile?: string): Promise<OpenListUser> => invoke('get_openlist_api_user', { profile }),
+    storages: {
+      list: (profile?: string): Promise<OpenListStorageSummary[]> => invoke('list_openlist_storages', { profile }),
+      setEnabled: (id: number, enabled: boolean, profile?: string): Promise<void> =>
+        invoke('set_openlist_storage_enabled', { id, enabled, profile }),
+      reload: (id: number, profile?: string): Promise<void> => invoke('reload_openlist_storage', { id, profile }),
+      delete: (id: number, profile?: string): Promise<void> => invoke('delete_openlist_storage', { id, profile }),
+    },
+    shareFolder: (
+      localPath: string,
+      mountPath: string,
+      options?: { readOnly?: boolean; shareAccount?: boolean; profile?: string },
+    ): Promise<SharedFolder> =>
+      invoke('share_local_folder', {
+        localPath,
+        mountPath,
+        readOnly: options?.readOnly,
+        shareAccount: options?.shareAccount,
+        profile: options?.profile,
+      }),
+    exportConfig: (
+      path: string,
+      options?: { includeMetas?: boolean; includeUsers?: boolean; stripSecrets?: boolean; profile?: string },
+    ): Promise<number> =>
+      invoke('export_openlist_config', {
+        path,
+        includeMetas: options?.includeMetas,
+        includeUsers: options?.includeUsers,
+        stripSecrets: options?.stripSecrets,
+        profile: options?.profile,
+      }),
+    importConfig: (path: string, conflict?: OpenListConflictPolicy, profile?: string): Promise<OpenListImportReport> =>
+      invoke('import_openlist_config', { path, conflict, profile }),
+    tasks: {
+      list: (done?: boolean, profile?: string): Promise<OpenListTask[]> =>
+        invoke('list_openlist_tasks', { done, profile }),
+      cancel: (kind: OpenListTaskKind, id: string, profile?: string): Promise<void> =>
+        invoke('cancel_openlist_task', { kind, id, profile }),
+      retry: (kind: OpenListTaskKind, id: string, profile?: string): Promise<void> =>
+        invoke('retry_openlist_task', { kind, id, profile }),
+      onUpdate: (cb: (snapshot: OpenListTaskSnapshot) => void) =>
+        listen('openlist-tasks', e => cb(e.payload as OpenListTaskSnapshot)),
+      onFinished: (cb: (task: OpenListFinishedTask) => void) =>
+        listen('openlist-task-finished', e => cb(e.payload as OpenListFinishedTask)),
+    },
+    users: {
+      list: (profile?: string): Promise<OpenListUser[]> => invoke('list_openlist_users', { profile }),
+      create: (user: OpenListNewUser, profile?: string): Promise<OpenListUser> =>
+        invoke('create_openlist_user', { user, profile }),
+      update: (id: number, patch: OpenListUserPatch, profile?: string): Promise<OpenListUser> =>
+        invoke('update_openlist_user', { id, patch, profile }),
+      // Without an id these act on the admin account, which also works while a local core is stopped
+      resetPassword: (id?: number, password?: string, profile?: string): Promise<string> =>
+        invoke('reset_openlist_user_password', { id, password, profile }),
+      clear2fa: (id?: number, profile?: string): Promise<void> => invoke('clear_openlist_user_2fa', { id, profile }),
+    },
+    createLink: (
+      path: string,
+      kind: OpenListLinkKind,
+      options?: { expiresInSecs?: number; password?: string; profile?: string },
+    ): Promise<OpenListLink> =>
+      invoke('create_openlist_link', {
+        path,
+        kind,
+        expiresInSecs: options?.expiresInSecs,
+        password: options?.password,
+        profile: options?.profile,
+      }),
+    transfers: {
+      upload: (localPath: string, remoteDir: string, profile?: string): Promise<Transfer[]> =>
+        invoke('upload_to_openlist', { localPath, remoteDir, profile }),
+      download: (remotePath: string, localDir: string, profile?: string): Promise<Transfer[]> =>
+        invoke('download_from_openlist', { remotePath, localDir, profile }),
+      list: (): Promise<Transfer[]> => invoke('list_transfers'),
+      pause: (id: number): Promise<Transfer> => invoke('pause_transfer', { id }),
+      resume: (id: number): Promise<Transfer> => invoke('resume_transfer', { id }),
+      cancel: (id: number): Promise<Transfer> => invoke('cancel_transfer', { id }),
+      clearFinished: (): Promise<void> => invoke('clear_finished_transfers'),
+      onProgress: (cb: (transfer: Transfer) => void) =>
+        listen('openlist-transfer', e => cb(e.payload as Transfer)),
+    },
+    files: (path: string, refresh?: boolean, profile?: string): Promise<OpenListFsList> =>
+      invoke('list_openlist_files', { path, refresh, profile }),
+    settings: (group?: number, profile?: string): Promise<OpenListSettingItem[]> =>
+      invoke('list_openlist_settings', { group, profile }),
   }
 
   // --- Rclone management ---
@@ -22,12 +111,22 @@ export class TauriAPI {
     // Remote configuration management (direct file-based)
     remotes: {
       list: (): Promise<string[]> => invoke('rclone_list_remotes'),
-      create: (name: string, type: string, config: RcloneWebdavConfig): Promise<boolean> =>
+      create: (name: string, type: string, config: RcloneWebdavConfig | RcloneRemoteOptions): Promise<boolean> =>
         invoke('rclone_create_remote', { name, type, config }),
-      update: (name: string, type: string, config: RcloneWebdavConfig): Promise<boolean> =>
+      update: (name: string, type: string, config: RcloneWebdavConfig | RcloneRemoteOptions): Promise<boolean> =>
         invoke('rclone_update_remote', { name, type, config }),
       delete: (name: string): Promise<boolean> => invoke('rclone_delete_remote', { name }),
       listConfig: (t: string): Promise<IRemoteConfig> => invoke('rclone_list_config', { remoteType: t }),
+      revealSecret: (name: string, field: string): Promise<string> =>
+        invoke('rclone_reveal_remote_secret', { name, field }),
+      schema: (): Promise<RcloneBackend[]> => invoke('rclone_backend_schema'),
+    },
+
+    // Encrypted rclone.conf handling
+    config: {
+      status: (): Promise<RcloneConfigStatus> => invoke('rclone_config_status'),
+      unlock: (password: string): Promise<boolean> => invoke('unlock_rclone_config', { password }),
+      encrypt: (password: string): Promise<boolean> => invoke('encrypt_rclone_config', { password }),
     },
 
     // Mount process management
@@ -36,6 +135,18 @@ export class TauriAPI {
       check: (mp: string): Promise<boolean> => invoke('check_mount_status', { mountPoint: mp }),
       mount: (cfg: MountProcessInput): Promise<ProcessInfo> => invoke('mount_remote', { config: cfg }),
       unmount: (name: string): Promise<boolean> => invoke('unmount_remote', { name }),
+      onStats: (cb: (mounts: RcloneMountInfo[]) => void) =>
+        listen('rclone-mount-stats', e => cb(e.payload as RcloneMountInfo[])),
+      onWatchdog: (cb: (event: RcloneWatchdogEvent) => void) =>
+        listen('rclone-mount-watchdog', e => cb(e.payload as RcloneWatchdogEvent)),
+    },
+
+    // VFS cache of each mount
+    cache: {
+      info: (name: string): Promise<RcloneCacheInfo> => invoke('get_mount_cache_info', { name }),
+      purge: (name: string): Promise<number> => invoke('purge_mount_cache', { name }),
+      refresh: (name: string, dir?: string, recursive = true): Promise<void> =>
+        invoke('refresh_mount_dir', { name, dir, recursive }),
     },
   }
 
@@ -82,6 +193,8 @@ export class TauriAPI {
     defaultDataDir: (): Promise<string> => appDataDir().then(d => join(d, 'openlist-desktop')),
     defaultConfig: (): Promise<string> => appDataDir().then(d => join(d, 'openlist-desktop', 'rclone.conf')),
     selectDirectory: (title: string): Promise<string | null> => invoke('select_directory', { title }),
+    notify: (title: string, body: string): Promise<void> =>
+      invoke('plugin:notification|notify', { options: { title, body } }),
   }
 
   // --- Tray management ---
@@ -101,6 +214,12 @@ export class TauriAPI {
     remove: (): Promise<boolean> => invoke('remove_firewall_rule'),
   }
 
+  // --- Network access ---
+  static network = {
+    interfaces: (): Promise<NetworkInterfaceAddress[]> => invoke('list_network_interfaces'),
+    accessInfo: (): Promise<NetworkAccessInfo> => invoke('get_network_access_info'),
+  }
+
   // --- Update management ---
   static updater = {
     check: async (): Promise<UpdateCheck> => {
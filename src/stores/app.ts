@@ -8,9 +8,23 @@ type ActionFn<T = any> = () => Promise<T>
 const mediaQuery = window.matchMedia('(prefers-color-scheme: dark)')
 let mediaQueryListener: ((e: MediaQueryListEvent) => void) | null = null
 
+/** Count settings are stored as unsigned integers; anything else is unset. */
+const settingCount = (value: unknown) => (Number.isInteger(value) && (value as number) >= 0 ? (value as number) : undefined)
+
 export const useAppStore = defineStore('app', () => {
   const settings = ref<MergedSettings>({
-    openlist: { port: 5244, data_dir: '', auto_launch: false, ssl_enabled: false, binary_path: undefined },
+    openlist: {
+      port: 5244,
+      data_dir: '',
+      auto_launch: false,
+      ssl_enabled: false,
+      auto_select_port: false,
+      bind_mode: 'all',
+      extra_args: [],
+      env: {},
+      connection_mode: 'local',
+      binary_path: undefined,
+    },
     rclone: { binary_path: undefined, rclone_conf_path: undefined, mount_config: {} },
     app: {
       theme: 'light',
@@ -20,10 +34,13 @@ export const useAppStore = defineStore('app', () => {
       open_links_in_browser: false,
       admin_password: undefined,
       show_window_on_startup: true,
+      task_notifications: true,
+      transfer_concurrency: 3,
     },
   })
   const openlistCoreStatus = ref<OpenListCoreStatus>({ running: false })
   const remoteConfigs = ref<IRemoteConfig>({})
+  const rcloneConfigStatus = ref<RcloneConfigStatus>({ encrypted: false, unlocked: true })
   const mountInfos = ref<RcloneMountInfo[]>([])
   const logs = ref<string[]>([])
   const isCoreLoading = ref(false)
@@ -44,27 +61,45 @@ export const useAppStore = defineStore('app', () => {
     extraFlags: [],
     autoMount: false,
     networkMode: false,
+    cacheDir: '',
+    cacheMaxSize: '',
+    cacheMaxAge: '',
+    watchdog: true,
+    watchdogFailedChecks: 2,
+    watchdogMaxRetries: 5,
+    watchdogRetryDelay: 10,
   }
 
   // Computed
   const mountedConfigs = computed(() => mountInfos.value.filter(mount => mount.status === 'mounted'))
 
+  const optionText = (value: unknown) => (value === undefined || value === null ? '' : String(value))
+
   const fullRcloneConfigs = computed<RcloneFormConfig[]>(() => {
     const mountConfig = settings.value.rclone.mount_config
     return Object.entries(remoteConfigs.value).map(([key, config]) => {
       const saved = mountConfig[key]
+      const options = Object.entries(config).filter(([option]) => option !== 'type' && option !== 'secrets')
       return {
         name: key,
-        type: 'webdav',
-        url: config.url,
-        vendor: config.vendor,
-        user: config.user,
-        pass: config.pass,
+        type: config.type,
+        url: optionText(config.url),
+        vendor: optionText(config.vendor),
+        user: optionText(config.user),
+        pass: optionText(config.pass),
+        options: Object.fromEntries(options.map(([option, value]) => [option, optionText(value)])),
         mountPoint: saved?.mountPoint || '',
         volumeName: saved?.volumeName || '',
         extraFlags: saved?.extraFlags || [],
         autoMount: saved?.autoMount ?? false,
         networkMode: saved?.networkMode ?? false,
+        cacheDir: saved?.cacheDir || '',
+        cacheMaxSize: saved?.cacheMaxSize || '',
+        cacheMaxAge: saved?.cacheMaxAge || '',
+        watchdog: saved?.watchdog ?? true,
+        watchdogFailedChecks: saved?.watchdogFailedChecks ?? 2,
+        watchdogMaxRetries: saved?.watchdogMaxRetries ?? 5,
+        watchdogRetryDelay: saved?.watchdogRetryDelay ?? 10,
       }
     })
   })
@@ -128,6 +163,17 @@ export const useAppStore = defineStore('app', () => {
     }
   }
 
+  // Options left out are kept as they are in rclone.conf, empty ones are cleared.
+  function remoteOptions(type: string, config: RcloneFormConfig): RcloneWebdavConfig | RcloneRemoteOptions {
+    if (type !== 'webdav') return { ...config.options }
+    return {
+      url: config.url,
+      vendor: config.vendor || '',
+      user: config.user,
+      pass: config.pass,
+    }
+  }
+
   async function createRemoteConfig(name: string, type: string, config: RcloneFormConfig) {
     try {
       const fullConfig = {
@@ -136,20 +182,21 @@ export const useAppStore = defineStore('app', () => {
         url: config.url,
         vendor: config.vendor || '',
         user: config.user,
-        pass: config.pass,
+        pass: '',
         mountPoint: config.mountPoint || '',
         volumeName: config.volumeName || '',
         extraFlags: config.extraFlags || [],
         autoMount: config.autoMount ?? false,
         networkMode: config.networkMode ?? false,
+        cacheDir: config.cacheDir || '',
+        cacheMaxSize: config.cacheMaxSize || '',
+        cacheMaxAge: config.cacheMaxAge || '',
+        watchdog: config.watchdog ?? true,
+        watchdogFailedChecks: settingCount(config.watchdogFailedChecks),
+        watchdogMaxRetries: settingCount(config.watchdogMaxRetries),
+        watchdogRetryDelay: settingCount(config.watchdogRetryDelay),
       }
-      const createdConfig: RcloneWebdavConfig = {
-        url: fullConfig.url,
-        vendor: fullConfig.vendor || '',
-        user: fullConfig.user,
-        pass: fullConfig.pass,
-      }
-      const result = await TauriAPI.rclone.remotes.create(name, type, createdConfig)
+      const result = await TauriAPI.rclone.remotes.create(name, type, remoteOptions(type, config))
       if (!result) {
         throw new Error('Failed to create remote configuration')
       }
@@ -173,20 +220,21 @@ export const useAppStore = defineStore('app', () => {
         url: config.url,
         vendor: config.vendor || undefined,
         user: config.user,
-        pass: config.pass,
+        pass: '',
         mountPoint: config.mountPoint || undefined,
         volumeName: config.volumeName || undefined,
         extraFlags: config.extraFlags || [],
         autoMount: config.autoMount ?? false,
         networkMode: config.networkMode ?? false,
+        cacheDir: config.cacheDir || undefined,
+        cacheMaxSize: config.cacheMaxSize || undefined,
+        cacheMaxAge: config.cacheMaxAge || undefined,
+        watchdog: config.watchdog ?? true,
+        watchdogFailedChecks: settingCount(config.watchdogFailedChecks),
+        watchdogMaxRetries: settingCount(config.watchdogMaxRetries),
+        watchdogRetryDelay: settingCount(config.watchdogRetryDelay),
       }
-      const updatedConfig: RcloneWebdavConfig = {
-        url: fullConfig.url,
-        vendor: fullConfig.vendor || undefined,
-        user: fullConfig.user,
-        pass: fullConfig.pass,
-      }
-      const result = await TauriAPI.rclone.remotes.update(name, type, updatedConfig)
+      const result = await TauriAPI.rclone.remotes.update(name, type, remoteOptions(type, config))
       if (!result) {
         throw new Error('Failed to update remote configuration')
       }
@@ -226,12 +274,35 @@ export const useAppStore = defineStore('app', () => {
 
   async function loadRemoteConfigs() {
     try {
-      remoteConfigs.value = await TauriAPI.rclone.remotes.listConfig('webdav')
+      rcloneConfigStatus.value = await TauriAPI.rclone.config.status()
+      if (!rcloneConfigStatus.value.unlocked) {
+        remoteConfigs.value = {}
+        return
+      }
+      remoteConfigs.value = await TauriAPI.rclone.remotes.listConfig('')
     } catch (err: any) {
       error.value = 'Failed to load remote configurations'
       console.error('Failed to load remote configs:', err)
     }
   }
+  async function revealRemoteSecret(name: string, field: string): Promise<string> {
+    return TauriAPI.rclone.remotes.revealSecret(name, field)
+  }
+
+  async function getRcloneBackends(): Promise<RcloneBackend[]> {
+    return TauriAPI.rclone.remotes.schema()
+  }
+
+  async function unlockRcloneConfig(password: string) {
+    await TauriAPI.rclone.config.unlock(password)
+    await loadRemoteConfigs()
+  }
+
+  async function encryptRcloneConfig(password: string) {
+    await TauriAPI.rclone.config.encrypt(password)
+    await loadRemoteConfigs()
+  }
+
 
   async function mountRemote(name: string) {
     try {
@@ -268,6 +339,18 @@ export const useAppStore = defineStore('app', () => {
     }
   }
 
+  async function getMountCacheInfo(name: string): Promise<RcloneCacheInfo> {
+    return TauriAPI.rclone.cache.info(name)
+  }
+
+  async function purgeMountCache(name: string): Promise<number> {
+    return TauriAPI.rclone.cache.purge(name)
+  }
+
+  async function refreshMountDir(name: string, dir?: string, recursive = true) {
+    await TauriAPI.rclone.cache.refresh(name, dir, recursive)
+  }
+
   async function unmountRemote(name: string) {
     try {
       await TauriAPI.rclone.mounts.unmount(name)
@@ -523,8 +606,16 @@ export const useAppStore = defineStore('app', () => {
 
   return {
     remoteConfigs,
+    rcloneConfigStatus,
+    revealRemoteSecret,
+    getRcloneBackends,
+    unlockRcloneConfig,
+    encryptRcloneConfig,
     mountInfos,
     mountedConfigs,
+    getMountCacheInfo,
+    purgeMountCache,
+    refreshMountDir,
     mountRemote,
     unmountRemote,
     createRemoteConfig,
@@ -1,12 +1,30 @@
 declare const OS_PLATFORM: Platform
 
-type IRemoteConfig = Record<string, RcloneWebdavConfig>
+type IRemoteConfig = Record<string, RcloneListedRemote>
+
+type BindMode = 'loopback' | 'all' | 'interface'
+
+type ConnectionMode = 'local' | 'remote'
+
+interface RemoteInstanceConfig {
+  url: string
+  username?: string
+  password?: string
+}
 
 interface OpenListCoreConfig {
   port: number
   data_dir: string
   auto_launch: boolean
   ssl_enabled: boolean
+  auto_select_port: boolean
+  bind_mode: BindMode
+  bind_interface?: string
+  extra_args: string[]
+  env: Record<string, string>
+  connection_mode: ConnectionMode
+  remote?: RemoteInstanceConfig
+  admin_password?: string
   binary_path?: string
 }
 
@@ -14,13 +32,62 @@ interface RcloneConfig {
   mount_config: Record<string, RcloneFormConfig>
   binary_path?: string
   rclone_conf_path?: string
+  daemon_mode?: boolean
 }
 
 interface RcloneWebdavConfig {
   url: string
   vendor?: string
   user: string
+  /** Masked when read back from rclone_list_config. */
   pass: string
+  /** Which secret options are set, as returned by rclone_list_config. */
+  secrets?: Record<string, boolean>
+}
+
+interface RcloneConfigStatus {
+  encrypted: boolean
+  unlocked: boolean
+}
+
+/** Options of any rclone backend, keyed by option name. `null` clears an option. */
+type RcloneRemoteOptions = Record<string, string | number | boolean | null | undefined>
+
+/** A remote as returned by rclone_list_config, with its secrets masked. */
+interface RcloneListedRemote {
+  type: string
+  /** Which secret options are set. */
+  secrets?: Record<string, boolean>
+  [option: string]: unknown
+}
+
+interface RcloneOptionExample {
+  value: string
+  help: string
+  provider: string
+}
+
+interface RcloneBackendOption {
+  name: string
+  help: string
+  provider: string
+  default_str: string
+  option_type: string
+  examples?: RcloneOptionExample[] | null
+  required: boolean
+  is_password: boolean
+  sensitive: boolean
+  advanced: boolean
+  exclusive: boolean
+  hide: number
+}
+
+interface RcloneBackend {
+  name: string
+  description: string
+  prefix: string
+  options: RcloneBackendOption[]
+  hide: boolean
 }
 
 interface RcloneFormConfig {
@@ -35,6 +102,39 @@ interface RcloneFormConfig {
   extraFlags?: string[]
   autoMount: boolean
   networkMode: boolean
+  cacheDir?: string
+  cacheMaxSize?: string
+  cacheMaxAge?: string
+  watchdog?: boolean
+  watchdogFailedChecks?: number
+  watchdogMaxRetries?: number
+  /** Seconds before the first retry, doubled after each one. */
+  watchdogRetryDelay?: number
+  /** rclone.conf options of backends other than WebDAV. */
+  options?: Record<string, string>
+}
+
+interface RcloneWatchdogEvent {
+  name: string
+  kind: 'remounting' | 'remount_failed' | 'recovered' | 'gave_up'
+  attempt: number
+  max_retries: number
+  error?: string
+}
+
+interface RcloneCacheInfo {
+  name: string
+  cache_dir: string
+  data_dir: string
+  meta_dir: string
+  /** Bytes on disk. */
+  size: number
+  files: number
+  /** Cached files not uploaded yet. */
+  dirty_files: number
+  max_size?: string
+  max_age?: string
+  mounted: boolean
 }
 
 interface RcloneMountInfo {
@@ -44,6 +144,21 @@ interface RcloneMountInfo {
   mountPoint: string
   status: 'mounted' | 'unmounted' | 'error'
   error_msg?: string
+  stats?: RcloneMountStats
+}
+
+interface RcloneMountStats {
+  /** Bytes per second. */
+  speed: number
+  transfers: number
+  queued_uploads: number
+  uploads_in_progress: number
+  cache_size: number
+  cache_files: number
+  errors: number
+  last_error?: string
+  /** Transfer figures cover every mount of the shared daemon. */
+  shared: boolean
 }
 
 interface AppConfig {
@@ -57,18 +172,50 @@ interface AppConfig {
   log_filter_level?: string
   log_filter_source?: string
   hide_dock_icon?: boolean
+  task_notifications?: boolean
+  transfer_concurrency?: number
 }
 
 interface MergedSettings {
   openlist: OpenListCoreConfig
   rclone: RcloneConfig
   app: AppConfig
+  profiles?: Record<string, OpenListCoreConfig>
+}
+
+interface NetworkInterfaceAddress {
+  name: string
+  ip: string
+  is_loopback: boolean
+}
+
+interface AccessUrl {
+  interface: string
+  address: string
+  url: string
+}
+
+type NetworkWarning = 'port_unknown' | 'plain_http' | 'no_lan_address'
+
+interface NetworkAccessInfo {
+  bind_address: string
+  local_url?: string
+  lan_urls: AccessUrl[]
+  warnings: NetworkWarning[]
 }
 
 interface OpenListCoreStatus {
   running: boolean
   pid?: number
   port?: number
+  version?: string
+  started_at?: number
+  uptime_secs?: number
+  latency_ms?: number
+  http_status?: number
+  ownership?: 'managed' | 'adopted' | 'external' | 'remote'
+  availability?: number
+  command_line?: string
 }
 
 // ProcessConfig for creating/registering processes
@@ -124,3 +271,200 @@ interface DownloadProgress {
   percentage: number
   speed: number
 }
+
+interface OpenListPage<T> {
+  content: T[]
+  total: number
+}
+
+interface OpenListUser {
+  id: number
+  username: string
+  base_path: string
+  role: number
+  disabled: boolean
+  permission: number
+  otp: boolean
+}
+
+interface OpenListNewUser {
+  username: string
+  password: string
+  base_path?: string
+  permission?: number
+  disabled?: boolean
+}
+
+interface OpenListUserPatch {
+  base_path?: string
+  permission?: number
+  disabled?: boolean
+  password?: string
+}
+
+interface OpenListStorage {
+  id: number
+  mount_path: string
+  order: number
+  driver: string
+  cache_expiration: number
+  status: string
+  addition: string
+  remark: string
+  modified: string
+  disabled: boolean
+  enable_sign: boolean
+  order_by: string
+  order_direction: string
+  extract_folder: string
+  web_proxy: boolean
+  webdav_policy: string
+  down_proxy_url: string
+}
+
+interface OpenListStorageSummary {
+  id: number
+  mount_path: string
+  driver: string
+  order: number
+  remark: string
+  disabled: boolean
+  status: string
+  healthy: boolean
+  error?: string
+}
+
+interface OpenListFsObject {
+  name: string
+  size: number
+  is_dir: boolean
+  modified: string
+  sign: string
+  thumb: string
+  type: number
+}
+
+interface OpenListFsList {
+  content: OpenListFsObject[]
+  total: number
+  readme: string
+  write: boolean
+  provider: string
+}
+
+interface OpenListSettingItem {
+  key: string
+  value: string
+  help: string
+  type: string
+  options: string
+  group: number
+  flag: number
+}
+
+type OpenListTaskKind =
+  | 'upload'
+  | 'copy'
+  | 'move'
+  | 'offline_download'
+  | 'offline_download_transfer'
+  | 'decompress'
+  | 'decompress_upload'
+
+interface OpenListTask {
+  kind: OpenListTaskKind
+  id: string
+  name: string
+  creator: string
+  state: number
+  status: string
+  progress: number
+  start_time?: string
+  end_time?: string
+  total_bytes: number
+  error: string
+}
+
+interface OpenListTaskSnapshot {
+  profile: string
+  tasks: OpenListTask[]
+}
+
+interface OpenListFinishedTask {
+  profile: string
+  kind: OpenListTaskKind
+  task: Omit<OpenListTask, 'kind'>
+  failed: boolean
+  elapsed_secs: number
+  notify: boolean
+}
+
+type TransferDirection = 'upload' | 'download'
+type TransferState = 'queued' | 'running' | 'paused' | 'completed' | 'failed' | 'canceled'
+
+interface Transfer {
+  id: number
+  profile: string
+  direction: TransferDirection
+  local_path: string
+  remote_path: string
+  total_bytes: number
+  transferred_bytes: number
+  state: TransferState
+  error?: string
+}
+
+type OpenListLinkKind = 'direct' | 'share'
+
+interface OpenListLink {
+  kind: OpenListLinkKind
+  profile: string
+  path: string
+  url: string
+  alternate_urls: string[]
+  expires_at?: string
+  password_protected: boolean
+}
+
+type OpenListConflictPolicy = 'skip' | 'replace' | 'rename'
+
+interface OpenListImportFailure {
+  kind: 'storage' | 'meta' | 'user'
+  name: string
+  error: string
+}
+
+interface OpenListGeneratedPassword {
+  username: string
+  password: string
+}
+
+interface OpenListImportReport {
+  created: string[]
+  replaced: string[]
+  renamed: [string, string][]
+  skipped: string[]
+  failed: OpenListImportFailure[]
+  without_secrets: string[]
+  generated_passwords: OpenListGeneratedPassword[]
+}
+
+interface SharedFolderUrl {
+  interface?: string
+  web_url: string
+  webdav_url: string
+}
+
+/** Login confined to one shared folder. */
+interface SharedFolderAccount {
+  username: string
+  password: string
+}
+
+interface SharedFolder {
+  storage_id: number
+  mount_path: string
+  local_path: string
+  urls: SharedFolderUrl[]
+  account?: SharedFolderAccount
+}
@@ -4,12 +4,18 @@ export const DEFAULT_CONFIG = {
     data_dir: '',
     auto_launch: false,
     ssl_enabled: false,
+    auto_select_port: false,
+    bind_mode: 'all',
+    extra_args: [],
+    env: {},
+    connection_mode: 'local',
     binary_path: '',
   },
   rclone: {
     mount_config: {},
     binary_path: '',
     rclone_conf_path: '',
+    daemon_mode: false,
   },
   app: {
     theme: 'light',
@@ -19,9 +25,32 @@ export const DEFAULT_CONFIG = {
     open_links_in_browser: false,
     show_window_on_startup: true,
     hide_dock_icon: false,
+    task_notifications: true,
+    transfer_concurrency: 3,
     admin_password: '',
   },
 }
 export const isWindows = typeof OS_PLATFORM !== 'undefined' && OS_PLATFORM === 'win32'
 export const isLinux = typeof OS_PLATFORM !== 'undefined' && OS_PLATFORM === 'linux'
 export const isMacOs = typeof OS_PLATFORM !== 'undefined' && OS_PLATFORM === 'darwin'
+
+// Shown by the backend in place of stored rclone secrets; saving it back keeps them
+export const RCLONE_SECRET_MASK = '••••••••'
+
+// Permission bits of OpenList users, in bit order
+export const OPENLIST_USER_PERMISSIONS = [
+  'see_hides',
+  'access_without_password',
+  'offline_download',
+  'write',
+  'rename',
+  'move',
+  'copy',
+  'remove',
+  'webdav_read',
+  'webdav_manage',
+  'ftp_read',
+  'ftp_write',
+  'read_archives',
+  'decompress',
+] as const